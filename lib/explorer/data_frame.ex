@@ -525,13 +525,19 @@ defmodule Explorer.DataFrame do
       If present, only these columns are read into the dataframe. (default: `nil`)
 
     * `:infer_schema_length` Maximum number of rows read for schema inference.
-      Setting this to nil will do a full table scan and will be slow (default: `1000`).
+      Setting this to nil will do a full table scan and will be slow, and setting
+      it to `0` will read all columns as strings (default: `1000`).
 
     * `:parse_dates` - Automatically try to parse dates/ datetimes and time.
       If parsing fails, columns remain of dtype `string`
 
     * `:eol_delimiter` - A single character used to represent new lines. (default: `"\n"`)
 
+    * `:encoding` - The encoding of the file. Can be one of `"utf8"`, `"utf8-lossy"`,
+      `"latin1"`, `"windows-1252"`, `"utf16"`, `"utf16-le"` or `"utf16-be"`. Files that
+      are not UTF-8 are transcoded before parsing. With `"utf16"`, the endianness is
+      detected from the BOM. (default: `"utf8"`)
+
     * `:config` - An optional struct, keyword list or map, normally associated with remote
       file systems. See [IO section](#module-io-operations) for more details. (default: `nil`)
 
@@ -615,9 +621,10 @@ defmodule Explorer.DataFrame do
     * `:nil_values` - A list of strings that should be interpreted as a nil values. (default: `[]`)
     * `:skip_rows` - The number of lines to skip at the beginning of the file. (default: `0`)
     * `:columns` - A list of column names or indexes to keep. If present, only these columns are read into the dataframe. (default: `nil`)
-    * `:infer_schema_length` Maximum number of rows read for schema inference. Setting this to nil will do a full table scan and will be slow (default: `1000`).
+    * `:infer_schema_length` Maximum number of rows read for schema inference. Setting this to nil will do a full table scan and will be slow, and setting it to `0` will read all columns as strings (default: `1000`).
     * `:parse_dates` - Automatically try to parse dates/ datetimes and time. If parsing fails, columns remain of dtype `string`
     * `:eol_delimiter` - A single character used to represent new lines. (default: `"\n"`)
+    * `:encoding` - The encoding of the contents. Can be one of `"utf8"`, `"utf8-lossy"`, `"latin1"`, `"windows-1252"`, `"utf16"`, `"utf16-le"` or `"utf16-be"`. (default: `"utf8"`)
     * `:backend` - The Explorer backend to use. Defaults to the value returned by `Explorer.Backend.get/0`.
     * `:lazy` - force the results into the lazy version of the current backend.
   """
@@ -774,11 +781,11 @@ defmodule Explorer.DataFrame do
       such as `{:zstd, 3}`. Supported options are:
 
         * `nil` (uncompressed, default)
-        * `:snappy`
+        * `:snappy` (without levels)
         * `:gzip` (with levels 1-9)
         * `:brotli` (with levels 1-11)
         * `:zstd` (with levels -7-22)
-        * `:lz4raw`.
+        * `:lz4raw` (without levels).
 
     * `:streaming` - Tells the backend if it should use streaming, which means
       that the dataframe is not loaded to the memory at once, and instead it is
@@ -820,6 +827,15 @@ defmodule Explorer.DataFrame do
     end
   end
 
+  for algorithm <- [:snappy, :lz4raw] do
+    defp parquet_compression({unquote(algorithm), nil}), do: {unquote(algorithm), nil}
+
+    defp parquet_compression({unquote(algorithm), level}) do
+      raise ArgumentError,
+            "#{unquote(algorithm)} compression does not support a compression level, got #{inspect(level)}"
+    end
+  end
+
   defp parquet_compression(other) do
     raise ArgumentError, "unsupported :compression #{inspect(other)} for Parquet"
   end
@@ -104,22 +104,20 @@ defmodule Explorer.PolarsBackend.DataFrame do
     {columns, with_projection} = column_names_or_projection(columns)
 
     df =
-      Native.df_from_csv(
-        entry.path,
-        infer_schema_length,
-        header?,
-        max_rows,
-        skip_rows,
-        with_projection,
-        delimiter,
-        true,
-        columns,
-        dtypes,
-        encoding,
-        nil_values,
-        parse_dates,
-        char_byte(eol_delimiter)
-      )
+      Native.df_from_csv(entry.path, %{
+        infer_schema_length: infer_schema_length,
+        has_header: header?,
+        max_rows: max_rows,
+        skip_rows: skip_rows,
+        projection: with_projection,
+        delimiter: delimiter,
+        columns: columns,
+        dtypes: dtypes,
+        encoding: encoding,
+        nil_values: nil_values,
+        parse_dates: parse_dates,
+        eol_delimiter: char_byte(eol_delimiter)
+      })
 
     case df do
       {:ok, df} -> {:ok, Shared.create_dataframe(df)}
@@ -148,7 +146,21 @@ defmodule Explorer.PolarsBackend.DataFrame do
   def to_csv(%DataFrame{data: df}, %Local.Entry{} = entry, header?, delimiter) do
     <<delimiter::utf8>> = delimiter
 
-    case Native.df_to_csv(df, entry.path, header?, delimiter) do
+    case Native.df_to_csv(
+           df,
+           entry.path,
+           header?,
+           delimiter,
+           false,
+           false,
+           nil,
+           nil,
+           nil,
+           nil,
+           true,
+           nil,
+           nil
+         ) do
       {:ok, _} -> :ok
       {:error, error} -> {:error, error}
     end
@@ -158,7 +170,22 @@ defmodule Explorer.PolarsBackend.DataFrame do
   def to_csv(%DataFrame{data: df}, %S3.Entry{} = entry, header?, delimiter) do
     <<delimiter::utf8>> = delimiter
 
-    case Native.df_to_csv_cloud(df, entry, header?, delimiter) do
+    case Native.df_to_csv_cloud(
+           df,
+           entry,
+           header?,
+           delimiter,
+           false,
+           nil,
+           nil,
+           nil,
+           nil,
+           true,
+           nil,
+           nil,
+           false,
+           nil
+         ) do
       {:ok, _} -> :ok
       {:error, error} -> {:error, error}
     end
@@ -166,7 +193,7 @@ defmodule Explorer.PolarsBackend.DataFrame do
 
   @impl true
   def dump_csv(%DataFrame{} = df, header?, <<delimiter::utf8>>) do
-    Native.df_dump_csv(df.data, header?, delimiter)
+    Native.df_dump_csv(df.data, header?, delimiter, false, nil, nil, nil, nil, true, nil, nil)
   end
 
   @impl true
@@ -211,7 +238,14 @@ defmodule Explorer.PolarsBackend.DataFrame do
         encoding,
         nil_values,
         parse_dates,
-        char_byte(eol_delimiter)
+        char_byte(eol_delimiter),
+        false,
+        false,
+        [],
+        [],
+        nil,
+        false,
+        true
       )
 
     case df do
@@ -241,28 +275,37 @@ defmodule Explorer.PolarsBackend.DataFrame do
 
   @impl true
   def from_ndjson(%Local.Entry{} = entry, infer_schema_length, batch_size) do
-    with {:ok, df} <- Native.df_from_ndjson(entry.path, infer_schema_length, batch_size) do
+    with {:ok, {df, _skipped}} <-
+           Native.df_from_ndjson(
+             entry.path,
+             infer_schema_length,
+             batch_size,
+             nil,
+             false,
+             0,
+             []
+           ) do
       {:ok, Shared.create_dataframe(df)}
     end
   end
 
   @impl true
   def to_ndjson(%DataFrame{data: df}, %Local.Entry{} = entry) do
-    with {:ok, _} <- Native.df_to_ndjson(df, entry.path) do
+    with {:ok, _} <- Native.df_to_ndjson(df, entry.path, nil) do
       :ok
     end
   end
 
   @impl true
   def to_ndjson(%DataFrame{data: df}, %S3.Entry{} = entry) do
-    with {:ok, _} <- Native.df_to_ndjson_cloud(df, entry) do
+    with {:ok, _} <- Native.df_to_ndjson_cloud(df, entry, false, nil) do
       :ok
     end
   end
 
   @impl true
   def dump_ndjson(%DataFrame{} = df) do
-    Native.df_dump_ndjson(df.data)
+    Native.df_dump_ndjson(df.data, nil)
   end
 
   @impl true
@@ -278,7 +321,7 @@ defmodule Explorer.PolarsBackend.DataFrame do
     # We first read using a lazy dataframe, then we collect.
     with {:ok, ldf} <- Native.lf_from_parquet_cloud(entry, max_rows, columns),
          {:ok, df} <- Native.lf_collect(ldf) do
-      {:ok, Shared.create_dataframe(df)}
+      Shared.create_dataframe_from_file(df)
     end
   end
 
@@ -305,12 +348,17 @@ defmodule Explorer.PolarsBackend.DataFrame do
         entry.path,
         max_rows,
         columns,
-        with_projection
+        with_projection,
+        true,
+        [],
+        "auto",
+        nil,
+        false,
+        0
       )
 
-    case df do
-      {:ok, df} -> {:ok, Shared.create_dataframe(df)}
-      {:error, error} -> {:error, error}
+    with {:ok, df} <- df do
+      Shared.create_dataframe_from_file(df)
     end
   end
 
@@ -321,7 +369,15 @@ defmodule Explorer.PolarsBackend.DataFrame do
         {compression, compression_level},
         _streaming
       ) do
-    case Native.df_to_parquet(df, entry.path, parquet_compression(compression, compression_level)) do
+    case Native.df_to_parquet(
+           df,
+           entry.path,
+           parquet_compression(compression, compression_level),
+           nil,
+           false,
+           [],
+           "dictionary"
+         ) do
       {:ok, _} -> :ok
       {:error, error} -> {:error, error}
     end
@@ -337,7 +393,10 @@ defmodule Explorer.PolarsBackend.DataFrame do
     case Native.df_to_parquet_cloud(
            df,
            entry,
-           parquet_compression(compression, compression_level)
+           parquet_compression(compression, compression_level),
+           nil,
+           false,
+           nil
          ) do
       {:ok, _} -> :ok
       {:error, error} -> {:error, error}
@@ -346,7 +405,7 @@ defmodule Explorer.PolarsBackend.DataFrame do
 
   @impl true
   def dump_parquet(%DataFrame{data: df}, {compression, compression_level}) do
-    Native.df_dump_parquet(df, parquet_compression(compression, compression_level))
+    Native.df_dump_parquet(df, parquet_compression(compression, compression_level), nil, false)
   end
 
   defp parquet_compression(nil, _), do: :uncompressed
@@ -355,13 +414,12 @@ defmodule Explorer.PolarsBackend.DataFrame do
     {algorithm, level}
   end
 
-  defp parquet_compression(algorithm, _) when algorithm in ~w(snappy lz4raw)a, do: algorithm
+  defp parquet_compression(algorithm, nil) when algorithm in ~w(snappy lz4raw)a, do: algorithm
 
   @impl true
   def load_parquet(contents) when is_binary(contents) do
-    case Native.df_load_parquet(contents) do
-      {:ok, df} -> {:ok, Shared.create_dataframe(df)}
-      {:error, error} -> {:error, error}
+    with {:ok, df} <- Native.df_load_parquet(contents) do
+      Shared.create_dataframe_from_file(df)
     end
   end
 
@@ -383,15 +441,14 @@ defmodule Explorer.PolarsBackend.DataFrame do
   def from_ipc(%Local.Entry{} = entry, columns) do
     {columns, projection} = column_names_or_projection(columns)
 
-    case Native.df_from_ipc(entry.path, columns, projection) do
-      {:ok, df} -> {:ok, Shared.create_dataframe(df)}
-      {:error, error} -> {:error, error}
+    with {:ok, df} <- Native.df_from_ipc(entry.path, columns, projection, true, [], 0) do
+      Shared.create_dataframe_from_file(df)
     end
   end
 
   @impl true
   def to_ipc(%DataFrame{data: df}, %Local.Entry{} = entry, {compression, _level}, _streaming) do
-    case Native.df_to_ipc(df, entry.path, maybe_atom_to_string(compression)) do
+    case Native.df_to_ipc(df, entry.path, maybe_atom_to_string(compression), nil, [], []) do
       {:ok, _} -> :ok
       {:error, error} -> {:error, error}
     end
@@ -399,7 +456,7 @@ defmodule Explorer.PolarsBackend.DataFrame do
 
   @impl true
   def to_ipc(%DataFrame{data: df}, %S3.Entry{} = entry, {compression, _level}, _streaming) do
-    case Native.df_to_ipc_cloud(df, entry, maybe_atom_to_string(compression)) do
+    case Native.df_to_ipc_cloud(df, entry, maybe_atom_to_string(compression), false, nil) do
       {:ok, _} -> :ok
       {:error, error} -> {:error, error}
     end
@@ -407,16 +464,15 @@ defmodule Explorer.PolarsBackend.DataFrame do
 
   @impl true
   def dump_ipc(%DataFrame{data: df}, {compression, _level}) do
-    Native.df_dump_ipc(df, maybe_atom_to_string(compression))
+    Native.df_dump_ipc(df, maybe_atom_to_string(compression), [], [])
   end
 
   @impl true
   def load_ipc(contents, columns) when is_binary(contents) do
     {columns, projection} = column_names_or_projection(columns)
 
-    case Native.df_load_ipc(contents, columns, projection) do
-      {:ok, df} -> {:ok, Shared.create_dataframe(df)}
-      {:error, error} -> {:error, error}
+    with {:ok, df} <- Native.df_load_ipc(contents, columns, projection) do
+      Shared.create_dataframe_from_file(df)
     end
   end
 
@@ -438,9 +494,8 @@ defmodule Explorer.PolarsBackend.DataFrame do
   def from_ipc_stream(%Local.Entry{} = entry, columns) do
     {columns, projection} = column_names_or_projection(columns)
 
-    case Native.df_from_ipc_stream(entry.path, columns, projection) do
-      {:ok, df} -> {:ok, Shared.create_dataframe(df)}
-      {:error, error} -> {:error, error}
+    with {:ok, df} <- Native.df_from_ipc_stream(entry.path, columns, projection, 0) do
+      Shared.create_dataframe_from_file(df)
     end
   end
 
@@ -454,7 +509,13 @@ defmodule Explorer.PolarsBackend.DataFrame do
 
   @impl true
   def to_ipc_stream(%DataFrame{data: df}, %S3.Entry{} = entry, {compression, _level}) do
-    case Native.df_to_ipc_stream_cloud(df, entry, maybe_atom_to_string(compression)) do
+    case Native.df_to_ipc_stream_cloud(
+           df,
+           entry,
+           maybe_atom_to_string(compression),
+           false,
+           nil
+         ) do
       {:ok, _} -> :ok
       {:error, error} -> {:error, error}
     end
@@ -469,9 +530,8 @@ defmodule Explorer.PolarsBackend.DataFrame do
   def load_ipc_stream(contents, columns) when is_binary(contents) do
     {columns, projection} = column_names_or_projection(columns)
 
-    case Native.df_load_ipc_stream(contents, columns, projection) do
-      {:ok, df} -> {:ok, Shared.create_dataframe(df)}
-      {:error, error} -> {:error, error}
+    with {:ok, df} <- Native.df_load_ipc_stream(contents, columns, projection) do
+      Shared.create_dataframe_from_file(df)
     end
   end
 
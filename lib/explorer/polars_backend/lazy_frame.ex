@@ -147,17 +147,15 @@ defmodule Explorer.PolarsBackend.LazyFrame do
 
   @impl true
   def from_parquet(%S3.Entry{} = entry, max_rows, columns) do
-    case Native.lf_from_parquet_cloud(entry, max_rows, columns) do
-      {:ok, df} -> {:ok, Shared.create_dataframe(df)}
-      {:error, error} -> {:error, error}
+    with {:ok, df} <- Native.lf_from_parquet_cloud(entry, max_rows, columns) do
+      Shared.create_dataframe_from_file(df)
     end
   end
 
   @impl true
   def from_parquet(%Local.Entry{} = entry, max_rows, columns) do
-    case Native.lf_from_parquet(entry.path, max_rows, columns) do
-      {:ok, df} -> {:ok, Shared.create_dataframe(df)}
-      {:error, error} -> {:error, error}
+    with {:ok, df} <- Native.lf_from_parquet(entry.path, max_rows, columns) do
+      Shared.create_dataframe_from_file(df)
     end
   end
 
@@ -187,9 +185,8 @@ defmodule Explorer.PolarsBackend.LazyFrame do
               "Consider using `select/2` after reading the IPC file"
     end
 
-    case Native.lf_from_ipc(entry.path) do
-      {:ok, df} -> {:ok, Shared.create_dataframe(df)}
-      {:error, error} -> {:error, error}
+    with {:ok, df} <- Native.lf_from_ipc(entry.path) do
+      Shared.create_dataframe_from_file(df)
     end
   end
 
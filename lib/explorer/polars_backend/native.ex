@@ -31,31 +31,25 @@ defmodule Explorer.PolarsBackend.Native do
 
   defstruct [:inner]
 
+  def cloud_download(_ex_entry, _local_path), do: err()
   def df_from_arrow_stream_pointer(_stream_ptr), do: err()
+  def df_export_arrow_c(_df), do: err()
   def df_arrange(_df, _by, _reverse, _groups), do: err()
   def df_arrange_with(_df, _expressions, _directions, _groups), do: err()
+  def df_cancel(_token), do: err()
+  def df_cancel_token_new, do: err()
+  def df_column_stats(_df), do: err()
   def df_concat_columns(_df, _others), do: err()
   def df_concat_rows(_df, _others), do: err()
-  def df_distinct(_df, _subset, _selection), do: err()
-  def df_drop(_df, _name), do: err()
-  def df_drop_nils(_df, _subset), do: err()
-  def df_dtypes(_df), do: err()
-  def df_dump_csv(_df, _has_headers, _delimiter), do: err()
-  def df_dump_ndjson(_df), do: err()
-  def df_dump_parquet(_df, _compression), do: err()
-  def df_dump_ipc(_df, _compression), do: err()
-  def df_dump_ipc_stream(_df, _compression), do: err()
-  def df_filter_with(_df, _operation, _groups), do: err()
 
-  def df_from_csv(
+  def df_csv_batched_open(
         _filename,
+        _batch_size,
         _infer_schema_length,
         _has_header,
-        _stop_after_n_rows,
         _skip_rows,
         _projection,
         _sep,
-        _rechunk,
         _columns,
         _dtypes,
         _encoding,
@@ -65,22 +59,169 @@ defmodule Explorer.PolarsBackend.Native do
       ),
       do: err()
 
-  def df_from_ipc(_filename, _columns, _projection), do: err()
-  def df_from_ipc_stream(_filename, _columns, _projection), do: err()
-  def df_from_ndjson(_filename, _infer_schema_length, _batch_size), do: err()
+  def df_csv_batched_next(_reader, _n_batches, _cancel_token), do: err()
+
+  def df_csv_to_parquet(
+        _csv_filename,
+        _parquet_filename,
+        _batch_size,
+        _infer_schema_length,
+        _has_header,
+        _delimiter,
+        _dtypes,
+        _nil_vals,
+        _compression
+      ),
+      do: err()
+
+  def df_csv_infer_schema(
+        _filename,
+        _infer_schema_length,
+        _has_header,
+        _skip_rows,
+        _sep,
+        _encoding,
+        _nil_vals,
+        _parse_dates,
+        _eol_delimiter
+      ),
+      do: err()
+
+  def df_csv_sniff(_filename, _sample_bytes), do: err()
+  def df_distinct(_df, _subset, _selection), do: err()
+  def df_drop(_df, _name), do: err()
+  def df_drop_nils(_df, _subset), do: err()
+  def df_dtypes(_df), do: err()
+  def df_dump_arrow_chunks(_df), do: err()
+  def df_dump_csv(
+        _df,
+        _has_headers,
+        _delimiter,
+        _include_bom,
+        _columns,
+        _binary_encoding,
+        _line_terminator,
+        _header_names,
+        _trailing_newline,
+        _nan_value,
+        _inf_value
+      ),
+      do: err()
+
+  def df_dump_ndjson(_df, _compression), do: err()
+  def df_dump_parquet(_df, _compression, _data_page_size, _statistics), do: err()
+  def df_dump_tsv(_df, _has_headers, _include_bom, _columns), do: err()
+  def df_dump_ipc(_df, _compression, _metadata, _dictionary_encode), do: err()
+  def df_dump_ipc_shards(_df, _n_shards, _compression), do: err()
+  def df_dump_ipc_with_schema(_df, _compression), do: err()
+  def df_dump_ipc_zstd_dict(_df, _zstd_dict, _metadata, _dictionary_encode), do: err()
+  def df_dump_ipc_stream(_df, _compression), do: err()
+  def df_filter_with(_df, _operation, _groups), do: err()
+
+  def df_from_csv(_filename, _options), do: err()
+
+  def df_from_csv_range(
+        _filename,
+        _start_byte,
+        _length,
+        _delimiter,
+        _column_names,
+        _dtypes,
+        _nil_vals,
+        _eol_delimiter
+      ),
+      do: err()
+
+  def df_from_csv_resumable(
+        _filename,
+        _start_offset,
+        _n_rows,
+        _has_header,
+        _delimiter,
+        _column_names,
+        _dtypes,
+        _nil_vals,
+        _eol_delimiter
+      ),
+      do: err()
+
+  def df_from_csv_tail(
+        _filename,
+        _n,
+        _has_header,
+        _infer_schema_length,
+        _delimiter,
+        _dtypes,
+        _nil_vals,
+        _eol_delimiter
+      ),
+      do: err()
+
+  def df_from_csv_with_schema(_filename, _schema, _options), do: err()
+
+  def df_from_delta(_table_uri, _version, _columns, _config), do: err()
+  def df_from_flight(_endpoint, _ticket, _token), do: err()
+  def df_from_ipc(_filename, _columns, _projection, _normalize, _rename, _open_retries),
+    do: err()
+
+  def df_from_ipc_stream(_filename, _columns, _projection, _open_retries), do: err()
+  def df_from_ndjson(
+        _filename,
+        _infer_schema_length,
+        _batch_size,
+        _column_order,
+        _ignore_errors,
+        _open_retries,
+        _dtypes
+      ),
+      do: err()
+
+  def df_from_orc(_filename, _columns, _projection), do: err()
 
   def df_from_parquet(
         _filename,
         _stop_after_n_rows,
         _columns,
-        _projection
+        _projection,
+        _normalize,
+        _rename,
+        _parallel,
+        _coerce_int96,
+        _map_as_struct,
+        _open_retries
+      ),
+      do: err()
+
+  def df_from_parquet_cloud(_ex_entry, _columns, _projection), do: err()
+  def df_from_parquet_cloud_prefix(_ex_entry, _columns), do: err()
+  def df_from_parquet_dataset(
+        _root_dir,
+        _columns,
+        _projection,
+        _hive_partitioning,
+        _partition_filter
       ),
       do: err()
 
+  def df_from_parquet_filtered(_filename, _column, _op, _value, _projection), do: err()
+  def df_from_parquet_url(_url, _columns, _projection), do: err()
   def df_from_series(_columns), do: err()
+  def df_from_sql(_connection_uri, _query, _protocol), do: err()
+
+  def df_from_tsv(_filename, _options), do: err()
+
+  def df_from_xlsx(_filename, _sheet, _has_header, _skip_rows), do: err()
+  def df_from_xlsx_all(_filename, _has_header), do: err()
   def df_group_indices(_df, _column_names), do: err()
   def df_groups(_df, _column_names), do: err()
   def df_head(_df, _length, _groups), do: err()
+  def df_import_arrow_c(_array_ptr, _schema_ptr), do: err()
+  def df_ipc_metadata(_filename), do: err()
+  def df_ipc_schema(_filename), do: err()
+  def df_ipc_stream_schema(_filename), do: err()
+  def df_ipc_stream_writer_close(_writer), do: err()
+  def df_ipc_stream_writer_open(_filename, _compression), do: err()
+  def df_ipc_stream_writer_write(_writer, _df), do: err()
   def df_join(_df, _other, _left_on, _right_on, _how, _suffix), do: err()
 
   def df_load_csv(
@@ -97,21 +238,40 @@ defmodule Explorer.PolarsBackend.Native do
         _encoding,
         _nil_vals,
         _parse_dates,
-        _eol_delimiter
+        _eol_delimiter,
+        _low_memory,
+        _truncate_ragged_lines,
+        _true_values,
+        _false_values,
+        _binary_encoding,
+        _all_strings,
+        _strip_bom
       ),
       do: err()
 
   def df_load_ipc(_binary, _columns, _projection), do: err()
+  def df_load_ipc_zstd_dict(_binary, _zstd_dict, _columns, _projection), do: err()
+  def df_load_ipc_concat(_binaries, _columns, _projection), do: err()
   def df_load_ipc_stream(_binary, _columns, _projection), do: err()
   def df_load_ndjson(_binary, _infer_schema_length, _batch_size), do: err()
+  def df_load_orc(_binary, _columns, _projection), do: err()
   def df_load_parquet(_binary), do: err()
 
   def df_mask(_df, _mask), do: err()
   def df_mutate_with_exprs(_df, _exprs, _groups), do: err()
   def df_n_rows(_df), do: err()
+  def df_ndjson_batched_next(_reader, _cancel_token), do: err()
+  def df_ndjson_batched_open(_filename, _infer_schema_length, _batch_size), do: err()
+  def df_parquet_append(_filename, _df, _compression), do: err()
+  def df_parquet_batched_next(_reader, _cancel_token), do: err()
+  def df_parquet_batched_open(_filename, _columns), do: err()
+  def df_parquet_column_info(_filename), do: err()
+  def df_parquet_recompress(_src, _dst, _compression), do: err()
+  def df_parquet_verify(_filename), do: err()
   def df_names(_df), do: err()
   def df_pivot_longer(_df, _id_vars, _value_vars, _names_to, _values_to), do: err()
   def df_pivot_wider(_df, _id_columns, _pivot_column, _values_column, _names_prefix), do: err()
+  def df_preview(_filename, _format, _n), do: err()
   def df_pull(_df, _name), do: err()
   def df_put_column(_df, _series), do: err()
   def df_rename_columns(_df, _old_new_pairs), do: err()
@@ -125,18 +285,83 @@ defmodule Explorer.PolarsBackend.Native do
   def df_slice_by_series(_df, _series, _groups), do: err()
   def df_summarise_with_exprs(_df, _groups_exprs, _aggs_pairs), do: err()
   def df_tail(_df, _length, _groups), do: err()
-  def df_to_csv(_df, _filename, _has_headers, _delimiter), do: err()
-  def df_to_csv_cloud(_df, _ex_entry, _has_headers, _delimiter), do: err()
+  def df_to_csv(
+        _df,
+        _filename,
+        _has_headers,
+        _delimiter,
+        _include_bom,
+        _append,
+        _columns,
+        _binary_encoding,
+        _line_terminator,
+        _header_names,
+        _trailing_newline,
+        _nan_value,
+        _inf_value
+      ),
+      do: err()
+
+  def df_to_csv_cloud(
+        _df,
+        _ex_entry,
+        _has_headers,
+        _delimiter,
+        _include_bom,
+        _columns,
+        _binary_encoding,
+        _line_terminator,
+        _header_names,
+        _trailing_newline,
+        _nan_value,
+        _inf_value,
+        _skip_if_empty,
+        _progress_pid
+      ),
+      do: err()
+
   def df_to_dummies(_df, _columns), do: err()
-  def df_to_ipc(_df, _filename, _compression), do: err()
-  def df_to_ipc_cloud(_df, _ex_entry, _compression), do: err()
+  def df_to_file(_df, _filename, _opts), do: err()
+  def df_to_ipc(_df, _filename, _compression, _chunk_size, _metadata, _dictionary_encode),
+    do: err()
+
+  def df_to_ipc_cloud(_df, _ex_entry, _compression, _skip_if_empty, _progress_pid),
+    do: err()
+
   def df_to_ipc_stream(_df, _filename, _compression), do: err()
-  def df_to_ipc_stream_cloud(_df, _ex_entry, _compression), do: err()
+  def df_to_ipc_stream_cloud(_df, _ex_entry, _compression, _skip_if_empty, _progress_pid),
+    do: err()
+
   def df_to_lazy(_df), do: err()
-  def df_to_ndjson(_df, _filename), do: err()
-  def df_to_ndjson_cloud(_df, _ex_entry), do: err()
-  def df_to_parquet(_df, _filename, _compression), do: err()
-  def df_to_parquet_cloud(_df, _ex_entry, _compression), do: err()
+  def df_to_ndjson(_df, _filename, _compression), do: err()
+  def df_to_ndjson_cloud(_df, _ex_entry, _skip_if_empty, _progress_pid), do: err()
+  def df_to_parquet(
+        _df,
+        _filename,
+        _compression,
+        _data_page_size,
+        _statistics,
+        _schema,
+        _categorical_as
+      ),
+      do: err()
+
+  def df_to_parquet_partitioned(_df, _root_dir, _partition_by, _compression), do: err()
+  def df_to_parquet_sharded(_df, _dir, _by, _n_files, _compression), do: err()
+  def df_to_parquet_cloud(
+        _df,
+        _ex_entry,
+        _compression,
+        _data_page_size,
+        _skip_if_empty,
+        _progress_pid
+      ),
+      do: err()
+
+  def df_to_tsv(_df, _filename, _has_headers, _include_bom, _append, _columns), do: err()
+  def df_to_xlsx(_df, _filename, _sheet_name), do: err()
+  def df_train_zstd_dict(_samples, _dict_size), do: err()
+  def df_validate_schema(_df, _expected_dtypes), do: err()
   def df_width(_df), do: err()
   def df_describe(_df, _percentiles), do: err()
   def df_nil_count(_df), do: err()
@@ -215,6 +440,8 @@ defmodule Explorer.PolarsBackend.Native do
   def lf_to_parquet(_df, _filename, _compression, _streaming), do: err()
   def lf_to_ipc(_df, _filename, _compression, _streaming), do: err()
 
+  def schema_build(_dtypes), do: err()
+
   # Series
   def s_as_str(_s), do: err()
   def s_add(_s, _other), do: err()
@@ -80,6 +80,30 @@ defmodule Explorer.PolarsBackend.Shared do
     Explorer.Backend.DataFrame.new(polars_df, df_names(polars_df), df_dtypes(polars_df))
   end
 
+  # Explorer has no decimal dtype, and casting the decimal columns of a
+  # file to floats would lose precision without notice, so the readers
+  # return an error for them instead.
+  def create_dataframe_from_file(polars_df) do
+    {:ok, dtypes} = native_dtypes(polars_df)
+
+    decimals =
+      for {name, "decimal[" <> _ = dtype} <- Enum.zip(df_names(polars_df), dtypes),
+          do: "#{inspect(name)} (#{dtype})"
+
+    case decimals do
+      [] ->
+        {:ok, create_dataframe(polars_df)}
+
+      decimals ->
+        {:error,
+         "decimal columns are not supported, got: #{Enum.join(decimals, ", ")}. " <>
+           "Cast them to floats before writing the file if the loss of precision is acceptable"}
+    end
+  end
+
+  defp native_dtypes(%PolarsDataFrame{} = polars_df), do: Native.df_dtypes(polars_df)
+  defp native_dtypes(%PolarsLazyFrame{} = polars_df), do: Native.lf_dtypes(polars_df)
+
   defp df_names(%PolarsDataFrame{} = polars_df) do
     {:ok, names} = Native.df_names(polars_df)
     names
@@ -0,0 +1,212 @@
+use std::io::{Read, Seek, SeekFrom};
+use std::ops::Range;
+
+use bytes::Bytes;
+use object_store::path::Path;
+use object_store::ObjectStore;
+
+/// CloudReader wraps the asynchronous interface of [ObjectStore::get_ranges](https://docs.rs/object_store/latest/object_store/trait.ObjectStore.html#method.get_ranges)
+/// in a synchronous interface.
+///
+/// Readers that only need parts of the object, like the footer and some
+/// columns of a Parquet file, fetch those ranges up front and read them
+/// through a [FetchedRanges], so they don't download the entire object.
+///
+/// If the store doesn't support range requests, the entire object is
+/// fetched once, and the ranges are taken from it.
+pub struct CloudReader {
+    // Hold a reference to the store. The store itself is thread-safe.
+    object_store: Box<dyn ObjectStore>,
+    // The path in the object_store which we want to read from
+    path: Path,
+    // The size of the object in bytes
+    length: u64,
+    // The Tokio runtime which the reader uses internally.
+    runtime: tokio::runtime::Runtime,
+    // The entire object, once we know that the store doesn't support ranges
+    object: Option<Bytes>,
+}
+
+impl CloudReader {
+    /// Construct a new CloudReader
+    ///
+    /// Creates a new (current-thread) Tokio runtime and fetches the
+    /// size of the object, which is necessary for reading from the end.
+    pub fn new(
+        object_store: Box<dyn ObjectStore>,
+        path: Path,
+    ) -> Result<Self, object_store::Error> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .enable_io()
+            .build()
+            .map_err(|error| object_store::Error::Generic {
+                store: "CloudReader",
+                source: Box::new(error),
+            })?;
+        let meta = runtime.block_on(object_store.head(&path))?;
+
+        Ok(CloudReader {
+            object_store,
+            path,
+            length: meta.size as u64,
+            runtime,
+            object: None,
+        })
+    }
+
+    /// The size of the object in bytes.
+    pub fn size(&self) -> u64 {
+        self.length
+    }
+
+    /// Fetches the given ranges of the object. Ranges that are close to
+    /// each other are coalesced by the store, so this is usually a single
+    /// request.
+    pub fn get_ranges(
+        &mut self,
+        ranges: &[Range<usize>],
+    ) -> Result<FetchedRanges, object_store::Error> {
+        let fetched = match &self.object {
+            Some(object) => slice_ranges(object, ranges)?,
+            None => self.fetch_ranges(ranges)?,
+        };
+
+        let ranges = ranges
+            .iter()
+            .zip(fetched)
+            .map(|(range, bytes)| (range.start as u64, bytes))
+            .collect();
+
+        Ok(FetchedRanges {
+            ranges,
+            length: self.length,
+            position: 0,
+        })
+    }
+
+    // Stores without range requests fail with `NotImplemented` or
+    // `NotSupported`, and HTTP servers that ignore the Range header send
+    // the entire object instead. A single range is fetched on its own, so
+    // the size of the response shows it, and the first request of a reader
+    // is always for a single range, like the footer of a Parquet file.
+    fn fetch_ranges(&mut self, ranges: &[Range<usize>]) -> Result<Vec<Bytes>, object_store::Error> {
+        let result = self.runtime.block_on(async {
+            match ranges {
+                [range] => self
+                    .object_store
+                    .get_range(&self.path, range.clone())
+                    .await
+                    .map(|bytes| vec![bytes]),
+                _ => self.object_store.get_ranges(&self.path, ranges).await,
+            }
+        });
+
+        match result {
+            Ok(fetched)
+                if fetched
+                    .iter()
+                    .zip(ranges)
+                    .all(|(bytes, range)| bytes.len() == range.len()) =>
+            {
+                Ok(fetched)
+            }
+            Ok(_)
+            | Err(object_store::Error::NotImplemented)
+            | Err(object_store::Error::NotSupported { .. }) => {
+                let object = self
+                    .runtime
+                    .block_on(async { self.object_store.get(&self.path).await?.bytes().await })?;
+
+                let fetched = slice_ranges(&object, ranges)?;
+                self.object = Some(object);
+                Ok(fetched)
+            }
+            Err(error) => Err(error),
+        }
+    }
+}
+
+fn slice_ranges(
+    object: &Bytes,
+    ranges: &[Range<usize>],
+) -> Result<Vec<Bytes>, object_store::Error> {
+    ranges
+        .iter()
+        .map(|range| {
+            if range.start <= range.end && range.end <= object.len() {
+                Ok(object.slice(range.clone()))
+            } else {
+                Err(object_store::Error::Generic {
+                    store: "CloudReader",
+                    source: format!(
+                        "the range {range:?} is out of the bounds of the object of {} bytes",
+                        object.len()
+                    )
+                    .into(),
+                })
+            }
+        })
+        .collect()
+}
+
+/// FetchedRanges implements `std::io::Read` and `std::io::Seek` over the
+/// ranges of an object that were fetched by [CloudReader::get_ranges],
+/// positioned as in the whole object. Reading outside of them is an error.
+pub struct FetchedRanges {
+    // The start of each range in the object, and its bytes
+    ranges: Vec<(u64, Bytes)>,
+    // The size of the object in bytes
+    length: u64,
+    // The current position of the reader
+    position: u64,
+}
+
+impl Read for FetchedRanges {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.position >= self.length || buf.is_empty() {
+            return Ok(0);
+        }
+
+        let position = self.position;
+        let (start, bytes) = self
+            .ranges
+            .iter()
+            .find(|(start, bytes)| *start <= position && position < start + bytes.len() as u64)
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::Other,
+                    format!("the byte {position} of the object was not fetched"),
+                )
+            })?;
+
+        let offset = (position - start) as usize;
+        let length = std::cmp::min(buf.len(), bytes.len() - offset);
+
+        buf[..length].copy_from_slice(&bytes[offset..offset + length]);
+        self.position += length as u64;
+
+        Ok(length)
+    }
+}
+
+impl Seek for FetchedRanges {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.length.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        };
+
+        match position {
+            Some(position) => {
+                self.position = position;
+                Ok(position)
+            }
+            None => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )),
+        }
+    }
+}
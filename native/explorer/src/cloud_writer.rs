@@ -1,9 +1,17 @@
+use std::sync::mpsc;
+use std::thread::JoinHandle;
+
+use rustler::{Encoder, LocalPid, OwnedEnv};
 use tokio::io::{AsyncWrite, AsyncWriteExt};
 
 use object_store::path::Path;
 use object_store::MultipartId;
 use object_store::ObjectStore;
 
+// How many bytes are written between each progress report.
+// This is the minimum size of a part in S3 multipart uploads.
+const PROGRESS_INTERVAL: u64 = 5 * 1024 * 1024;
+
 /// CloudWriter wraps the asynchronous interface of [ObjectStore::put_multipart](https://docs.rs/object_store/latest/object_store/trait.ObjectStore.html#tymethod.put_multipart)
 /// in a synchronous interface which implements `std::io::Write`.
 ///
@@ -20,6 +28,8 @@ pub struct CloudWriter {
     runtime: tokio::runtime::Runtime,
     // Internal writer, constructed at creation
     writer: Box<dyn AsyncWrite + Send + Unpin>,
+    // Optionally reports the number of bytes written to an Elixir process
+    progress: Option<ProgressReporter>,
 }
 
 impl CloudWriter {
@@ -41,9 +51,17 @@ impl CloudWriter {
             multipart_id,
             runtime,
             writer,
+            progress: None,
         }
     }
 
+    /// Sends `{:upload_progress, bytes_written}` messages to the given process
+    /// while the upload happens, and once more when the writer is dropped.
+    pub fn with_progress(mut self, pid: Option<LocalPid>) -> Self {
+        self.progress = pid.map(ProgressReporter::new);
+        self
+    }
+
     async fn build_writer(
         object_store: &dyn ObjectStore,
         path: &Path,
@@ -67,8 +85,13 @@ impl CloudWriter {
 impl std::io::Write for CloudWriter {
     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
         let res = self.runtime.block_on(self.writer.write(buf));
-        if res.is_err() {
-            self.abort();
+        match res {
+            Ok(written) => {
+                if let Some(progress) = self.progress.as_mut() {
+                    progress.advance(written);
+                }
+            }
+            Err(_) => self.abort(),
         }
         res
     }
@@ -85,6 +108,66 @@ impl std::io::Write for CloudWriter {
 impl Drop for CloudWriter {
     fn drop(&mut self) {
         let _ = self.runtime.block_on(self.writer.shutdown());
+
+        if let Some(progress) = self.progress.take() {
+            progress.finish();
+        }
+    }
+}
+
+/// Reports the upload progress to an Elixir process.
+///
+/// Messages can't be sent with an `OwnedEnv` from the (dirty) scheduler threads
+/// running the NIFs, so the byte counts are handed over to a separate thread.
+struct ProgressReporter {
+    bytes_written: u64,
+    last_reported: u64,
+    sender: mpsc::Sender<u64>,
+    thread: JoinHandle<()>,
+}
+
+impl ProgressReporter {
+    fn new(pid: LocalPid) -> Self {
+        let (sender, receiver) = mpsc::channel::<u64>();
+
+        let thread = std::thread::spawn(move || {
+            let mut env = OwnedEnv::new();
+
+            for bytes_written in receiver {
+                env.send_and_clear(&pid, |env| {
+                    (crate::atoms::upload_progress(), bytes_written).encode(env)
+                });
+            }
+        });
+
+        ProgressReporter {
+            bytes_written: 0,
+            last_reported: 0,
+            sender,
+            thread,
+        }
+    }
+
+    fn advance(&mut self, written: usize) {
+        self.bytes_written += written as u64;
+
+        if self.bytes_written - self.last_reported >= PROGRESS_INTERVAL {
+            self.report();
+        }
+    }
+
+    fn report(&mut self) {
+        self.last_reported = self.bytes_written;
+        // The receiver only goes away if the thread panicked, and then
+        // there is nobody to report to anyway.
+        let _ = self.sender.send(self.bytes_written);
+    }
+
+    // Sends the final count and waits for the messages to be delivered.
+    fn finish(mut self) {
+        self.report();
+        drop(self.sender);
+        let _ = self.thread.join();
     }
 }
 
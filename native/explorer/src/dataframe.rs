@@ -5,8 +5,12 @@ use polars::export::{arrow, arrow::ffi};
 use std::collections::HashMap;
 use std::result::Result;
 
+use crate::dataframe::io::dtype_to_str;
+use crate::datatypes::{ExDate, ExTime};
+use crate::encoding::{encode_datetime, term_from_float};
 use crate::ex_expr_to_exprs;
-use crate::{ExDataFrame, ExExpr, ExLazyFrame, ExSeries, ExplorerError};
+use crate::{ExArrowCExportRef, ExDataFrame, ExExpr, ExLazyFrame, ExSeries, ExplorerError};
+use rustler::{Encoder, Env, ResourceArc, Term};
 use smartstring::alias::String as SmartString;
 
 // Loads the IO functions for read/writing CSV, NDJSON, Parquet, etc.
@@ -96,6 +100,81 @@ pub fn df_width(df: ExDataFrame) -> Result<usize, ExplorerError> {
     Ok(df.width())
 }
 
+// Returns the name, dtype, null count, min and max of each column, to
+// sanity-check the data right after a read. The min and max are nil for
+// columns that are not numeric or temporal, or that only have nils.
+// Timezone-aware datetimes are given in UTC, and durations as integers
+// in the time unit of their dtype.
+#[rustler::nif(schedule = "DirtyCpu")]
+#[allow(clippy::type_complexity)]
+pub fn df_column_stats(
+    env: Env,
+    df: ExDataFrame,
+) -> Result<Vec<(String, String, usize, Term, Term)>, ExplorerError> {
+    df.get_columns()
+        .iter()
+        .map(|series| {
+            let (min, max) = column_min_max(series, env)?;
+            let name = series.name().to_string();
+
+            Ok((
+                name,
+                dtype_to_str(series.dtype()).unwrap_or_else(|_| series.dtype().to_string()),
+                series.null_count(),
+                min,
+                max,
+            ))
+        })
+        .collect()
+}
+
+fn column_min_max<'a>(
+    series: &Series,
+    env: Env<'a>,
+) -> Result<(Term<'a>, Term<'a>), ExplorerError> {
+    let float = |value: Option<f64>| value.map(|value| term_from_float(value, env));
+    let datetime = |value: Option<i64>, unit: TimeUnit| {
+        value
+            .map(|value| encode_datetime(value, unit, env))
+            .transpose()
+    };
+
+    let (min, max) = match series.dtype() {
+        dtype if dtype.is_float() => (float(series.min()), float(series.max())),
+        dtype if dtype.is_integer() => (
+            series.min::<i64>().map(|value| value.encode(env)),
+            series.max::<i64>().map(|value| value.encode(env)),
+        ),
+        DataType::Date => (
+            series
+                .min::<i32>()
+                .map(|value| ExDate::from(value).encode(env)),
+            series
+                .max::<i32>()
+                .map(|value| ExDate::from(value).encode(env)),
+        ),
+        DataType::Time => (
+            series
+                .min::<i64>()
+                .map(|value| ExTime::from(value).encode(env)),
+            series
+                .max::<i64>()
+                .map(|value| ExTime::from(value).encode(env)),
+        ),
+        DataType::Datetime(unit, _) => (
+            datetime(series.min(), *unit)?,
+            datetime(series.max(), *unit)?,
+        ),
+        DataType::Duration(_) => (
+            series.min::<i64>().map(|value| value.encode(env)),
+            series.max::<i64>().map(|value| value.encode(env)),
+        ),
+        _ => (None, None),
+    };
+
+    Ok((min.encode(env), max.encode(env)))
+}
+
 #[rustler::nif(schedule = "DirtyCpu")]
 pub fn df_concat_rows(
     data: ExDataFrame,
@@ -329,6 +408,79 @@ fn array_to_dataframe(
     DataFrame::try_from(struct_array).map_err(ExplorerError::Polars)
 }
 
+// Exports the dataframe as a struct array through the Arrow C Data Interface,
+// returning the resource that owns the exported structs and their addresses.
+// The addresses are only valid while the resource is alive, so the Elixir
+// side must keep a reference to it until the consumer is done. A consumer
+// that takes ownership of the structs must move them and mark them released.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_export_arrow_c(
+    data: ExDataFrame,
+) -> Result<(ResourceArc<ExArrowCExportRef>, u64, u64), ExplorerError> {
+    let mut df = data.clone_inner();
+    df.rechunk();
+
+    let fields = df.schema().to_arrow().fields;
+
+    let arrays: Vec<Box<dyn arrow::array::Array>> = match df.iter_chunks().next() {
+        Some(chunk) => chunk.into_arrays(),
+        None => fields
+            .iter()
+            .map(|field| arrow::array::new_empty_array(field.data_type.clone()))
+            .collect(),
+    };
+
+    let data_type = arrow::datatypes::DataType::Struct(fields);
+    let struct_array = arrow::array::StructArray::new(data_type.clone(), arrays, None);
+
+    let array = Box::new(ffi::export_array_to_c(Box::new(struct_array)));
+    let schema = Box::new(ffi::export_field_to_c(&arrow::datatypes::Field::new(
+        "", data_type, false,
+    )));
+
+    let array_ptr = &*array as *const ffi::ArrowArray as u64;
+    let schema_ptr = &*schema as *const ffi::ArrowSchema as u64;
+
+    let resource = ResourceArc::new(ExArrowCExportRef { array, schema });
+
+    Ok((resource, array_ptr, schema_ptr))
+}
+
+// Imports a struct array produced by another library through the Arrow C Data
+// Interface. We take ownership of both structs by moving them out and marking
+// the originals as released, so their release callbacks are invoked exactly
+// once, when the imported data is dropped.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_import_arrow_c(array_ptr: u64, schema_ptr: u64) -> Result<ExDataFrame, ExplorerError> {
+    let array_ptr = array_ptr as *mut ffi::ArrowArray;
+    let schema_ptr = schema_ptr as *mut ffi::ArrowSchema;
+
+    if array_ptr.is_null() || schema_ptr.is_null() {
+        return Err(ExplorerError::Other(
+            "Incorrect Arrow C Data Interface pointers".into(),
+        ));
+    }
+
+    let schema = unsafe { std::ptr::replace(schema_ptr, ffi::ArrowSchema::empty()) };
+    let array = unsafe { std::ptr::replace(array_ptr, ffi::ArrowArray::empty()) };
+
+    let field = unsafe { ffi::import_field_from_c(&schema) }.map_err(arrow_to_explorer_error)?;
+
+    if !matches!(field.data_type, arrow::datatypes::DataType::Struct(_)) {
+        return Err(ExplorerError::Other(format!(
+            "expected a struct array from the Arrow C Data Interface, got {:?}",
+            field.data_type
+        )));
+    }
+
+    let array = unsafe { ffi::import_array_from_c(array, field.data_type) };
+
+    let mut df = array_to_dataframe(array)?;
+    df.align_chunks();
+
+    Ok(ExDataFrame::new(df))
+}
+
 fn arrow_to_explorer_error(error: impl std::fmt::Debug) -> ExplorerError {
     ExplorerError::Other(format!("Internal Arrow error: #{error:?}"))
 }
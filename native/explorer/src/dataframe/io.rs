@@ -7,19 +7,40 @@
 // - to: writes a dataframe to a file in a given format.
 //
 // Today we have the following formats: CSV, NDJSON, Parquet, Apache Arrow and Apache Arrow Stream.
+// We can also read Apache ORC files, which is behind the "orc" feature,
+// and read/write Excel files, which is behind the "excel" feature.
+// Delta Lake tables can be read when the "delta" feature is enabled,
+// SQL queries can be read when the "database" feature is enabled,
+// and Arrow Flight streams can be read when the "flight" feature is enabled.
 //
 use polars::prelude::*;
 
-use rustler::{Binary, Env, NewBinary};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use encoding_rs::Encoding;
+use encoding_rs_io::DecodeReaderBytesBuilder;
+use rustler::{Atom, Binary, Env, LocalPid, NewBinary, ResourceArc};
+use std::borrow::Cow;
 use std::convert::TryFrom;
-use std::fs::File;
-use std::io::{BufReader, BufWriter, Cursor};
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Cursor, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
 use std::result::Result;
 use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
 
 use crate::dataframe::normalize_numeric_dtypes;
-use crate::datatypes::{ExParquetCompression, ExS3Entry};
-use crate::{ExDataFrame, ExplorerError};
+use crate::datatypes::{
+    ExCsvDialect, ExCsvReadOptions, ExParquetCompression, ExS3Config, ExS3Entry, ExWriteOptions,
+    ExXlsxSheet,
+};
+use crate::datatypes::{ExIpcStreamWriter, ExParquetBatchedReader};
+use crate::error::SchemaMismatch;
+use crate::{
+    atoms, ExCancelTokenRef, ExCsvBatchedReaderRef, ExDataFrame, ExExpr, ExIpcStreamWriterRef,
+    ExNdjsonBatchedReaderRef, ExParquetBatchedReaderRef, ExSchemaRef, ExplorerError,
+};
 
 // Note that we have two types of "Compression" for IPC: this one and IpcCompresion.
 use polars::export::arrow::io::ipc::write::Compression as IpcStreamCompression;
@@ -35,11 +56,423 @@ where
     Ok(ExDataFrame::new(normalized_df))
 }
 
+// Same as `finish_reader`, but keeps the dtypes exactly as they were stored,
+// so a `u32` column stays as `u32` instead of being cast to `i64`.
+fn finish_reader_raw<R>(reader: impl SerReader<R>) -> Result<ExDataFrame, ExplorerError>
+where
+    R: polars::io::mmap::MmapBytesReader,
+{
+    Ok(ExDataFrame::new(reader.finish()?))
+}
+
+// Renames the columns right after reading, from the old to the new names,
+// so we don't need another pass over the dataframe in Elixir.
+fn rename_columns(
+    df: ExDataFrame,
+    rename: Vec<(String, String)>,
+) -> Result<ExDataFrame, ExplorerError> {
+    if rename.is_empty() {
+        return Ok(df);
+    }
+
+    let mut df = df.clone_inner();
+
+    for (old_name, new_name) in rename {
+        if !df.get_column_names().contains(&old_name.as_str()) {
+            return Err(ExplorerError::Other(format!(
+                "cannot rename column {old_name:?} because it does not exist. \
+                The existing columns are: {:?}",
+                df.get_column_names()
+            )));
+        }
+
+        df.rename(&old_name, &new_name)?;
+    }
+
+    Ok(ExDataFrame::new(df))
+}
+
+// Same as `File::open` and `File::create`, but the errors include the path.
+pub fn open_file(path: impl AsRef<Path>) -> Result<File, ExplorerError> {
+    File::open(&path).map_err(|error| ExplorerError::file(path.as_ref(), "reading", error))
+}
+
+// Same as `open_file`, but retries up to `retries` times on the transient
+// errors of network mounts, such as NFS, doubling the wait after each
+// attempt. Other errors, like a missing file, are returned right away.
+pub fn open_file_with_retries(
+    path: impl AsRef<Path>,
+    retries: usize,
+) -> Result<File, ExplorerError> {
+    let mut backoff = Duration::from_millis(50);
+
+    for _ in 0..retries {
+        match File::open(&path) {
+            Err(error) if is_transient_io_error(&error) => {
+                thread::sleep(backoff);
+                backoff *= 2;
+            }
+            result => {
+                return result.map_err(|error| ExplorerError::file(path.as_ref(), "reading", error))
+            }
+        }
+    }
+
+    open_file(path)
+}
+
+fn is_transient_io_error(error: &io::Error) -> bool {
+    match error.kind() {
+        io::ErrorKind::WouldBlock | io::ErrorKind::Interrupted | io::ErrorKind::TimedOut => true,
+        #[cfg(unix)]
+        _ => error.raw_os_error() == Some(libc::ESTALE),
+        #[cfg(not(unix))]
+        _ => false,
+    }
+}
+
+pub fn create_file(path: impl AsRef<Path>) -> Result<File, ExplorerError> {
+    File::create(&path).map_err(|error| ExplorerError::file(path.as_ref(), "writing", error))
+}
+
 // ============ CSV ============ //
 
+// The options are given as a map, with the defaults of `ExCsvReadOptions`.
+//
+// The `low_memory` mode reduces the peak memory usage while parsing,
+// at the cost of speed. Note that `rechunk` will copy the chunks
+// into contiguous memory after parsing, which partially defeats
+// its purpose, so you probably want to disable rechunking with it.
+//
+// With `truncate_ragged_lines`, the fields that overflow the number of
+// columns are dropped, instead of failing the entire read.
+//
+// The `true_values` and `false_values` are extra spellings for the
+// declared boolean columns, such as "Y" and "N". See `CsvBooleans`.
+//
+// The `binary_encoding` is how the declared binary columns are encoded
+// as text, either "hex" or "base64". See `CsvBinaries`.
+//
+// The `nan_values` and `inf_values` are extra spellings of NaN and infinity
+// for the declared float columns. See `CsvFloats`.
+//
+// The columns are renamed after reading with `rename`. See `rename_columns`.
+// The `fallback_dtypes` only apply to empty columns. See `apply_fallback_dtypes`.
+//
+// With `all_strings`, the columns are read as strings without inferring
+// their dtypes, and dates are not parsed. The declared `dtypes` still apply.
+//
+// With `strip_bom`, a leading UTF-8 or UTF-16 BOM doesn't end up in the
+// name of the first column. See `open_csv_file`.
+//
+// The `columns` select the columns by name. When the `projection` is
+// also given, the columns are selected by their indexes in the file instead,
+// and the `columns` become the names of the projected columns, in the
+// order they appear in the file. The `dtypes` always refer to the names in
+// the file. See `project_csv_columns`.
+//
+// With `fixed_columns`, the file has no header and every row has exactly
+// as many fields as the `columns`, which name the columns by position.
+// A row with another number of fields is an error, instead of having its
+// values shifted or filled with nils, unless `truncate_ragged_lines` is set.
+// See `check_csv_field_counts`.
+//
+// The `header_row` is the 0-based index of the header line. The lines above
+// it are skipped and the lines below it are the rows. It takes precedence
+// over `has_header` and `skip_rows`, which are ignored when it is given.
+//
+// The `cancel_token` is checked before and after each pass over the file,
+// see `check_cancelled`.
 #[rustler::nif(schedule = "DirtyIo")]
-#[allow(clippy::too_many_arguments)]
 pub fn df_from_csv(
+    filename: &str,
+    options: ExCsvReadOptions,
+) -> Result<ExDataFrame, ExplorerError> {
+    read_csv_with_options(filename, options, Some(b'"'), None)
+}
+
+// Same as `df_from_csv`, but with the tab delimiter. Since TSV files
+// have no notion of quoting, quotes are read as regular characters.
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_from_tsv(
+    filename: &str,
+    options: ExCsvReadOptions,
+) -> Result<ExDataFrame, ExplorerError> {
+    let options = ExCsvReadOptions {
+        delimiter: b'\t',
+        ..options
+    };
+
+    read_csv_with_options(filename, options, None, None)
+}
+
+// Same as `df_from_csv`, but with a schema built by `schema_build`,
+// so the dtypes are not parsed again when reading many files that
+// share the same layout. The schema replaces the `dtypes` option.
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_from_csv_with_schema(
+    filename: &str,
+    schema: ResourceArc<ExSchemaRef>,
+    options: ExCsvReadOptions,
+) -> Result<ExDataFrame, ExplorerError> {
+    if !options.dtypes.is_empty() {
+        return Err(ExplorerError::Other(
+            "the dtypes cannot be given together with a schema".to_string(),
+        ));
+    }
+
+    read_csv_with_options(filename, options, Some(b'"'), Some(schema.0.clone()))
+}
+
+// The body shared by the CSV and TSV readers. The `schema`, when given,
+// is used instead of the `dtypes` of the options.
+fn read_csv_with_options(
+    filename: &str,
+    options: ExCsvReadOptions,
+    quote_char: Option<u8>,
+    schema: Option<Arc<Schema>>,
+) -> Result<ExDataFrame, ExplorerError> {
+    let ExCsvReadOptions {
+        infer_schema_length,
+        has_header,
+        max_rows: stop_after_n_rows,
+        skip_rows,
+        projection,
+        delimiter: delimiter_as_byte,
+        rechunk: do_rechunk,
+        columns: column_names,
+        dtypes,
+        encoding,
+        nil_values: null_vals,
+        parse_dates,
+        eol_delimiter,
+        low_memory,
+        truncate_ragged_lines,
+        true_values,
+        false_values,
+        binary_encoding,
+        rename,
+        fallback_dtypes,
+        all_strings,
+        strip_bom,
+        date_format,
+        datetime_format,
+        time_format,
+        strict_date_formats,
+        fixed_columns,
+        nan_values,
+        inf_values,
+        special_floats_as_null,
+        header_row,
+        cancel_token,
+    } = options;
+
+    check_cancelled(&cancel_token)?;
+
+    let (has_header, skip_rows) = match header_row {
+        Some(header_row) => (true, header_row),
+        None => (has_header, skip_rows),
+    };
+
+    let dtypes = match schema {
+        Some(schema) => schema,
+        None => schema_from_dtypes_pairs(dtypes)?,
+    };
+    let fallback_dtypes = schema_from_dtypes_pairs(fallback_dtypes)?;
+    let date_formats = CsvDateFormats {
+        date: date_format,
+        datetime: datetime_format,
+        time: time_format,
+        strict: strict_date_formats,
+    };
+    let special_floats = CsvSpecialFloats {
+        nan_values,
+        inf_values,
+        as_null: special_floats_as_null,
+    };
+
+    let (column_names, positional_names) = if fixed_columns {
+        if has_header || projection.is_some() {
+            return Err(ExplorerError::Other(
+                "fixed columns cannot be read with a header or a projection".to_string(),
+            ));
+        }
+
+        let column_names = column_names.ok_or_else(|| {
+            ExplorerError::Other("fixed columns require the column names".to_string())
+        })?;
+
+        if !truncate_ragged_lines {
+            check_csv_field_counts(
+                filename,
+                encoding,
+                strip_bom,
+                &CsvDialect {
+                    delimiter: delimiter_as_byte,
+                    quote_char,
+                    eol_delimiter: eol_delimiter.unwrap_or(b'\n'),
+                },
+                skip_rows,
+                stop_after_n_rows,
+                column_names.len(),
+            )?;
+
+            check_cancelled(&cancel_token)?;
+        }
+
+        (None, Some(column_names))
+    } else {
+        (column_names, None)
+    };
+
+    let df = read_csv_file(
+        filename,
+        infer_schema_length,
+        has_header,
+        stop_after_n_rows,
+        skip_rows,
+        projection,
+        delimiter_as_byte,
+        quote_char,
+        do_rechunk,
+        column_names,
+        dtypes,
+        encoding,
+        null_vals,
+        parse_dates,
+        eol_delimiter,
+        low_memory,
+        truncate_ragged_lines,
+        true_values,
+        false_values,
+        binary_encoding,
+        all_strings,
+        strip_bom,
+        date_formats,
+        special_floats,
+    )?;
+
+    check_cancelled(&cancel_token)?;
+
+    let df = match positional_names {
+        None => df,
+        Some(names) => {
+            let mut df = df.clone_inner();
+
+            if names.len() != df.width() {
+                return Err(ExplorerError::Other(format!(
+                    "expected {} fixed columns, but the file has {}",
+                    names.len(),
+                    df.width()
+                )));
+            }
+
+            df.set_column_names(&names)?;
+            ExDataFrame::new(df)
+        }
+    };
+
+    let df = apply_fallback_dtypes(df, &fallback_dtypes)?;
+    rename_columns(df, rename)
+}
+
+// The options of the reader that decide how a line is split into fields.
+struct CsvDialect {
+    delimiter: u8,
+    quote_char: Option<u8>,
+    eol_delimiter: u8,
+}
+
+// Polars fills the missing fields of a short row with nils, so the field
+// counts are checked line by line before the read, with the same encoding
+// and dialect as the reader. Only the rows that will be read are checked.
+// The row in the error starts at zero, after the skipped rows. Quoted fields
+// spanning several lines are not supported.
+fn check_csv_field_counts(
+    filename: &str,
+    encoding: &str,
+    strip_bom: bool,
+    dialect: &CsvDialect,
+    skip_rows: usize,
+    stop_after_n_rows: Option<usize>,
+    expected: usize,
+) -> Result<(), ExplorerError> {
+    let (file, _encoding) = open_csv_file(filename, encoding, strip_bom)?;
+    let lines = BufReader::new(file)
+        .split(dialect.eol_delimiter)
+        .skip(skip_rows)
+        .enumerate()
+        .take(stop_after_n_rows.unwrap_or(usize::MAX));
+
+    for (row, line) in lines {
+        let line = line?;
+        let line = String::from_utf8_lossy(&line);
+        let line = line.strip_suffix('\r').unwrap_or(&line);
+
+        if line.is_empty() {
+            continue;
+        }
+
+        let count = match dialect.quote_char {
+            Some(quote_char) => split_csv_line(line, dialect.delimiter, quote_char).len(),
+            None => {
+                line.bytes()
+                    .filter(|byte| *byte == dialect.delimiter)
+                    .count()
+                    + 1
+            }
+        };
+
+        if count != expected {
+            return Err(ExplorerError::Other(format!(
+                "expected {expected} fields in row {row} of {filename}, but got {count}"
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+// Columns without any values are inferred as strings, which breaks the
+// concatenation with files where the same column has values. So the
+// columns that are entirely nil or empty are cast to their fallback dtype.
+// The columns with values keep their inferred dtypes.
+fn apply_fallback_dtypes(
+    df: ExDataFrame,
+    fallback_dtypes: &Schema,
+) -> Result<ExDataFrame, ExplorerError> {
+    if fallback_dtypes.is_empty() {
+        return Ok(df);
+    }
+
+    let mut df = df.clone_inner();
+
+    for (name, dtype) in fallback_dtypes.iter() {
+        let series = match df.column(name.as_str()) {
+            Ok(series) => series,
+            // The column may have been left out by the projection.
+            Err(_) => continue,
+        };
+
+        let is_empty = match series.dtype() {
+            DataType::Utf8 => series
+                .utf8()?
+                .into_iter()
+                .all(|value| value.map_or(true, str::is_empty)),
+            _ => series.null_count() == series.len(),
+        };
+
+        if is_empty {
+            let series = Series::full_null(name.as_str(), series.len(), dtype);
+            df.with_column(series)?;
+        }
+    }
+
+    Ok(ExDataFrame::new(df))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn read_csv_file(
     filename: &str,
     infer_schema_length: Option<usize>,
     has_header: bool,
@@ -47,107 +480,4986 @@ pub fn df_from_csv(
     skip_rows: usize,
     projection: Option<Vec<usize>>,
     delimiter_as_byte: u8,
+    quote_char: Option<u8>,
     do_rechunk: bool,
     column_names: Option<Vec<String>>,
-    dtypes: Vec<(&str, &str)>,
+    mut dtypes: Arc<Schema>,
     encoding: &str,
     null_vals: Vec<String>,
     parse_dates: bool,
     eol_delimiter: Option<u8>,
+    low_memory: bool,
+    truncate_ragged_lines: bool,
+    true_values: Vec<String>,
+    false_values: Vec<String>,
+    binary_encoding: Option<&str>,
+    all_strings: bool,
+    strip_bom: bool,
+    date_formats: CsvDateFormats,
+    special_floats: CsvSpecialFloats,
 ) -> Result<ExDataFrame, ExplorerError> {
-    let encoding = match encoding {
-        "utf8-lossy" => CsvEncoding::LossyUtf8,
-        _ => CsvEncoding::Utf8,
+    let infer_schema_length = csv_infer_schema_length(infer_schema_length, all_strings);
+    let parse_dates = parse_dates && !all_strings;
+    let booleans = CsvBooleans::new(&mut dtypes, true_values, false_values);
+    let binaries = CsvBinaries::new(&mut dtypes, decode_csv_binary_encoding(binary_encoding)?);
+    let temporals = CsvTemporals::new(&mut dtypes, date_formats);
+    let floats = CsvFloats::new(&mut dtypes, special_floats);
+    let (column_names, new_names) = project_csv_columns(&projection, column_names)?;
+
+    let df = finish_csv_reader(&booleans, &binaries, || {
+        let (file, csv_encoding) = open_csv_file(filename, encoding, strip_bom)?;
+
+        Ok(CsvReader::new(file)
+            .infer_schema(infer_schema_length)
+            .has_header(has_header)
+            .with_try_parse_dates(parse_dates)
+            .with_n_rows(stop_after_n_rows)
+            .with_delimiter(delimiter_as_byte)
+            .with_quote_char(quote_char)
+            .with_skip_rows(skip_rows)
+            .with_projection(projection.clone())
+            .with_rechunk(do_rechunk)
+            .with_encoding(csv_encoding)
+            .with_columns(column_names.clone())
+            .with_dtypes(Some(dtypes.clone()))
+            .with_null_values(Some(NullValues::AllColumns(null_vals.clone())))
+            .with_end_of_line_char(eol_delimiter.unwrap_or(b'\n'))
+            .low_memory(low_memory)
+            .truncate_ragged_lines(truncate_ragged_lines))
+    })?;
+
+    let mut df = df.clone_inner();
+    temporals.parse(&mut df)?;
+    floats.parse(&mut df)?;
+
+    if strip_bom {
+        strip_bom_from_names(&mut df)?;
+    }
+
+    match new_names {
+        None => Ok(ExDataFrame::new(df)),
+        Some(new_names) => {
+            if new_names.len() != df.width() {
+                return Err(ExplorerError::Other(format!(
+                    "expected {} column names for the projected columns, but got {}",
+                    df.width(),
+                    new_names.len()
+                )));
+            }
+
+            df.set_column_names(&new_names)?;
+            Ok(ExDataFrame::new(df))
+        }
+    }
+}
+
+// An inference length of `None` infers the dtypes from every row of the
+// file, so values that only show up near the end, like a late float in an
+// integer column, are taken into account. It is slower, as the file is
+// read twice. A length of zero reads all columns as strings, which is
+// faster than inferring the dtypes. The declared dtypes still apply.
+fn csv_infer_schema_length(infer_schema_length: Option<usize>, all_strings: bool) -> Option<usize> {
+    if all_strings {
+        Some(0)
+    } else {
+        infer_schema_length
+    }
+}
+
+// Splits the `column_names` into the names to select, and the new names
+// for the projected columns, which are only given with a projection.
+// Giving both to Polars would select the columns twice.
+#[allow(clippy::type_complexity)]
+fn project_csv_columns(
+    projection: &Option<Vec<usize>>,
+    column_names: Option<Vec<String>>,
+) -> Result<(Option<Vec<String>>, Option<Vec<String>>), ExplorerError> {
+    match (projection, column_names) {
+        (Some(projection), Some(column_names)) => {
+            if projection.len() != column_names.len() {
+                return Err(ExplorerError::Other(format!(
+                    "the projection has {} columns, but {} column names were given",
+                    projection.len(),
+                    column_names.len()
+                )));
+            }
+
+            Ok((None, Some(column_names)))
+        }
+        (_, column_names) => Ok((column_names, None)),
+    }
+}
+
+// Polars tells which value failed to parse, but not in which row.
+// So when a read fails, we read the CSV again with every column as
+// a string and look for the first value that does not parse to the
+// expected dtype. If none is found, the original error is returned.
+fn finish_csv_reader<'a, R>(
+    booleans: &CsvBooleans,
+    binaries: &CsvBinaries,
+    new_reader: impl Fn() -> Result<CsvReader<'a, R>, ExplorerError>,
+) -> Result<ExDataFrame, ExplorerError>
+where
+    R: polars::io::mmap::MmapBytesReader + 'a,
+{
+    match new_reader()?.finish() {
+        Ok(mut df) => {
+            booleans.parse(&mut df)?;
+            binaries.parse(&mut df)?;
+
+            Ok(ExDataFrame::new(normalize_numeric_dtypes(&mut df)?))
+        }
+        Err(error) => match locate_csv_parse_error(&new_reader) {
+            Ok(Some(parse_error)) => Err(parse_error),
+            _ => Err(error.into()),
+        },
+    }
+}
+
+// Polars only parses "true" and "false" as booleans. When custom spellings
+// are given, the declared boolean columns are read as strings instead, and
+// converted after the read. Values that match neither set become nil.
+struct CsvBooleans {
+    columns: Vec<String>,
+    true_values: Vec<String>,
+    false_values: Vec<String>,
+}
+
+impl CsvBooleans {
+    fn new(dtypes: &mut Arc<Schema>, true_values: Vec<String>, false_values: Vec<String>) -> Self {
+        let mut columns = vec![];
+
+        if !true_values.is_empty() || !false_values.is_empty() {
+            let schema = Arc::make_mut(dtypes);
+
+            for (name, dtype) in schema.iter() {
+                if dtype == &DataType::Boolean {
+                    columns.push(name.to_string());
+                }
+            }
+
+            for name in &columns {
+                schema.with_column(name.into(), DataType::Utf8);
+            }
+        }
+
+        Self {
+            columns,
+            true_values,
+            false_values,
+        }
+    }
+
+    fn parse(&self, df: &mut DataFrame) -> Result<(), ExplorerError> {
+        for name in &self.columns {
+            // The column may have been left out by the projection.
+            if !df.get_column_names().contains(&name.as_str()) {
+                continue;
+            }
+
+            let mut series = df
+                .column(name)?
+                .utf8()?
+                .into_iter()
+                .map(|value| value.and_then(|value| self.parse_value(value)))
+                .collect::<BooleanChunked>()
+                .into_series();
+
+            series.rename(name);
+            df.with_column(series)?;
+        }
+
+        Ok(())
+    }
+
+    fn parse_value(&self, value: &str) -> Option<bool> {
+        if self
+            .true_values
+            .iter()
+            .any(|true_value| true_value == value)
+        {
+            Some(true)
+        } else if self
+            .false_values
+            .iter()
+            .any(|false_value| false_value == value)
+        {
+            Some(false)
+        } else {
+            None
+        }
+    }
+}
+
+// Binary columns cannot be represented in a CSV as they are, so they are
+// written as hex or base64 text. When reading, the declared binary columns
+// are read as strings and decoded after the read, the same way as `CsvBooleans`.
+#[derive(Clone, Copy)]
+enum CsvBinaryEncoding {
+    Hex,
+    Base64,
+}
+
+impl CsvBinaryEncoding {
+    fn encode(self, value: &[u8]) -> String {
+        match self {
+            Self::Hex => value.iter().map(|byte| format!("{byte:02x}")).collect(),
+            Self::Base64 => BASE64.encode(value),
+        }
+    }
+
+    fn decode(self, value: &str) -> Option<Vec<u8>> {
+        match self {
+            Self::Hex => {
+                if value.len() % 2 != 0 || !value.is_ascii() {
+                    return None;
+                }
+
+                (0..value.len())
+                    .step_by(2)
+                    .map(|index| u8::from_str_radix(&value[index..index + 2], 16).ok())
+                    .collect()
+            }
+            Self::Base64 => BASE64.decode(value).ok(),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Hex => "hex",
+            Self::Base64 => "base64",
+        }
+    }
+}
+
+fn decode_csv_binary_encoding(
+    encoding: Option<&str>,
+) -> Result<Option<CsvBinaryEncoding>, ExplorerError> {
+    match encoding {
+        None => Ok(None),
+        Some("hex") => Ok(Some(CsvBinaryEncoding::Hex)),
+        Some("base64") => Ok(Some(CsvBinaryEncoding::Base64)),
+        Some(other) => Err(ExplorerError::Other(format!(
+            "the binary encoding {other} is not supported for CSV. \
+            The supported binary encodings are: hex and base64"
+        ))),
+    }
+}
+
+struct CsvBinaries {
+    columns: Vec<String>,
+    encoding: Option<CsvBinaryEncoding>,
+}
+
+impl CsvBinaries {
+    fn new(dtypes: &mut Arc<Schema>, encoding: Option<CsvBinaryEncoding>) -> Self {
+        let mut columns = vec![];
+
+        if encoding.is_some() {
+            let schema = Arc::make_mut(dtypes);
+
+            for (name, dtype) in schema.iter() {
+                if dtype == &DataType::Binary {
+                    columns.push(name.to_string());
+                }
+            }
+
+            for name in &columns {
+                schema.with_column(name.into(), DataType::Utf8);
+            }
+        }
+
+        Self { columns, encoding }
+    }
+
+    fn parse(&self, df: &mut DataFrame) -> Result<(), ExplorerError> {
+        let encoding = match self.encoding {
+            Some(encoding) => encoding,
+            None => return Ok(()),
+        };
+
+        for name in &self.columns {
+            // The column may have been left out by the projection.
+            if !df.get_column_names().contains(&name.as_str()) {
+                continue;
+            }
+
+            let values = df.column(name)?.utf8()?;
+            let mut decoded = Vec::with_capacity(values.len());
+
+            for (row, value) in values.into_iter().enumerate() {
+                match value.map(|value| encoding.decode(value)) {
+                    Some(None) => {
+                        return Err(ExplorerError::CsvParse {
+                            column: name.to_string(),
+                            row,
+                            value: value.unwrap_or_default().to_string(),
+                            dtype: format!("binary ({})", encoding.name()),
+                        })
+                    }
+                    Some(bytes) => decoded.push(bytes),
+                    None => decoded.push(None),
+                }
+            }
+
+            let mut series = decoded.into_iter().collect::<BinaryChunked>().into_series();
+
+            series.rename(name);
+            df.with_column(series)?;
+        }
+
+        Ok(())
+    }
+}
+
+// Tools spell NaN and infinity differently, like "NaN", "nan" or "?". These
+// are the spellings to read and write, besides the ones Polars understands.
+// Negative infinity is spelled as infinity with a leading "-". When writing,
+// the first spelling of each is used. With `as_null`, they are read as nils.
+#[derive(Default)]
+struct CsvSpecialFloats {
+    nan_values: Vec<String>,
+    inf_values: Vec<String>,
+    as_null: bool,
+}
+
+impl CsvSpecialFloats {
+    fn for_writing(nan_value: Option<String>, inf_value: Option<String>) -> Self {
+        Self {
+            nan_values: nan_value.into_iter().collect(),
+            inf_values: inf_value.into_iter().collect(),
+            as_null: false,
+        }
+    }
+
+    // Writes the float columns as strings, with the special values spelled
+    // as given. The other values are written the same as without spellings.
+    fn encode(&self, data: &mut DataFrame) -> Result<(), ExplorerError> {
+        let (nan, inf) = (self.nan_values.first(), self.inf_values.first());
+
+        if nan.is_none() && inf.is_none() {
+            return Ok(());
+        }
+
+        let names: Vec<String> = data
+            .get_columns()
+            .iter()
+            .filter(|series| series.dtype().is_float())
+            .map(|series| series.name().to_string())
+            .collect();
+
+        for name in names {
+            let column = data.column(&name)?;
+            let floats = column.cast(&DataType::Float64)?;
+            let strings = column.cast(&DataType::Utf8)?;
+
+            let mut series = floats
+                .f64()?
+                .into_iter()
+                .zip(strings.utf8()?)
+                .map(|(float, string)| match (float, nan, inf) {
+                    (Some(float), Some(nan), _) if float.is_nan() => Some(nan.clone()),
+                    (Some(float), _, Some(inf)) if float == f64::INFINITY => Some(inf.clone()),
+                    (Some(float), _, Some(inf)) if float == f64::NEG_INFINITY => {
+                        Some(format!("-{inf}"))
+                    }
+                    _ => string.map(|string| string.to_string()),
+                })
+                .collect::<Utf8Chunked>()
+                .into_series();
+
+            series.rename(&name);
+            data.with_column(series)?;
+        }
+
+        Ok(())
+    }
+}
+
+// When spellings are given, the declared float columns are read as strings
+// and parsed after the read, the same way as `CsvBooleans`. Those columns
+// are always read as `f64`, like the normalized columns.
+struct CsvFloats {
+    columns: Vec<String>,
+    special_floats: CsvSpecialFloats,
+}
+
+impl CsvFloats {
+    fn new(dtypes: &mut Arc<Schema>, special_floats: CsvSpecialFloats) -> Self {
+        let mut columns = vec![];
+
+        if !special_floats.nan_values.is_empty() || !special_floats.inf_values.is_empty() {
+            let schema = Arc::make_mut(dtypes);
+
+            for (name, dtype) in schema.iter() {
+                if dtype.is_float() {
+                    columns.push(name.to_string());
+                }
+            }
+
+            for name in &columns {
+                schema.with_column(name.into(), DataType::Utf8);
+            }
+        }
+
+        Self {
+            columns,
+            special_floats,
+        }
+    }
+
+    fn parse(&self, df: &mut DataFrame) -> Result<(), ExplorerError> {
+        for name in &self.columns {
+            // The column may have been left out by the projection.
+            if !df.get_column_names().contains(&name.as_str()) {
+                continue;
+            }
+
+            let values = df.column(name)?.utf8()?;
+            let mut parsed = Vec::with_capacity(values.len());
+
+            for (row, value) in values.into_iter().enumerate() {
+                match value.map(|value| self.parse_value(value)) {
+                    Some(None) => {
+                        return Err(ExplorerError::CsvParse {
+                            column: name.to_string(),
+                            row,
+                            value: value.unwrap_or_default().to_string(),
+                            dtype: "f64".to_string(),
+                        })
+                    }
+                    Some(Some(float)) => parsed.push(float),
+                    None => parsed.push(None),
+                }
+            }
+
+            let mut series = parsed.into_iter().collect::<Float64Chunked>().into_series();
+
+            series.rename(name);
+            df.with_column(series)?;
+        }
+
+        Ok(())
+    }
+
+    // Returns `None` when the value is not a float, and `Some(None)` when
+    // it is a special value read as nil.
+    fn parse_value(&self, value: &str) -> Option<Option<f64>> {
+        let special = |float: f64| Some(Some(float).filter(|_| !self.special_floats.as_null));
+        let is_inf = |value: &str| {
+            self.special_floats
+                .inf_values
+                .iter()
+                .any(|inf| inf == value)
+        };
+
+        if self
+            .special_floats
+            .nan_values
+            .iter()
+            .any(|nan| nan == value)
+        {
+            special(f64::NAN)
+        } else if is_inf(value) || value.strip_prefix('+').map_or(false, |rest| is_inf(rest)) {
+            special(f64::INFINITY)
+        } else if value.strip_prefix('-').map_or(false, |rest| is_inf(rest)) {
+            special(f64::NEG_INFINITY)
+        } else {
+            value.trim().parse::<f64>().ok().map(Some)
+        }
+    }
+}
+
+// The formats used to parse the declared date, datetime and time columns.
+#[derive(Default)]
+struct CsvDateFormats {
+    date: Option<String>,
+    datetime: Option<String>,
+    time: Option<String>,
+    // When false, the values that do not match the format become nil.
+    strict: bool,
+}
+
+// `with_try_parse_dates` guesses the format of each column, which can't
+// tell "03/04/2024" apart from "04/03/2024". When a format is given, the
+// declared columns of its dtype are read as strings and parsed with it
+// after the read, the same way as `CsvBooleans`.
+struct CsvTemporals {
+    columns: Vec<(String, DataType, String)>,
+    strict: bool,
+}
+
+impl CsvTemporals {
+    fn new(dtypes: &mut Arc<Schema>, formats: CsvDateFormats) -> Self {
+        let mut columns = vec![];
+
+        for (name, dtype) in dtypes.iter() {
+            let format = match dtype {
+                DataType::Date => &formats.date,
+                DataType::Datetime(_, _) => &formats.datetime,
+                DataType::Time => &formats.time,
+                _ => continue,
+            };
+
+            if let Some(format) = format {
+                columns.push((name.to_string(), dtype.clone(), format.clone()));
+            }
+        }
+
+        if !columns.is_empty() {
+            let schema = Arc::make_mut(dtypes);
+
+            for (name, _, _) in &columns {
+                schema.with_column(name.into(), DataType::Utf8);
+            }
+        }
+
+        Self {
+            columns,
+            strict: formats.strict,
+        }
+    }
+
+    fn parse(&self, df: &mut DataFrame) -> Result<(), ExplorerError> {
+        for (name, dtype, format) in &self.columns {
+            // The column may have been left out by the projection.
+            if !df.get_column_names().contains(&name.as_str()) {
+                continue;
+            }
+
+            let values = df.column(name)?.utf8()?;
+
+            // Datetimes are parsed at the declared time unit, so `datetime[ns]`
+            // keeps its nanoseconds, and then cast to set the timezone.
+            let mut series = match dtype {
+                DataType::Date => values.as_date(Some(format), false)?.into_series(),
+                DataType::Datetime(time_unit, _) => values
+                    .as_datetime(Some(format), *time_unit, false, format.contains("%z"), None)?
+                    .into_series()
+                    .cast(dtype)?,
+                _ => values.as_time(Some(format), false)?.into_series(),
+            };
+
+            if self.strict {
+                let is_invalid = &values.is_not_null() & &series.is_null();
+
+                if let Some(row) = is_invalid
+                    .into_iter()
+                    .position(|invalid| invalid == Some(true))
+                {
+                    return Err(ExplorerError::CsvParse {
+                        column: name.to_string(),
+                        row,
+                        value: values.get(row).unwrap_or_default().to_string(),
+                        dtype: format!("{dtype} ({format})"),
+                    });
+                }
+            }
+
+            series.rename(name);
+            df.with_column(series)?;
+        }
+
+        Ok(())
+    }
+}
+
+// Replaces the binary columns by their text representation before writing.
+// Without an encoding, the binary columns are given to the writer as they are.
+fn encode_csv_binaries(
+    data: &mut DataFrame,
+    binary_encoding: Option<&str>,
+) -> Result<(), ExplorerError> {
+    let encoding = match decode_csv_binary_encoding(binary_encoding)? {
+        Some(encoding) => encoding,
+        None => return Ok(()),
     };
 
-    let reader = CsvReader::from_path(filename)?
-        .infer_schema(infer_schema_length)
-        .has_header(has_header)
-        .with_try_parse_dates(parse_dates)
-        .with_n_rows(stop_after_n_rows)
-        .with_delimiter(delimiter_as_byte)
-        .with_skip_rows(skip_rows)
-        .with_projection(projection)
-        .with_rechunk(do_rechunk)
-        .with_encoding(encoding)
-        .with_columns(column_names)
-        .with_dtypes(Some(schema_from_dtypes_pairs(dtypes)?))
-        .with_null_values(Some(NullValues::AllColumns(null_vals)))
-        .with_end_of_line_char(eol_delimiter.unwrap_or(b'\n'));
+    let names: Vec<String> = data
+        .get_columns()
+        .iter()
+        .filter(|series| series.dtype() == &DataType::Binary)
+        .map(|series| series.name().to_string())
+        .collect();
+
+    for name in names {
+        let mut series = data
+            .column(&name)?
+            .binary()?
+            .into_iter()
+            .map(|value| value.map(|value| encoding.encode(value)))
+            .collect::<Utf8Chunked>()
+            .into_series();
+
+        series.rename(&name);
+        data.with_column(series)?;
+    }
+
+    Ok(())
+}
+
+fn locate_csv_parse_error<'a, R>(
+    new_reader: &impl Fn() -> Result<CsvReader<'a, R>, ExplorerError>,
+) -> Result<Option<ExplorerError>, ExplorerError>
+where
+    R: polars::io::mmap::MmapBytesReader + 'a,
+{
+    let schema = new_reader()?.with_n_rows(Some(0)).finish()?.schema();
+
+    // An inference length of zero reads all columns as strings.
+    let strings = new_reader()?
+        .infer_schema(Some(0))
+        .with_dtypes(None)
+        .with_try_parse_dates(false)
+        .finish()?;
+
+    for (name, dtype) in schema.iter() {
+        let column = strings.column(name)?;
+        let values = column.utf8()?;
+
+        let is_invalid: BooleanChunked = match dtype {
+            DataType::Boolean => values
+                .into_iter()
+                .map(|value| {
+                    value.map(|value| {
+                        !value.eq_ignore_ascii_case("true") && !value.eq_ignore_ascii_case("false")
+                    })
+                })
+                .collect(),
+            dtype if dtype.is_numeric() => &values.is_not_null() & &column.cast(dtype)?.is_null(),
+            _ => continue,
+        };
+
+        if let Some(row) = is_invalid
+            .into_iter()
+            .position(|invalid| invalid == Some(true))
+        {
+            return Ok(Some(ExplorerError::CsvParse {
+                column: name.to_string(),
+                row,
+                value: values.get(row).unwrap_or_default().to_string(),
+                dtype: dtype.to_string(),
+            }));
+        }
+    }
+
+    Ok(None)
+}
+
+// Polars only knows how to read UTF-8 CSVs, so any other encoding
+// needs to be transcoded to UTF-8 before reaching the `CsvReader`.
+// When `detect_from_bom` is true, a BOM in the input takes precedence
+// over the given encoding, whether the BOM is stripped or not.
+enum ExCsvEncoding {
+    Native(CsvEncoding),
+    Transcoded {
+        encoding: &'static Encoding,
+        detect_from_bom: bool,
+    },
+}
+
+fn decode_csv_encoding(encoding: &str) -> Result<ExCsvEncoding, ExplorerError> {
+    match encoding {
+        "utf8" => Ok(ExCsvEncoding::Native(CsvEncoding::Utf8)),
+        "utf8-lossy" => Ok(ExCsvEncoding::Native(CsvEncoding::LossyUtf8)),
+        // The WHATWG Encoding Standard maps "latin1" to windows-1252,
+        // which is a superset of ISO-8859-1 for all printable characters.
+        "latin1" | "windows-1252" => Ok(ExCsvEncoding::Transcoded {
+            encoding: encoding_rs::WINDOWS_1252,
+            detect_from_bom: false,
+        }),
+        // Without a BOM, we assume little endian since that is what Windows tools produce.
+        "utf16" => Ok(ExCsvEncoding::Transcoded {
+            encoding: encoding_rs::UTF_16LE,
+            detect_from_bom: true,
+        }),
+        "utf16-le" => Ok(ExCsvEncoding::Transcoded {
+            encoding: encoding_rs::UTF_16LE,
+            detect_from_bom: false,
+        }),
+        "utf16-be" => Ok(ExCsvEncoding::Transcoded {
+            encoding: encoding_rs::UTF_16BE,
+            detect_from_bom: false,
+        }),
+        other => Err(ExplorerError::Other(format!(
+            "the encoding {other} is not supported for CSV. \
+            The supported encodings are: utf8, utf8-lossy, latin1, windows-1252, \
+            utf16, utf16-le and utf16-be"
+        ))),
+    }
+}
+
+// Opens the CSV file, transcoding it to UTF-8 first if needed.
+// Returns the encoding that the `CsvReader` should use for the file.
+//
+// With `strip_bom`, a UTF-8 file that starts with a UTF-16 BOM is
+// transcoded from UTF-16. A UTF-8 BOM is left in the file, which is read
+// as is, and stripped from the name of the first column after the read
+// instead. See `strip_bom_from_names`. Without it, the raw bytes are kept.
+fn open_csv_file(
+    filename: &str,
+    encoding: &str,
+    strip_bom: bool,
+) -> Result<(File, CsvEncoding), ExplorerError> {
+    match decode_csv_encoding(encoding)? {
+        ExCsvEncoding::Native(encoding) => {
+            if strip_bom && starts_with_utf16_bom(filename)? {
+                let file = transcode_file_to_utf8(filename, encoding_rs::UTF_8, true, true)?;
+                Ok((file, encoding))
+            } else {
+                Ok((open_file(filename)?, encoding))
+            }
+        }
+        ExCsvEncoding::Transcoded {
+            encoding,
+            detect_from_bom,
+        } => Ok((
+            transcode_file_to_utf8(filename, encoding, detect_from_bom, strip_bom)?,
+            CsvEncoding::Utf8,
+        )),
+    }
+}
+
+fn starts_with_utf16_bom(filename: &str) -> Result<bool, ExplorerError> {
+    let mut start = vec![];
+    open_file(filename)?.take(2).read_to_end(&mut start)?;
+
+    Ok(has_utf16_bom(&start))
+}
+
+fn has_utf16_bom(bytes: &[u8]) -> bool {
+    bytes.starts_with(b"\xFF\xFE") || bytes.starts_with(b"\xFE\xFF")
+}
+
+// A UTF-8 BOM that is read as part of the header ends up at the start of
+// the name of the first column. This avoids copying the file to skip it,
+// but the `column_names` given to the reader still see the BOM.
+fn strip_bom_from_names(df: &mut DataFrame) -> Result<(), ExplorerError> {
+    let first_name = df.get_column_names().first().map(|name| name.to_string());
+
+    if let Some(name) = first_name {
+        if let Some(stripped) = name.strip_prefix('\u{feff}') {
+            df.rename(&name, stripped)?;
+        }
+    }
+
+    Ok(())
+}
+
+// Streams the transcoded contents into an anonymous temporary file,
+// so we don't need to hold the entire file in memory.
+fn transcode_file_to_utf8(
+    filename: &str,
+    encoding: &'static Encoding,
+    detect_from_bom: bool,
+    strip_bom: bool,
+) -> Result<File, ExplorerError> {
+    let file = open_file(filename)?;
+    let mut decoder = DecodeReaderBytesBuilder::new()
+        .encoding(Some(encoding))
+        .bom_override(detect_from_bom)
+        .strip_bom(strip_bom)
+        .build(BufReader::new(file));
+
+    let mut transcoded = tempfile::tempfile()?;
+    std::io::copy(&mut decoder, &mut transcoded)?;
+    transcoded.seek(SeekFrom::Start(0))?;
+
+    Ok(transcoded)
+}
+
+// Same as `open_csv_file`, but for the contents of a CSV in memory.
+fn decode_csv_bytes<'a>(
+    bytes: &'a [u8],
+    encoding: &str,
+    strip_bom: bool,
+) -> Result<(Cow<'a, [u8]>, CsvEncoding), ExplorerError> {
+    match decode_csv_encoding(encoding)? {
+        ExCsvEncoding::Native(encoding) => {
+            if strip_bom && has_utf16_bom(bytes) {
+                let transcoded = transcode_bytes_to_utf8(bytes, encoding_rs::UTF_8, true, true);
+                Ok((Cow::Owned(transcoded), encoding))
+            } else if strip_bom && bytes.starts_with(UTF8_BOM) {
+                Ok((Cow::Borrowed(&bytes[UTF8_BOM.len()..]), encoding))
+            } else {
+                Ok((Cow::Borrowed(bytes), encoding))
+            }
+        }
+        ExCsvEncoding::Transcoded {
+            encoding,
+            detect_from_bom,
+        } => Ok((
+            Cow::Owned(transcode_bytes_to_utf8(
+                bytes,
+                encoding,
+                detect_from_bom,
+                strip_bom,
+            )),
+            CsvEncoding::Utf8,
+        )),
+    }
+}
+
+fn transcode_bytes_to_utf8(
+    bytes: &[u8],
+    encoding: &'static Encoding,
+    detect_from_bom: bool,
+    strip_bom: bool,
+) -> Vec<u8> {
+    // The BOM decides the encoding even when it is kept, like the endianness
+    // of UTF-16, so it is sniffed before deciding whether to strip it.
+    let encoding = match Encoding::for_bom(bytes) {
+        Some((bom_encoding, _bom_length)) if detect_from_bom => bom_encoding,
+        _ => encoding,
+    };
+
+    let (decoded, _had_errors) = if strip_bom {
+        encoding.decode_with_bom_removal(bytes)
+    } else {
+        encoding.decode_without_bom_handling(bytes)
+    };
+
+    decoded.into_owned().into_bytes()
+}
+
+pub fn schema_from_dtypes_pairs(dtypes: Vec<(&str, &str)>) -> Result<Arc<Schema>, ExplorerError> {
+    let mut schema = Schema::new();
+    for (name, dtype_str) in dtypes {
+        let dtype = dtype_from_str(dtype_str)?;
+        schema.with_column(name.into(), dtype);
+    }
+    Ok(Arc::new(schema))
+}
+
+// Parses the dtypes once into a schema that can be given to
+// `df_from_csv_with_schema` as many times as needed.
+#[rustler::nif]
+pub fn schema_build(dtypes: Vec<(&str, &str)>) -> Result<ResourceArc<ExSchemaRef>, ExplorerError> {
+    Ok(ResourceArc::new(ExSchemaRef::new(
+        schema_from_dtypes_pairs(dtypes)?,
+    )))
+}
+
+fn dtype_from_str(dtype: &str) -> Result<DataType, ExplorerError> {
+    match dtype {
+        "binary" => Ok(DataType::Binary),
+        "bool" => Ok(DataType::Boolean),
+        "cat" => Ok(DataType::Categorical(None)),
+        "date" => Ok(DataType::Date),
+        "time" => Ok(DataType::Time),
+        "f64" => Ok(DataType::Float64),
+        "i64" => Ok(DataType::Int64),
+        "str" => Ok(DataType::Utf8),
+        other => {
+            if let Some(rest) = other.strip_prefix("list[") {
+                list_dtype_from_str(other, rest)
+            } else if let Some(rest) = other.strip_prefix("struct{") {
+                struct_dtype_from_str(other, rest)
+            } else if let Some(params) = other
+                .strip_prefix("datetime[")
+                .and_then(|rest| rest.strip_suffix(']'))
+            {
+                datetime_dtype_from_str(params)
+            } else if let Some(unit) = other
+                .strip_prefix("duration[")
+                .and_then(|rest| rest.strip_suffix(']'))
+            {
+                Ok(DataType::Duration(time_unit_from_str(
+                    unit.trim(),
+                    "duration",
+                )?))
+            } else if let Some(params) = other
+                .strip_prefix("decimal[")
+                .and_then(|rest| rest.strip_suffix(']'))
+            {
+                decimal_dtype_from_str(other, params)
+            } else {
+                Err(ExplorerError::Internal("Unrecognised datatype".into()))
+            }
+        }
+    }
+}
+
+// Parses the inner type of a list, like "i64" in "list[i64]".
+// The inner type may be a list itself, as in "list[list[str]]".
+fn list_dtype_from_str(dtype: &str, rest: &str) -> Result<DataType, ExplorerError> {
+    match rest.strip_suffix(']') {
+        Some(inner) if !inner.is_empty() => match dtype_from_str(inner) {
+            Ok(inner_dtype) => Ok(DataType::List(Box::new(inner_dtype))),
+            Err(error) => Err(ExplorerError::Other(format!(
+                "invalid inner type {inner} in the list dtype {dtype}: {error}"
+            ))),
+        },
+        _ => Err(ExplorerError::Other(format!(
+            "invalid list dtype {dtype}. Expected the syntax list[<type>]"
+        ))),
+    }
+}
+
+// The inverse of `dtype_from_str`. Numeric dtypes are normalized
+// in the same way as `normalize_numeric_dtypes` does.
+pub fn dtype_to_str(dtype: &DataType) -> Result<String, ExplorerError> {
+    let dtype_str = match dtype {
+        DataType::Binary => "binary".to_string(),
+        DataType::Boolean => "bool".to_string(),
+        DataType::Categorical(_) => "cat".to_string(),
+        DataType::Date => "date".to_string(),
+        DataType::Time => "time".to_string(),
+        DataType::Datetime(time_unit, timezone) => {
+            let unit = time_unit_to_str(time_unit);
+
+            match timezone {
+                Some(tz) => format!("datetime[{unit},{tz}]"),
+                None => format!("datetime[{unit}]"),
+            }
+        }
+        DataType::Duration(time_unit) => format!("duration[{}]", time_unit_to_str(time_unit)),
+        DataType::Float32 | DataType::Float64 => "f64".to_string(),
+        DataType::UInt8
+        | DataType::UInt16
+        | DataType::UInt32
+        | DataType::Int8
+        | DataType::Int16
+        | DataType::Int32
+        | DataType::Int64 => "i64".to_string(),
+        DataType::Utf8 => "str".to_string(),
+        DataType::List(inner) => format!("list[{}]", dtype_to_str(inner)?),
+        DataType::Struct(fields) => {
+            let fields = fields
+                .iter()
+                .map(|field| {
+                    Ok(format!(
+                        "{}:{}",
+                        field.name(),
+                        dtype_to_str(field.data_type())?
+                    ))
+                })
+                .collect::<Result<Vec<_>, ExplorerError>>()?;
+
+            format!("struct{{{}}}", fields.join(","))
+        }
+        other => {
+            return Err(ExplorerError::Other(format!(
+                "cannot represent the datatype {other} as a dtype string"
+            )))
+        }
+    };
+
+    Ok(dtype_str)
+}
+
+// Parses the fields of a struct, like "name:str,tags:list[str]" in
+// "struct{name:str,tags:list[str]}". Each field type is parsed recursively.
+fn struct_dtype_from_str(dtype: &str, rest: &str) -> Result<DataType, ExplorerError> {
+    let invalid_struct = || {
+        ExplorerError::Other(format!(
+            "invalid struct dtype {dtype}. Expected the syntax struct{{<name>:<type>,...}}"
+        ))
+    };
+
+    let inner = rest.strip_suffix('}').ok_or_else(invalid_struct)?;
+    let mut fields = vec![];
+
+    for field in split_top_level_commas(inner).ok_or_else(invalid_struct)? {
+        let (name, field_dtype) = field.split_once(':').ok_or_else(invalid_struct)?;
+        let name = name.trim();
+
+        if name.is_empty() {
+            return Err(invalid_struct());
+        }
+
+        match dtype_from_str(field_dtype.trim()) {
+            Ok(field_dtype) => fields.push(Field::new(name, field_dtype)),
+            Err(error) => {
+                return Err(ExplorerError::Other(format!(
+                    "invalid type {} for the field {name} in the struct dtype {dtype}: {error}",
+                    field_dtype.trim()
+                )))
+            }
+        }
+    }
+
+    if fields.is_empty() {
+        return Err(invalid_struct());
+    }
+
+    Ok(DataType::Struct(fields))
+}
+
+// Splits on the commas that are not nested inside brackets or braces,
+// so "a:i64,b:datetime[ms,UTC]" becomes ["a:i64", "b:datetime[ms,UTC]"].
+// Returns `None` if the brackets and braces are unbalanced.
+fn split_top_level_commas(value: &str) -> Option<Vec<&str>> {
+    let mut parts = vec![];
+    let mut closing = vec![];
+    let mut start = 0;
+
+    for (index, character) in value.char_indices() {
+        match character {
+            '[' => closing.push(']'),
+            '{' => closing.push('}'),
+            ']' | '}' => {
+                if closing.pop() != Some(character) {
+                    return None;
+                }
+            }
+            ',' if closing.is_empty() => {
+                parts.push(&value[start..index]);
+                start = index + 1;
+            }
+            _ => (),
+        }
+    }
+
+    if !closing.is_empty() {
+        return None;
+    }
+
+    parts.push(&value[start..]);
+    Some(parts)
+}
+
+// Parses the parameters of a datetime dtype, which is the time unit
+// optionally followed by a timezone, like in "datetime[ms,America/New_York]".
+fn datetime_dtype_from_str(params: &str) -> Result<DataType, ExplorerError> {
+    let (unit, timezone) = match params.split_once(',') {
+        Some((unit, timezone)) => (unit.trim(), Some(timezone.trim())),
+        None => (params.trim(), None),
+    };
+
+    let time_unit = time_unit_from_str(unit, "datetime")?;
+
+    let timezone = match timezone {
+        Some(tz) => match tz.parse::<chrono_tz::Tz>() {
+            Ok(_) => Some(tz.to_string()),
+            Err(_) => {
+                return Err(ExplorerError::Other(format!(
+                    "unknown timezone {tz} for datetime"
+                )))
+            }
+        },
+        None => None,
+    };
+
+    Ok(DataType::Datetime(time_unit, timezone))
+}
+
+fn time_unit_from_str(unit: &str, dtype: &str) -> Result<TimeUnit, ExplorerError> {
+    match unit {
+        "ms" => Ok(TimeUnit::Milliseconds),
+        "ns" => Ok(TimeUnit::Nanoseconds),
+        "μs" => Ok(TimeUnit::Microseconds),
+        other => Err(ExplorerError::Other(format!(
+            "the time unit {other} is not supported for {dtype}"
+        ))),
+    }
+}
+
+fn time_unit_to_str(time_unit: &TimeUnit) -> &'static str {
+    match time_unit {
+        TimeUnit::Milliseconds => "ms",
+        TimeUnit::Nanoseconds => "ns",
+        TimeUnit::Microseconds => "μs",
+    }
+}
+
+// Parses the precision and scale of a decimal dtype, like in "decimal[38,4]".
+fn decimal_dtype_from_str(dtype: &str, params: &str) -> Result<DataType, ExplorerError> {
+    let parsed = params.split_once(',').and_then(|(precision, scale)| {
+        match (precision.trim().parse(), scale.trim().parse()) {
+            (Ok(precision), Ok(scale)) if scale <= precision => Some((precision, scale)),
+            _ => None,
+        }
+    });
+
+    match parsed {
+        Some((precision, scale)) => Ok(DataType::Decimal(Some(precision), Some(scale))),
+        None => Err(ExplorerError::Other(format!(
+            "invalid decimal dtype {dtype}. Expected the syntax decimal[<precision>,<scale>]"
+        ))),
+    }
+}
+
+// Some programs, like Excel on Windows, need the BOM to detect that a CSV is UTF-8.
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+// When `append` is true and the file already has contents, the rows are
+// appended to it without writing the header (or the BOM) again.
+//
+// The `binary_encoding` writes the binary columns as "hex" or "base64" text.
+// The `line_terminator` is either "\n" (the default) or "\r\n".
+// The `header_names` replace the column names in the header row.
+#[rustler::nif(schedule = "DirtyIo")]
+#[allow(clippy::too_many_arguments)]
+pub fn df_to_csv(
+    data: ExDataFrame,
+    filename: &str,
+    has_headers: bool,
+    delimiter: u8,
+    include_bom: bool,
+    append: bool,
+    columns: Option<Vec<String>>,
+    binary_encoding: Option<&str>,
+    line_terminator: Option<String>,
+    header_names: Option<Vec<String>>,
+    trailing_newline: bool,
+    nan_value: Option<String>,
+    inf_value: Option<String>,
+) -> Result<(), ExplorerError> {
+    write_csv_file(
+        &data,
+        filename,
+        has_headers,
+        delimiter,
+        true,
+        include_bom,
+        append,
+        columns,
+        binary_encoding,
+        line_terminator,
+        header_names,
+        trailing_newline,
+        CsvSpecialFloats::for_writing(nan_value, inf_value),
+    )
+}
+
+// Same as `df_to_csv`, but with the tab delimiter and without quoting,
+// so quotes are written as regular characters, like `df_from_tsv` reads them.
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_to_tsv(
+    data: ExDataFrame,
+    filename: &str,
+    has_headers: bool,
+    include_bom: bool,
+    append: bool,
+    columns: Option<Vec<String>>,
+) -> Result<(), ExplorerError> {
+    write_csv_file(
+        &data,
+        filename,
+        has_headers,
+        b'\t',
+        false,
+        include_bom,
+        append,
+        columns,
+        None,
+        None,
+        None,
+        true,
+        CsvSpecialFloats::default(),
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn write_csv_file(
+    data: &DataFrame,
+    filename: &str,
+    has_headers: bool,
+    delimiter: u8,
+    quoting: bool,
+    include_bom: bool,
+    append: bool,
+    columns: Option<Vec<String>>,
+    binary_encoding: Option<&str>,
+    line_terminator: Option<String>,
+    header_names: Option<Vec<String>>,
+    trailing_newline: bool,
+    special_floats: CsvSpecialFloats,
+) -> Result<(), ExplorerError> {
+    let line_terminator = decode_csv_line_terminator(line_terminator)?;
+    let mut data = prepare_csv_data(
+        data,
+        columns,
+        binary_encoding,
+        header_names,
+        &special_floats,
+    )?;
+    let appending = append && is_non_empty_file(filename)?;
+
+    if appending && has_headers {
+        check_csv_header(&data, filename, delimiter)?;
+    }
+
+    let file = if appending {
+        OpenOptions::new()
+            .append(true)
+            .open(filename)
+            .map_err(|error| ExplorerError::file(Path::new(filename), "writing", error))?
+    } else {
+        create_file(filename)?
+    };
+
+    write_csv(
+        BufWriter::new(file),
+        &mut data,
+        has_headers && !appending,
+        delimiter,
+        quoting,
+        include_bom && !appending,
+        line_terminator,
+        trailing_newline,
+    )
+}
+
+// Selects the columns, encodes the binaries and special floats, and renames
+// the header of the dataframe to write, in the same way for all CSV writers.
+fn prepare_csv_data(
+    data: &DataFrame,
+    columns: Option<Vec<String>>,
+    binary_encoding: Option<&str>,
+    header_names: Option<Vec<String>>,
+    special_floats: &CsvSpecialFloats,
+) -> Result<DataFrame, ExplorerError> {
+    let mut data = select_csv_columns(data, columns)?;
+    encode_csv_binaries(&mut data, binary_encoding)?;
+    special_floats.encode(&mut data)?;
+    rename_csv_header(&mut data, header_names)?;
+    Ok(data)
+}
+
+// All CSV writers, local or not, go through here, so they support the same options.
+// Note that appending to a file written without the trailing newline joins the
+// first new row with the last existing one.
+//
+// Without `quoting`, as in TSV files, no field is ever quoted, so the values
+// that contain the delimiter or a line break are rejected instead.
+#[allow(clippy::too_many_arguments)]
+fn write_csv<W: Write>(
+    mut writer: W,
+    data: &mut DataFrame,
+    has_headers: bool,
+    delimiter: u8,
+    quoting: bool,
+    include_bom: bool,
+    line_terminator: String,
+    trailing_newline: bool,
+) -> Result<(), ExplorerError> {
+    if include_bom {
+        writer.write_all(UTF8_BOM)?;
+    }
+
+    let held_back = if trailing_newline {
+        0
+    } else {
+        line_terminator.len()
+    };
+    let quote_char = if quoting {
+        b'"'
+    } else {
+        check_unquoted_csv_fields(data, has_headers, delimiter)?;
+        CSV_NO_QUOTE_CHAR
+    };
+
+    let mut writer = HoldBackWriter::new(writer, held_back);
+
+    CsvWriter::new(&mut writer)
+        .has_header(has_headers)
+        .with_delimiter(delimiter)
+        .with_quoting_char(quote_char)
+        .with_line_terminator(line_terminator)
+        .finish(data)?;
+
+    writer.flush()?;
+    Ok(())
+}
+
+// The byte 0xFF never appears in UTF-8 text, so with it as the quote char,
+// Polars never finds a quote to escape. Together with the check below, no
+// field is quoted.
+const CSV_NO_QUOTE_CHAR: u8 = 0xFF;
+
+fn check_unquoted_csv_fields(
+    data: &DataFrame,
+    has_headers: bool,
+    delimiter: u8,
+) -> Result<(), ExplorerError> {
+    let needs_quotes = |value: &str| {
+        value
+            .bytes()
+            .any(|byte| byte == delimiter || byte == b'\n' || byte == b'\r')
+    };
+
+    for series in data.get_columns() {
+        if has_headers && needs_quotes(series.name()) {
+            return Err(ExplorerError::Other(format!(
+                "cannot write the column name {:?} without quoting, \
+                as it contains the delimiter or a line break",
+                series.name()
+            )));
+        }
+
+        let strings = match series.dtype() {
+            DataType::Utf8 => series.clone(),
+            DataType::Categorical(_) => series.cast(&DataType::Utf8)?,
+            _ => continue,
+        };
+
+        if let Some(value) = strings
+            .utf8()?
+            .into_iter()
+            .flatten()
+            .find(|value| needs_quotes(value))
+        {
+            return Err(ExplorerError::Other(format!(
+                "cannot write the value {value:?} of column {:?} without quoting, \
+                as it contains the delimiter or a line break",
+                series.name()
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+// Holds back the last `len` bytes written, which are never written to the
+// inner writer. Every row written by the CSV writer ends with the line
+// terminator, so this drops the trailing one without buffering the output.
+struct HoldBackWriter<W: Write> {
+    inner: W,
+    held: Vec<u8>,
+    len: usize,
+}
+
+impl<W: Write> HoldBackWriter<W> {
+    fn new(inner: W, len: usize) -> Self {
+        HoldBackWriter {
+            inner,
+            held: Vec::with_capacity(len),
+            len,
+        }
+    }
+}
+
+impl<W: Write> Write for HoldBackWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.len == 0 {
+            return self.inner.write(buf);
+        }
+
+        self.held.extend_from_slice(buf);
+
+        if self.held.len() > self.len {
+            let ready = self.held.len() - self.len;
+            self.inner.write_all(&self.held[..ready])?;
+            self.held.drain(..ready);
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+// Only the line endings that our readers understand are allowed,
+// so whatever we write can be read back with `eol_delimiter`.
+fn decode_csv_line_terminator(line_terminator: Option<String>) -> Result<String, ExplorerError> {
+    match line_terminator.as_deref() {
+        None | Some("\n") => Ok("\n".to_string()),
+        Some("\r\n") => Ok("\r\n".to_string()),
+        Some(other) => Err(ExplorerError::Other(format!(
+            "the line terminator {other:?} is not supported for CSV. \
+            The supported line terminators are: \"\\n\" and \"\\r\\n\""
+        ))),
+    }
+}
+
+// Selects the given columns, in the given order, so we can write a subset
+// of the dataframe without copying the data or changing the original.
+fn select_csv_columns(
+    data: &DataFrame,
+    columns: Option<Vec<String>>,
+) -> Result<DataFrame, ExplorerError> {
+    match columns {
+        None => Ok(data.clone()),
+        Some(columns) => {
+            let names = data.get_column_names();
+
+            if let Some(unknown) = columns.iter().find(|name| !names.contains(&name.as_str())) {
+                return Err(ExplorerError::Other(format!(
+                    "cannot write column {unknown:?} because it does not exist. \
+                    The existing columns are: {names:?}"
+                )));
+            }
+
+            Ok(data.select(columns)?)
+        }
+    }
+}
+
+// Renames the columns of the (already cloned) dataframe, so the header row
+// has the given names while the data rows stay the same.
+fn rename_csv_header(
+    data: &mut DataFrame,
+    header_names: Option<Vec<String>>,
+) -> Result<(), ExplorerError> {
+    match header_names {
+        None => Ok(()),
+        Some(header_names) => {
+            if header_names.len() != data.width() {
+                return Err(ExplorerError::Other(format!(
+                    "expected {} header names, but got {}",
+                    data.width(),
+                    header_names.len()
+                )));
+            }
+
+            data.set_column_names(&header_names)?;
+            Ok(())
+        }
+    }
+}
+
+fn is_non_empty_file(filename: &str) -> Result<bool, ExplorerError> {
+    match std::fs::metadata(filename) {
+        Ok(metadata) => Ok(metadata.len() > 0),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(error) => Err(error.into()),
+    }
+}
+
+// Makes sure we are not going to misalign the columns when appending to an existing CSV.
+// The file may start with the BOM written by `include_bom`, which is not part of the header.
+fn check_csv_header(data: &DataFrame, filename: &str, delimiter: u8) -> Result<(), ExplorerError> {
+    let mut existing = CsvReader::new(open_file(filename)?)
+        .has_header(true)
+        .with_delimiter(delimiter)
+        .with_n_rows(Some(0))
+        .finish()?;
+
+    strip_bom_from_names(&mut existing)?;
+
+    let existing_names = existing.get_column_names();
+    let names = data.get_column_names();
+
+    if existing_names != names {
+        return Err(ExplorerError::Other(format!(
+            "cannot append to {filename} because the columns do not match. \
+            Expected {existing_names:?}, but got {names:?}"
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "aws")]
+#[rustler::nif(schedule = "DirtyIo")]
+#[allow(clippy::too_many_arguments)]
+pub fn df_to_csv_cloud(
+    data: ExDataFrame,
+    ex_entry: ExS3Entry,
+    has_headers: bool,
+    delimiter: u8,
+    include_bom: bool,
+    columns: Option<Vec<String>>,
+    binary_encoding: Option<&str>,
+    line_terminator: Option<String>,
+    header_names: Option<Vec<String>>,
+    trailing_newline: bool,
+    nan_value: Option<String>,
+    inf_value: Option<String>,
+    skip_if_empty: bool,
+    progress_pid: Option<LocalPid>,
+) -> Result<Atom, ExplorerError> {
+    if skip_if_empty && data.height() == 0 {
+        return Ok(atoms::skipped());
+    }
+
+    let line_terminator = decode_csv_line_terminator(line_terminator)?;
+    let mut data = prepare_csv_data(
+        &data,
+        columns,
+        binary_encoding,
+        header_names,
+        &CsvSpecialFloats::for_writing(nan_value, inf_value),
+    )?;
+    let cloud_writer = build_aws_s3_cloud_writer(ex_entry, progress_pid)?;
+
+    write_csv(
+        cloud_writer,
+        &mut data,
+        has_headers,
+        delimiter,
+        true,
+        include_bom,
+        line_terminator,
+        trailing_newline,
+    )?;
+
+    Ok(atoms::written())
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+#[allow(clippy::too_many_arguments)]
+pub fn df_dump_csv(
+    env: Env,
+    data: ExDataFrame,
+    has_headers: bool,
+    delimiter: u8,
+    include_bom: bool,
+    columns: Option<Vec<String>>,
+    binary_encoding: Option<&str>,
+    line_terminator: Option<String>,
+    header_names: Option<Vec<String>>,
+    trailing_newline: bool,
+    nan_value: Option<String>,
+    inf_value: Option<String>,
+) -> Result<Binary, ExplorerError> {
+    dump_csv(
+        env,
+        &data,
+        has_headers,
+        delimiter,
+        true,
+        include_bom,
+        columns,
+        binary_encoding,
+        line_terminator,
+        header_names,
+        trailing_newline,
+        CsvSpecialFloats::for_writing(nan_value, inf_value),
+    )
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_dump_tsv(
+    env: Env,
+    data: ExDataFrame,
+    has_headers: bool,
+    include_bom: bool,
+    columns: Option<Vec<String>>,
+) -> Result<Binary, ExplorerError> {
+    dump_csv(
+        env,
+        &data,
+        has_headers,
+        b'\t',
+        false,
+        include_bom,
+        columns,
+        None,
+        None,
+        None,
+        true,
+        CsvSpecialFloats::default(),
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn dump_csv<'a>(
+    env: Env<'a>,
+    data: &DataFrame,
+    has_headers: bool,
+    delimiter: u8,
+    quoting: bool,
+    include_bom: bool,
+    columns: Option<Vec<String>>,
+    binary_encoding: Option<&str>,
+    line_terminator: Option<String>,
+    header_names: Option<Vec<String>>,
+    trailing_newline: bool,
+    special_floats: CsvSpecialFloats,
+) -> Result<Binary<'a>, ExplorerError> {
+    let line_terminator = decode_csv_line_terminator(line_terminator)?;
+    let mut data = prepare_csv_data(
+        data,
+        columns,
+        binary_encoding,
+        header_names,
+        &special_floats,
+    )?;
+    let mut buf = vec![];
+
+    write_csv(
+        &mut buf,
+        &mut data,
+        has_headers,
+        delimiter,
+        quoting,
+        include_bom,
+        line_terminator,
+        trailing_newline,
+    )?;
+
+    let mut values_binary = NewBinary::new(env, buf.len());
+    values_binary.copy_from_slice(&buf);
+
+    Ok(values_binary.into())
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+#[allow(clippy::too_many_arguments)]
+pub fn df_load_csv(
+    binary: Binary,
+    infer_schema_length: Option<usize>,
+    has_header: bool,
+    stop_after_n_rows: Option<usize>,
+    skip_rows: usize,
+    projection: Option<Vec<usize>>,
+    delimiter_as_byte: u8,
+    do_rechunk: bool,
+    column_names: Option<Vec<String>>,
+    dtypes: Vec<(&str, &str)>,
+    encoding: &str,
+    null_vals: Vec<String>,
+    parse_dates: bool,
+    eol_delimiter: Option<u8>,
+    low_memory: bool,
+    truncate_ragged_lines: bool,
+    true_values: Vec<String>,
+    false_values: Vec<String>,
+    binary_encoding: Option<&str>,
+    all_strings: bool,
+    strip_bom: bool,
+) -> Result<ExDataFrame, ExplorerError> {
+    let infer_schema_length = csv_infer_schema_length(infer_schema_length, all_strings);
+    let parse_dates = parse_dates && !all_strings;
+    let (bytes, encoding) = decode_csv_bytes(binary.as_slice(), encoding, strip_bom)?;
+
+    let mut dtypes = schema_from_dtypes_pairs(dtypes)?;
+    let booleans = CsvBooleans::new(&mut dtypes, true_values, false_values);
+    let binaries = CsvBinaries::new(&mut dtypes, decode_csv_binary_encoding(binary_encoding)?);
+
+    finish_csv_reader(&booleans, &binaries, || {
+        Ok(CsvReader::new(Cursor::new(bytes.as_ref()))
+            .infer_schema(infer_schema_length)
+            .has_header(has_header)
+            .with_try_parse_dates(parse_dates)
+            .with_n_rows(stop_after_n_rows)
+            .with_delimiter(delimiter_as_byte)
+            .with_skip_rows(skip_rows)
+            .with_projection(projection.clone())
+            .with_rechunk(do_rechunk)
+            .with_encoding(encoding)
+            .with_columns(column_names.clone())
+            .with_dtypes(Some(dtypes.clone()))
+            .with_null_values(Some(NullValues::AllColumns(null_vals.clone())))
+            .with_end_of_line_char(eol_delimiter.unwrap_or(b'\n'))
+            .low_memory(low_memory)
+            .truncate_ragged_lines(truncate_ragged_lines))
+    })
+}
+
+// Reads roughly `length` bytes starting at `start_byte`, which is useful to
+// sample the middle of very large files without reading them from the top.
+// The start is aligned forward to the beginning of the next line, and the end
+// is extended to the end of the line, so only complete rows are parsed.
+//
+// Since a range in the middle of the file does not include the header,
+// the rows are read without one. Callers should give the `column_names`,
+// otherwise the columns are named "column_1", "column_2" and so on.
+// The `dtypes` refer to the given column names.
+#[rustler::nif(schedule = "DirtyIo")]
+#[allow(clippy::too_many_arguments)]
+pub fn df_from_csv_range(
+    filename: &str,
+    start_byte: u64,
+    length: u64,
+    delimiter_as_byte: u8,
+    column_names: Option<Vec<String>>,
+    dtypes: Vec<(&str, &str)>,
+    null_vals: Vec<String>,
+    eol_delimiter: Option<u8>,
+) -> Result<ExDataFrame, ExplorerError> {
+    let eol = eol_delimiter.unwrap_or(b'\n');
+    let mut reader = BufReader::new(open_file(filename)?);
+
+    // By starting one byte earlier, a range that already starts
+    // at the beginning of a line only discards the previous EOL.
+    if start_byte > 0 {
+        reader.seek(SeekFrom::Start(start_byte - 1))?;
+        reader.read_until(eol, &mut vec![])?;
+    }
+
+    let mut buf = vec![];
+    (&mut reader).take(length).read_to_end(&mut buf)?;
+
+    if !buf.is_empty() && buf.last() != Some(&eol) {
+        reader.read_until(eol, &mut buf)?;
+    }
+
+    let mut df = CsvReader::new(Cursor::new(buf))
+        .has_header(false)
+        .with_delimiter(delimiter_as_byte)
+        .with_dtypes(Some(positional_csv_dtypes(&column_names, dtypes)?))
+        .with_null_values(Some(NullValues::AllColumns(null_vals)))
+        .with_end_of_line_char(eol)
+        .finish()?;
+
+    if let Some(column_names) = column_names {
+        if column_names.len() != df.width() {
+            return Err(ExplorerError::Other(format!(
+                "expected {} column names for the CSV range, but got {}",
+                df.width(),
+                column_names.len()
+            )));
+        }
+
+        df.set_column_names(&column_names)?;
+    }
+
+    Ok(ExDataFrame::new(normalize_numeric_dtypes(&mut df)?))
+}
+
+// The rows read without a header are named "column_1", "column_2" and so on
+// by Polars, so the dtypes of the named columns are given by position.
+fn positional_csv_dtypes(
+    column_names: &Option<Vec<String>>,
+    dtypes: Vec<(&str, &str)>,
+) -> Result<Arc<Schema>, ExplorerError> {
+    let mut positional_dtypes = vec![];
+
+    for (name, dtype) in dtypes {
+        let index = column_names
+            .as_ref()
+            .and_then(|names| names.iter().position(|existing| existing == name))
+            .ok_or_else(|| {
+                ExplorerError::Other(format!(
+                    "cannot set the dtype of column {name:?} because it is not in the column names"
+                ))
+            })?;
+
+        positional_dtypes.push((format!("column_{}", index + 1), dtype));
+    }
+
+    let positional_dtypes = positional_dtypes
+        .iter()
+        .map(|(name, dtype)| (name.as_str(), *dtype))
+        .collect();
+
+    schema_from_dtypes_pairs(positional_dtypes)
+}
+
+// Reads up to `n_rows` rows starting at `start_offset`, and returns them with
+// the byte offset right after the last row read. Passing that offset to the
+// next call reads the following rows, so a file can be processed in chunks
+// without keeping a reader open.
+//
+// Rows are never split: a row that straddles the limit is either read whole
+// or left for the next call. Like in `df_from_csv_range`, an offset in the
+// middle of a row is aligned forward to the beginning of the next row. When
+// there are no rows left, an empty dataframe is returned with the same offset.
+// Since the rows are found by their line endings, quoted values with line
+// breaks are not supported.
+//
+// With `has_header`, the names come from the header, which is skipped even
+// when the offset is zero. The given `column_names` take precedence.
+// The `dtypes` refer to the final column names.
+#[rustler::nif(schedule = "DirtyIo")]
+#[allow(clippy::too_many_arguments)]
+pub fn df_from_csv_resumable(
+    filename: &str,
+    start_offset: u64,
+    n_rows: usize,
+    has_header: bool,
+    delimiter_as_byte: u8,
+    column_names: Option<Vec<String>>,
+    dtypes: Vec<(&str, &str)>,
+    null_vals: Vec<String>,
+    eol_delimiter: Option<u8>,
+) -> Result<(ExDataFrame, u64), ExplorerError> {
+    let eol = eol_delimiter.unwrap_or(b'\n');
+    let mut reader = BufReader::new(open_file(filename)?);
+
+    let mut header = vec![];
+
+    if has_header {
+        reader.read_until(eol, &mut header)?;
+    }
+
+    let column_names = match column_names {
+        None if !header.is_empty() => Some(
+            CsvReader::new(Cursor::new(&header))
+                .has_header(true)
+                .with_delimiter(delimiter_as_byte)
+                .with_end_of_line_char(eol)
+                .finish()?
+                .get_column_names()
+                .into_iter()
+                .map(|name| name.to_string())
+                .collect(),
+        ),
+        column_names => column_names,
+    };
+
+    let header_end = header.len() as u64;
+
+    let mut offset = if start_offset > header_end {
+        // By starting one byte earlier, an offset that already is
+        // at the beginning of a row only discards the previous EOL.
+        reader.seek(SeekFrom::Start(start_offset - 1))?;
+        start_offset - 1 + reader.read_until(eol, &mut vec![])? as u64
+    } else {
+        reader.seek(SeekFrom::Start(header_end))?;
+        header_end
+    };
+
+    let mut buf = vec![];
+
+    for _ in 0..n_rows {
+        match reader.read_until(eol, &mut buf)? {
+            0 => break,
+            read => offset += read as u64,
+        }
+    }
+
+    if buf.is_empty() {
+        return Ok((ExDataFrame::new(DataFrame::default()), offset));
+    }
+
+    let mut df = CsvReader::new(Cursor::new(buf))
+        .has_header(false)
+        .with_delimiter(delimiter_as_byte)
+        .with_dtypes(Some(positional_csv_dtypes(&column_names, dtypes)?))
+        .with_null_values(Some(NullValues::AllColumns(null_vals)))
+        .with_end_of_line_char(eol)
+        .finish()?;
+
+    if let Some(column_names) = column_names {
+        if column_names.len() != df.width() {
+            return Err(ExplorerError::Other(format!(
+                "expected {} column names for the CSV rows, but got {}",
+                df.width(),
+                column_names.len()
+            )));
+        }
+
+        df.set_column_names(&column_names)?;
+    }
+
+    Ok((ExDataFrame::new(normalize_numeric_dtypes(&mut df)?), offset))
+}
+
+// Reads the last `n` rows of the file, reading backwards from the end in blocks,
+// so peeking at the most recent rows of an append-only file doesn't scan it all.
+//
+// The names and the dtypes come from the header and the first
+// `infer_schema_length` rows, not from the tail, so a column that only has
+// values near the end may be inferred differently than in a full read.
+// Since the rows are found by their line endings, quoted values with line
+// breaks in the last rows are not supported.
+#[rustler::nif(schedule = "DirtyIo")]
+#[allow(clippy::too_many_arguments)]
+pub fn df_from_csv_tail(
+    filename: &str,
+    n: usize,
+    has_header: bool,
+    infer_schema_length: Option<usize>,
+    delimiter_as_byte: u8,
+    dtypes: Vec<(&str, &str)>,
+    null_vals: Vec<String>,
+    eol_delimiter: Option<u8>,
+) -> Result<ExDataFrame, ExplorerError> {
+    let eol = eol_delimiter.unwrap_or(b'\n');
+
+    let mut head = CsvReader::new(open_file(filename)?)
+        .infer_schema(infer_schema_length)
+        .has_header(has_header)
+        .with_delimiter(delimiter_as_byte)
+        .with_dtypes(Some(schema_from_dtypes_pairs(dtypes)?))
+        .with_null_values(Some(NullValues::AllColumns(null_vals.clone())))
+        .with_end_of_line_char(eol)
+        .with_n_rows(Some(0))
+        .finish()?;
+
+    let buf = read_csv_tail(filename, n, has_header, eol)?;
+
+    if buf.is_empty() {
+        return Ok(ExDataFrame::new(normalize_numeric_dtypes(&mut head)?));
+    }
+
+    // The rows are read without a header, so the dtypes are given by position.
+    let mut positional_schema = Schema::new();
+
+    for (index, (_name, dtype)) in head.schema().iter().enumerate() {
+        positional_schema.with_column(format!("column_{}", index + 1).into(), dtype.clone());
+    }
+
+    let mut df = CsvReader::new(Cursor::new(buf))
+        .has_header(false)
+        .with_delimiter(delimiter_as_byte)
+        .with_dtypes(Some(Arc::new(positional_schema)))
+        .with_null_values(Some(NullValues::AllColumns(null_vals)))
+        .with_end_of_line_char(eol)
+        .finish()?;
+
+    if df.width() != head.width() {
+        return Err(ExplorerError::Other(format!(
+            "expected {} columns in the last rows of {filename}, but got {}",
+            head.width(),
+            df.width()
+        )));
+    }
+
+    df.set_column_names(&head.get_column_names())?;
+
+    Ok(ExDataFrame::new(normalize_numeric_dtypes(&mut df)?))
+}
+
+// Reads blocks from the end of the file until there are `n` complete lines,
+// or until the header is reached. Returns the bytes of the last `n` lines.
+fn read_csv_tail(
+    filename: &str,
+    n: usize,
+    has_header: bool,
+    eol: u8,
+) -> Result<Vec<u8>, ExplorerError> {
+    const BLOCK_SIZE: u64 = 64 * 1024;
+
+    let mut file = open_file(filename)?;
+
+    let header_end = if has_header {
+        let mut header = vec![];
+        BufReader::new(&file).read_until(eol, &mut header)?;
+        header.len() as u64
+    } else {
+        0
+    };
+
+    let mut start = file.seek(SeekFrom::End(0))?;
+    let mut buf: Vec<u8> = vec![];
+
+    loop {
+        let lines = buf.strip_suffix(&[eol]).unwrap_or(&buf[..]);
+        let line_breaks = lines.iter().filter(|byte| **byte == eol).count();
+
+        if line_breaks >= n || start <= header_end {
+            // The first line in the buffer may be incomplete, unless it
+            // starts right after the header, so only the last `n` are kept.
+            let mut positions = lines
+                .iter()
+                .enumerate()
+                .filter(|(_, byte)| **byte == eol)
+                .map(|(position, _)| position);
+
+            let tail_start = if n == 0 {
+                lines.len()
+            } else {
+                positions.nth_back(n - 1).map_or(0, |position| position + 1)
+            };
+
+            return Ok(lines[tail_start..].to_vec());
+        }
+
+        let block_start = start.saturating_sub(BLOCK_SIZE).max(header_end);
+        let mut block = vec![0; (start - block_start) as usize];
+
+        file.seek(SeekFrom::Start(block_start))?;
+        file.read_exact(&mut block)?;
+
+        block.extend_from_slice(&buf);
+        buf = block;
+        start = block_start;
+    }
+}
+
+// Runs only the schema inference, without reading the rows into a dataframe.
+// The dtypes are returned as strings that `dtype_from_str` understands,
+// so they can be given back as `dtypes` for subsequent reads.
+#[rustler::nif(schedule = "DirtyIo")]
+#[allow(clippy::too_many_arguments)]
+pub fn df_csv_infer_schema(
+    filename: &str,
+    infer_schema_length: Option<usize>,
+    has_header: bool,
+    skip_rows: usize,
+    delimiter_as_byte: u8,
+    encoding: &str,
+    null_vals: Vec<String>,
+    parse_dates: bool,
+    eol_delimiter: Option<u8>,
+) -> Result<Vec<(String, String)>, ExplorerError> {
+    let (file, encoding) = open_csv_file(filename, encoding, true)?;
+
+    let mut df = CsvReader::new(file)
+        .infer_schema(infer_schema_length)
+        .has_header(has_header)
+        .with_try_parse_dates(parse_dates)
+        .with_n_rows(Some(0))
+        .with_delimiter(delimiter_as_byte)
+        .with_skip_rows(skip_rows)
+        .with_encoding(encoding)
+        .with_null_values(Some(NullValues::AllColumns(null_vals)))
+        .with_end_of_line_char(eol_delimiter.unwrap_or(b'\n'))
+        .finish()?;
+
+    strip_bom_from_names(&mut df)?;
+
+    df.schema()
+        .iter()
+        .map(|(name, dtype)| Ok((name.to_string(), dtype_to_str(dtype)?)))
+        .collect()
+}
+
+const CSV_SNIFF_DELIMITERS: [u8; 4] = [b',', b';', b'\t', b'|'];
+
+// Detects the dialect of a CSV file from its first `sample_bytes` bytes.
+// The delimiter is the one that splits the most lines into the same number
+// of fields. The quote char is `'` only when fields are quoted with it but
+// never with `"`. The file has a header when no field of the first line
+// is a number, and either some column has only numbers in the other lines,
+// or the values of the first line are all distinct and non-empty.
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_csv_sniff(filename: &str, sample_bytes: usize) -> Result<ExCsvDialect, ExplorerError> {
+    let mut sample = vec![];
+    let file = open_file(filename)?;
+    let file_len = file.metadata()?.len();
+    file.take(sample_bytes as u64).read_to_end(&mut sample)?;
+    let is_truncated = (sample.len() as u64) < file_len;
+
+    let sample = String::from_utf8_lossy(&sample);
+    let mut lines: Vec<&str> = sample.lines().filter(|line| !line.is_empty()).collect();
+
+    // The last line may have been cut in the middle.
+    if is_truncated && lines.len() > 1 {
+        lines.pop();
+    }
+
+    if lines.is_empty() {
+        return Err(ExplorerError::Other(format!(
+            "cannot detect the CSV dialect of {filename} because the sample is empty"
+        )));
+    }
+
+    let delimiter = sniff_csv_delimiter(&lines);
+    let rows: Vec<Vec<&str>> = lines
+        .iter()
+        .map(|line| split_csv_line(line, delimiter, b'"'))
+        .collect();
+
+    let is_quoted_with = |quote: char| {
+        rows.iter()
+            .flatten()
+            .any(|field| is_csv_field_quoted(field, quote))
+    };
+
+    let quote_char = if !is_quoted_with('"') && is_quoted_with('\'') {
+        b'\''
+    } else {
+        b'"'
+    };
+
+    Ok(ExCsvDialect {
+        delimiter,
+        quote_char,
+        has_header: sniff_csv_header(&rows, quote_char as char),
+    })
+}
+
+fn sniff_csv_delimiter(lines: &[&str]) -> u8 {
+    let mut best = (b',', 0, 0);
+
+    for delimiter in CSV_SNIFF_DELIMITERS {
+        let counts: Vec<usize> = lines
+            .iter()
+            .map(|line| split_csv_line(line, delimiter, b'"').len())
+            .collect();
+
+        // The number of fields that most lines have.
+        let fields = counts
+            .iter()
+            .max_by_key(|count| counts.iter().filter(|other| other == count).count())
+            .copied()
+            .unwrap_or(1);
+
+        if fields < 2 {
+            continue;
+        }
+
+        let matching = counts.iter().filter(|count| **count == fields).count();
+
+        if (matching, fields) > (best.1, best.2) {
+            best = (delimiter, matching, fields);
+        }
+    }
+
+    best.0
+}
+
+// Splits the line by the delimiter, except inside quotes.
+// The fields are returned as they are, including the quotes.
+fn split_csv_line(line: &str, delimiter: u8, quote_char: u8) -> Vec<&str> {
+    let mut fields = vec![];
+    let mut start = 0;
+    let mut in_quotes = false;
+
+    for (index, byte) in line.bytes().enumerate() {
+        if byte == quote_char {
+            in_quotes = !in_quotes;
+        } else if byte == delimiter && !in_quotes {
+            fields.push(&line[start..index]);
+            start = index + 1;
+        }
+    }
+
+    fields.push(&line[start..]);
+    fields
+}
+
+fn is_csv_field_quoted(field: &str, quote: char) -> bool {
+    let field = field.trim();
+    field.len() >= 2 && field.starts_with(quote) && field.ends_with(quote)
+}
+
+fn sniff_csv_header(rows: &[Vec<&str>], quote_char: char) -> bool {
+    let unquote = |field: &str| field.trim().trim_matches(quote_char).trim().to_string();
+    let is_number = |field: &str| unquote(field).parse::<f64>().is_ok();
+
+    let (first, others) = match rows.split_first() {
+        Some(split) => split,
+        None => return false,
+    };
+
+    if first.iter().any(|field| is_number(field)) {
+        return false;
+    }
+
+    let has_numeric_column = (0..first.len()).any(|index| {
+        let mut values = others
+            .iter()
+            .filter_map(|row| row.get(index))
+            .filter(|field| !unquote(field).is_empty())
+            .peekable();
+
+        values.peek().is_some() && values.all(|field| is_number(field))
+    });
+
+    let names: Vec<String> = first.iter().map(|field| unquote(field)).collect();
+    let distinct_names = names
+        .iter()
+        .enumerate()
+        .all(|(index, name)| !name.is_empty() && !names[..index].contains(name));
+
+    has_numeric_column || distinct_names
+}
+
+// ============ Cancellation ============ //
+
+// Creates a token that can be given to the batched readers, and
+// cancelled with `df_cancel` from another process.
+#[rustler::nif]
+pub fn df_cancel_token_new() -> ResourceArc<ExCancelTokenRef> {
+    ResourceArc::new(ExCancelTokenRef::default())
+}
+
+#[rustler::nif]
+pub fn df_cancel(token: ResourceArc<ExCancelTokenRef>) -> Result<(), ExplorerError> {
+    token.cancel();
+    Ok(())
+}
+
+// The batched readers check the token before each batch, before each column
+// of a Parquet row group, and between reading and parsing the NDJSON lines.
+// `df_from_csv` checks it between the steps of the read. The parsing done
+// by Polars, of a batch or of the whole file, is not interrupted.
+fn check_cancelled(token: &Option<ResourceArc<ExCancelTokenRef>>) -> Result<(), ExplorerError> {
+    match token {
+        Some(token) if token.is_cancelled() => Err(ExplorerError::Other("cancelled".into())),
+        _ => Ok(()),
+    }
+}
+
+// ============ CSV Batched ============ //
+
+#[rustler::nif(schedule = "DirtyIo")]
+#[allow(clippy::too_many_arguments)]
+pub fn df_csv_batched_open(
+    filename: &str,
+    batch_size: usize,
+    infer_schema_length: Option<usize>,
+    has_header: bool,
+    skip_rows: usize,
+    projection: Option<Vec<usize>>,
+    delimiter_as_byte: u8,
+    column_names: Option<Vec<String>>,
+    dtypes: Vec<(&str, &str)>,
+    encoding: &str,
+    null_vals: Vec<String>,
+    parse_dates: bool,
+    eol_delimiter: Option<u8>,
+) -> Result<ResourceArc<ExCsvBatchedReaderRef>, ExplorerError> {
+    let (file, encoding) = open_csv_file(filename, encoding, true)?;
+
+    // The dtypes only override the inferred ones, the schema given to
+    // `batched_read` would have to cover every column.
+    let schema = schema_from_dtypes_pairs(dtypes)?;
+    let boxed_file: Box<dyn polars::io::mmap::MmapBytesReader> = Box::new(file);
+
+    let reader = CsvReader::new(boxed_file)
+        .infer_schema(infer_schema_length)
+        .has_header(has_header)
+        .with_try_parse_dates(parse_dates)
+        .with_delimiter(delimiter_as_byte)
+        .with_skip_rows(skip_rows)
+        .with_projection(projection)
+        .with_encoding(encoding)
+        .with_columns(column_names)
+        .with_dtypes(Some(schema))
+        .with_chunk_size(batch_size)
+        .with_null_values(Some(NullValues::AllColumns(null_vals)))
+        .with_end_of_line_char(eol_delimiter.unwrap_or(b'\n'))
+        .batched_read(None)?;
+
+    Ok(ResourceArc::new(ExCsvBatchedReaderRef::new(reader)))
+}
+
+// Returns `None` once the reader is exhausted. The batches are read one
+// at a time, so the token is checked before each of them.
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_csv_batched_next(
+    reader: ResourceArc<ExCsvBatchedReaderRef>,
+    n_batches: usize,
+    cancel_token: Option<ResourceArc<ExCancelTokenRef>>,
+) -> Result<Option<Vec<ExDataFrame>>, ExplorerError> {
+    let mut reader = reader
+        .0
+        .lock()
+        .map_err(|_| ExplorerError::Internal("the CSV batched reader lock is poisoned".into()))?;
+
+    let mut dfs = Vec::with_capacity(n_batches);
+
+    for _ in 0..n_batches {
+        check_cancelled(&cancel_token)?;
+
+        match reader.next_batches(1)? {
+            Some(batches) => {
+                for mut df in batches {
+                    strip_bom_from_names(&mut df)?;
+                    dfs.push(ExDataFrame::new(normalize_numeric_dtypes(&mut df)?));
+                }
+            }
+            None => break,
+        }
+    }
+
+    if dfs.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(dfs))
+    }
+}
+
+// ============ Parquet ============ //
+
+// With `map_as_struct`, the MAP columns are read as lists of structs with
+// the `key` and `value` fields, one struct per entry. See `read_parquet_with_arrow2`.
+//
+// The file is opened up to `open_retries` more times on transient errors,
+// see `open_file_with_retries`. Once it opens, the mount is responsive,
+// so the helpers that read the metadata open it again without retrying.
+#[rustler::nif(schedule = "DirtyIo")]
+#[allow(clippy::too_many_arguments)]
+pub fn df_from_parquet(
+    filename: &str,
+    stop_after_n_rows: Option<usize>,
+    column_names: Option<Vec<String>>,
+    projection: Option<Vec<usize>>,
+    normalize: bool,
+    rename: Vec<(String, String)>,
+    parallel: &str,
+    coerce_int96: Option<&str>,
+    map_as_struct: bool,
+    open_retries: usize,
+) -> Result<ExDataFrame, ExplorerError> {
+    let file = open_file_with_retries(filename, open_retries)?;
+
+    let int96_time_unit = match coerce_int96 {
+        Some(unit) => Some(decode_int96_time_unit(unit)?),
+        None => None,
+    };
+
+    let parallel = decode_parallel_strategy(parallel)?;
+
+    let arrow2_df = if map_as_struct || int96_time_unit.is_some() {
+        read_parquet_with_arrow2(
+            filename,
+            stop_after_n_rows,
+            &column_names,
+            &projection,
+            map_as_struct,
+            int96_time_unit,
+        )?
+    } else {
+        None
+    };
+
+    let mut df = match arrow2_df {
+        Some(df) => df,
+        None => ParquetReader::new(BufReader::new(file))
+            .with_n_rows(stop_after_n_rows)
+            .with_columns(column_names)
+            .with_projection(projection)
+            .read_parallel(parallel)
+            .finish()?,
+    };
+
+    // The normalization only widens the integer and float columns, so the
+    // decimal columns keep their precision and scale. The fixed size binary
+    // columns are read as binaries with the same bytes.
+    let df = if normalize {
+        normalize_numeric_dtypes(&mut df)?
+    } else {
+        df
+    };
+
+    rename_columns(ExDataFrame::new(df), rename)
+}
+
+// Polars can't read the Parquet MAP columns, nor read the Int96 timestamps
+// with another time unit than nanoseconds, so those files are read with
+// arrow2 instead. Returns `None` when the file has none of the columns that
+// were asked for, so it is read by Polars as usual.
+//
+// With `map_as_struct`, each MAP column becomes a column of lists of
+// `{key, value}` structs, with an empty list for an empty map. Maps nested
+// in other columns are kept as they are. Explorer has no dtypes for lists
+// of structs, so a dataframe with these columns can only be used through
+// the native functions. The public `from_parquet` never reads the maps this way.
+//
+// With an `int96_time_unit`, arrow2 reads the Int96 timestamps, which legacy
+// writers like old Spark versions use, directly with that time unit. So the
+// timestamps outside of the range of nanoseconds, from 1677 to 2262, are
+// read correctly with the coarser units.
+//
+// Only the selected columns are decoded. They are read one row group at a
+// time on the calling thread, so the `parallel` strategy doesn't apply.
+fn read_parquet_with_arrow2(
+    filename: &str,
+    stop_after_n_rows: Option<usize>,
+    column_names: &Option<Vec<String>>,
+    projection: &Option<Vec<usize>>,
+    map_as_struct: bool,
+    int96_time_unit: Option<TimeUnit>,
+) -> Result<Option<DataFrame>, ExplorerError> {
+    use polars::export::arrow::array::{new_empty_array, Array};
+    use polars::export::arrow::datatypes::DataType as ArrowDataType;
+    use polars::export::arrow::io::parquet::read;
+    use polars::export::arrow::io::parquet::read::schema::SchemaInferenceOptions;
+
+    let mut buf_reader = BufReader::new(open_file(filename)?);
+    let metadata = read::read_metadata(&mut buf_reader).map_err(PolarsError::from)?;
+
+    let options = int96_time_unit.map(|time_unit| SchemaInferenceOptions {
+        int96_coerce_to_timeunit: time_unit.to_arrow(),
+    });
+    let schema =
+        read::schema::infer_schema_with_options(&metadata, &options).map_err(PolarsError::from)?;
+
+    let has_maps = map_as_struct
+        && schema
+            .fields
+            .iter()
+            .any(|field| matches!(field.data_type.to_logical_type(), ArrowDataType::Map(_, _)));
+
+    let has_int96 = int96_time_unit.is_some()
+        && metadata.schema().columns().iter().any(|column| {
+            column.descriptor.primitive_type.physical_type == read::PhysicalType::Int96
+        });
+
+    if !has_maps && !has_int96 {
+        return Ok(None);
+    }
+
+    let fields = match (column_names, projection) {
+        (Some(column_names), _) => column_names
+            .iter()
+            .map(|name| {
+                schema
+                    .fields
+                    .iter()
+                    .find(|field| &field.name == name)
+                    .cloned()
+                    .ok_or_else(|| {
+                        ExplorerError::Other(format!(
+                            "cannot read the column {name:?} because it does not exist"
+                        ))
+                    })
+            })
+            .collect::<Result<Vec<_>, ExplorerError>>()?,
+        (None, Some(projection)) => projection
+            .iter()
+            .map(|index| {
+                schema.fields.get(*index).cloned().ok_or_else(|| {
+                    ExplorerError::Other(format!("the projection index {index} is out of bounds"))
+                })
+            })
+            .collect::<Result<Vec<_>, ExplorerError>>()?,
+        (None, None) => schema.fields.clone(),
+    };
+
+    let convert_maps = |array: Box<dyn Array>| {
+        if map_as_struct {
+            map_array_to_list(array)
+        } else {
+            array
+        }
+    };
+
+    let mut chunks: Vec<Vec<Box<dyn Array>>> = fields.iter().map(|_| vec![]).collect();
+    let reader = read::FileReader::new(
+        buf_reader,
+        metadata.row_groups,
+        fields.clone().into(),
+        None,
+        stop_after_n_rows,
+        None,
+    );
+
+    for chunk in reader {
+        let chunk = chunk.map_err(PolarsError::from)?;
+
+        for (index, array) in chunk.into_arrays().into_iter().enumerate() {
+            chunks[index].push(convert_maps(array));
+        }
+    }
+
+    let columns = fields
+        .iter()
+        .zip(chunks)
+        .map(|(field, mut chunks)| {
+            if chunks.is_empty() {
+                chunks.push(convert_maps(new_empty_array(field.data_type.clone())));
+            }
+
+            Series::try_from((field.name.as_str(), chunks))
+        })
+        .collect::<Result<Vec<Series>, PolarsError>>()?;
+
+    Ok(Some(DataFrame::new(columns)?))
+}
+
+fn map_array_to_list(array: Box<dyn Array>) -> Box<dyn Array> {
+    use polars::export::arrow::array::{Array, ListArray, MapArray};
+    use polars::export::arrow::datatypes::DataType as ArrowDataType;
+    use polars::export::arrow::offset::OffsetsBuffer;
+
+    let field = match array.data_type().to_logical_type() {
+        ArrowDataType::Map(field, _) => field.clone(),
+        _ => return array,
+    };
+
+    match array.as_any().downcast_ref::<MapArray>() {
+        Some(map) => Box::new(ListArray::<i64>::new(
+            ArrowDataType::LargeList(field),
+            OffsetsBuffer::<i64>::from(map.offsets()),
+            map.field().clone(),
+            map.validity().cloned(),
+        )),
+        None => array,
+    }
+}
+
+fn decode_int96_time_unit(unit: &str) -> Result<TimeUnit, ExplorerError> {
+    match unit {
+        "ms" => Ok(TimeUnit::Milliseconds),
+        "us" | "μs" => Ok(TimeUnit::Microseconds),
+        "ns" => Ok(TimeUnit::Nanoseconds),
+        other => Err(ExplorerError::Other(format!(
+            "the time unit {other} is not supported for Int96 timestamps. \
+            The supported time units are: ms, us and ns"
+        ))),
+    }
+}
+
+// Polars can read the columns or the row groups in parallel, and picks
+// one of them with "auto". For small files on a busy system, "none"
+// avoids oversubscribing the thread pool.
+fn decode_parallel_strategy(parallel: &str) -> Result<ParallelStrategy, ExplorerError> {
+    match parallel {
+        "auto" => Ok(ParallelStrategy::Auto),
+        "columns" => Ok(ParallelStrategy::Columns),
+        "row_groups" => Ok(ParallelStrategy::RowGroups),
+        "none" => Ok(ParallelStrategy::None),
+        other => Err(ExplorerError::Other(format!(
+            "the parallel strategy {other} is not supported for Parquet. \
+            The supported strategies are: auto, columns, row_groups and none"
+        ))),
+    }
+}
+
+// Opens the file for reading one row group at a time with `df_parquet_batched_next`,
+// so files larger than memory can be processed. Only the footer is read here.
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_parquet_batched_open(
+    filename: &str,
+    columns: Option<Vec<String>>,
+) -> Result<ResourceArc<ExParquetBatchedReaderRef>, ExplorerError> {
+    use polars::export::arrow::io::parquet::read;
+
+    let mut buf_reader = BufReader::new(open_file(filename)?);
+
+    let metadata = read::read_metadata(&mut buf_reader).map_err(PolarsError::from)?;
+    let schema = read::infer_schema(&metadata).map_err(PolarsError::from)?;
+
+    let schema = match columns {
+        Some(columns) => {
+            if let Some(unknown) = columns
+                .iter()
+                .find(|name| !schema.fields.iter().any(|field| &field.name == *name))
+            {
+                return Err(ExplorerError::Other(format!(
+                    "column {unknown:?} does not exist in {filename}"
+                )));
+            }
+
+            schema.filter(|_index, field| columns.contains(&field.name))
+        }
+        None => schema,
+    };
+
+    Ok(ResourceArc::new(ExParquetBatchedReaderRef::new(
+        ExParquetBatchedReader {
+            reader: buf_reader,
+            row_groups: metadata.row_groups.into_iter(),
+            fields: schema.fields,
+        },
+    )))
+}
+
+// Returns the next row group, or `None` once the reader is exhausted.
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_parquet_batched_next(
+    reader: ResourceArc<ExParquetBatchedReaderRef>,
+    cancel_token: Option<ResourceArc<ExCancelTokenRef>>,
+) -> Result<Option<ExDataFrame>, ExplorerError> {
+    use polars::export::arrow::array::new_empty_array;
+    use polars::export::arrow::io::parquet::read;
+
+    let mut guard = reader.0.lock().map_err(|_| {
+        ExplorerError::Internal("the Parquet batched reader lock is poisoned".into())
+    })?;
+    let batched = &mut *guard;
+
+    let row_group = match batched.row_groups.next() {
+        Some(row_group) => row_group,
+        None => return Ok(None),
+    };
+
+    let mut columns = Vec::with_capacity(batched.fields.len());
+
+    // Each column is read and decoded on its own, so the token is checked
+    // between them. Without a chunk size, the column is a single array.
+    for field in &batched.fields {
+        check_cancelled(&cancel_token)?;
+
+        let arrays = read::read_columns_many(
+            &mut batched.reader,
+            &row_group,
+            vec![field.clone()],
+            None,
+            None,
+            None,
+        )
+        .map_err(PolarsError::from)?;
+
+        let array = match arrays.into_iter().flatten().next() {
+            Some(array) => array.map_err(PolarsError::from)?,
+            None => new_empty_array(field.data_type.clone()),
+        };
+
+        columns.push(Series::try_from((field.name.as_str(), array))?);
+    }
+
+    let mut df = DataFrame::new(columns)?;
+
+    Ok(Some(ExDataFrame::new(normalize_numeric_dtypes(&mut df)?)))
+}
+
+// Returns the compression codecs and the encodings of each column, as
+// `{name, codecs, encodings}`. Only the footer is read, not the data.
+// Since each row group may be written differently, the values of all
+// row groups are collected, without repetition.
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_parquet_column_info(
+    filename: &str,
+) -> Result<Vec<(String, Vec<String>, Vec<String>)>, ExplorerError> {
+    use polars::export::arrow::io::parquet::read;
+
+    let mut buf_reader = BufReader::new(open_file(filename)?);
+    let metadata = read::read_metadata(&mut buf_reader).map_err(PolarsError::from)?;
+
+    // The column chunks of every row group follow the order of the leaf
+    // columns in the schema, so they are matched by their index.
+    let mut info: Vec<(String, Vec<String>, Vec<String>)> = metadata
+        .schema()
+        .columns()
+        .iter()
+        .map(|column| (column.path_in_schema.join("."), vec![], vec![]))
+        .collect();
+
+    for row_group in &metadata.row_groups {
+        for (index, column) in row_group.columns().iter().enumerate() {
+            let (_, codecs, encodings) = info.get_mut(index).ok_or_else(|| {
+                ExplorerError::Other(format!(
+                    "the row group has more columns than the schema of {filename}"
+                ))
+            })?;
+            let codec = parquet_codec_to_str(column.metadata().codec.0).to_string();
+
+            if !codecs.contains(&codec) {
+                codecs.push(codec);
+            }
+
+            for encoding in &column.metadata().encodings {
+                let encoding = parquet_encoding_to_str(encoding.0).to_string();
+
+                if !encodings.contains(&encoding) {
+                    encodings.push(encoding);
+                }
+            }
+        }
+    }
+
+    Ok(info)
+}
+
+// With "dictionary", the categorical columns are written as dictionaries of
+// strings, which Polars reads back as categoricals. With "string", they are
+// cast to strings first, so other engines, which may not understand the
+// dictionary layout, read them as plain strings.
+fn encode_parquet_categoricals(
+    mut df: DataFrame,
+    categorical_as: &str,
+) -> Result<DataFrame, ExplorerError> {
+    match categorical_as {
+        "dictionary" => Ok(df),
+        "string" => {
+            let names: Vec<String> = df
+                .get_columns()
+                .iter()
+                .filter(|series| matches!(series.dtype(), DataType::Categorical(_)))
+                .map(|series| series.name().to_string())
+                .collect();
+
+            for name in names {
+                let series = df.column(&name)?.cast(&DataType::Utf8)?;
+                df.replace(&name, series)?;
+            }
+
+            Ok(df)
+        }
+        other => Err(ExplorerError::Other(format!(
+            "unknown categorical encoding {other:?} for Parquet. \
+            The supported encodings are: dictionary and string"
+        ))),
+    }
+}
+
+// Checks the CRC checksums of the pages of a Parquet file. The checksum is
+// optional, so the pages without one are skipped, and a file without any
+// checksums is always valid. Returns `None` when all checksums match, or
+// the location of the first corrupt page as `{row_group, column, page}`,
+// where the pages of each column chunk are counted from zero, including
+// the dictionary page.
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_parquet_verify(filename: &str) -> Result<Option<(usize, String, usize)>, ExplorerError> {
+    use polars::export::arrow::io::parquet::read;
+
+    let file = open_file(filename)?;
+    let file_length = file.metadata()?.len();
+    let mut buf_reader = BufReader::new(file);
+    let metadata = read::read_metadata(&mut buf_reader).map_err(PolarsError::from)?;
+
+    for (row_group_index, row_group) in metadata.row_groups.iter().enumerate() {
+        for column in row_group.columns() {
+            let column_metadata = column.metadata();
+            let start = match column_metadata.dictionary_page_offset {
+                Some(offset) if offset > 0 && offset < column_metadata.data_page_offset => offset,
+                _ => column_metadata.data_page_offset,
+            };
+
+            let name = column.descriptor().path_in_schema.join(".");
+
+            // The sizes come from the file, so a corrupt footer must not
+            // make us allocate more than the file itself.
+            let length = u64::try_from(column_metadata.total_compressed_size)
+                .ok()
+                .zip(u64::try_from(start).ok())
+                .filter(|(length, start)| {
+                    start
+                        .checked_add(*length)
+                        .map_or(false, |end| end <= file_length)
+                })
+                .map(|(length, _)| length as usize)
+                .ok_or_else(|| {
+                    ExplorerError::Other(format!(
+                        "the column chunk {name:?} of row group {row_group_index} \
+                        is out of the bounds of the Parquet file"
+                    ))
+                })?;
+
+            let mut bytes = vec![0; length];
+            buf_reader.seek(SeekFrom::Start(start as u64))?;
+            buf_reader.read_exact(&mut bytes)?;
+
+            if let Some(page) = find_corrupt_page(&bytes)? {
+                return Ok(Some((row_group_index, name, page)));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+// The pages of a column chunk are stored one after the other, each one as
+// a Thrift page header followed by the compressed page. The checksum is
+// the CRC-32 of the compressed page, as in the Parquet specification.
+fn find_corrupt_page(bytes: &[u8]) -> Result<Option<usize>, ExplorerError> {
+    let mut reader = ThriftCompactReader {
+        bytes,
+        pos: 0,
+        depth: 0,
+    };
+    let mut page = 0;
+
+    while reader.pos < bytes.len() {
+        let (compressed_size, crc) = reader.read_page_header()?;
+        let data = reader.take(compressed_size)?;
+
+        if let Some(crc) = crc {
+            if crc32fast::hash(data) != crc as u32 {
+                return Ok(Some(page));
+            }
+        }
+
+        page += 1;
+    }
+
+    Ok(None)
+}
+
+// A minimal reader of the Thrift compact protocol, enough to read the size
+// and the checksum of the page headers and skip the other fields.
+//
+// The files may be corrupt or crafted, so the nesting of the skipped
+// structs, lists, sets and maps is limited to `THRIFT_MAX_DEPTH` levels,
+// as in the Thrift libraries, instead of recursing until the stack overflows.
+struct ThriftCompactReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    depth: usize,
+}
+
+const THRIFT_MAX_DEPTH: usize = 64;
+
+impl<'a> ThriftCompactReader<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8], ExplorerError> {
+        let end = self.pos.checked_add(length);
+        let bytes = end
+            .and_then(|end| self.bytes.get(self.pos..end))
+            .ok_or_else(|| {
+                ExplorerError::Other(format!(
+                    "the Parquet page at byte {} is truncated",
+                    self.pos
+                ))
+            })?;
+
+        self.pos += length;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8, ExplorerError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn varint(&mut self) -> Result<u64, ExplorerError> {
+        let mut value = 0;
+        let mut shift = 0;
+
+        loop {
+            let byte = self.byte()?;
+            value |= u64::from(byte & 0x7F) << shift;
+
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+
+            shift += 7;
+
+            if shift > 63 {
+                return Err(ExplorerError::Other(format!(
+                    "the varint before byte {} of the Parquet page header is too long",
+                    self.pos
+                )));
+            }
+        }
+    }
+
+    fn zigzag(&mut self) -> Result<i64, ExplorerError> {
+        let value = self.varint()?;
+        Ok((value >> 1) as i64 ^ -((value & 1) as i64))
+    }
+
+    // Sizes are signed in Thrift, but a negative one can only come from
+    // a corrupt header.
+    fn size(&mut self) -> Result<usize, ExplorerError> {
+        let value = self.zigzag()?;
+
+        usize::try_from(value).map_err(|_| {
+            ExplorerError::Other(format!(
+                "the Parquet page header before byte {} has a negative size",
+                self.pos
+            ))
+        })
+    }
+
+    // Returns the compressed size and the checksum of the page header.
+    fn read_page_header(&mut self) -> Result<(usize, Option<i32>), ExplorerError> {
+        let mut field_id = 0;
+        let mut compressed_size = None;
+        let mut crc = None;
+
+        loop {
+            let header = self.byte()?;
+
+            if header == 0 {
+                break;
+            }
+
+            let field_type = header & 0x0F;
+
+            field_id = match header >> 4 {
+                0 => self.zigzag()?,
+                delta => field_id + i64::from(delta),
+            };
+
+            match (field_id, field_type) {
+                (3, 5) => compressed_size = Some(self.size()?),
+                (4, 5) => crc = Some(self.zigzag()? as i32),
+                _ => self.skip(field_type)?,
+            }
+        }
+
+        let compressed_size = compressed_size.ok_or_else(|| {
+            ExplorerError::Other(format!(
+                "the Parquet page header before byte {} has no size",
+                self.pos
+            ))
+        })?;
+
+        Ok((compressed_size, crc))
+    }
+
+    fn skip(&mut self, field_type: u8) -> Result<(), ExplorerError> {
+        match field_type {
+            9..=12 => {
+                if self.depth == THRIFT_MAX_DEPTH {
+                    return Err(ExplorerError::Other(format!(
+                        "the Parquet page header before byte {} is nested more than \
+                        {THRIFT_MAX_DEPTH} levels deep",
+                        self.pos
+                    )));
+                }
+
+                self.depth += 1;
+                let result = self.skip_nested(field_type);
+                self.depth -= 1;
+                result
+            }
+            _ => self.skip_scalar(field_type),
+        }
+    }
+
+    fn skip_scalar(&mut self, field_type: u8) -> Result<(), ExplorerError> {
+        match field_type {
+            // Booleans are stored in the field header.
+            1 | 2 => (),
+            3 => {
+                self.take(1)?;
+            }
+            4..=6 => {
+                self.varint()?;
+            }
+            7 => {
+                self.take(8)?;
+            }
+            8 => {
+                let length = self.varint()? as usize;
+                self.take(length)?;
+            }
+            other => {
+                return Err(ExplorerError::Other(format!(
+                    "unexpected Thrift type {other} in the Parquet page header"
+                )))
+            }
+        }
+
+        Ok(())
+    }
+
+    fn skip_nested(&mut self, field_type: u8) -> Result<(), ExplorerError> {
+        match field_type {
+            9 | 10 => {
+                let header = self.byte()?;
+                let size = match header >> 4 {
+                    15 => self.varint()? as usize,
+                    size => size as usize,
+                };
+
+                for _ in 0..size {
+                    self.skip_element(header & 0x0F)?;
+                }
+            }
+            11 => {
+                let size = self.varint()? as usize;
+
+                if size > 0 {
+                    let types = self.byte()?;
+
+                    for _ in 0..size {
+                        self.skip_element(types >> 4)?;
+                        self.skip_element(types & 0x0F)?;
+                    }
+                }
+            }
+            12 => loop {
+                let header = self.byte()?;
+
+                if header == 0 {
+                    break;
+                }
+
+                if header >> 4 == 0 {
+                    self.zigzag()?;
+                }
+
+                self.skip(header & 0x0F)?;
+            },
+            _ => (),
+        }
+
+        Ok(())
+    }
+
+    // Unlike in fields, the booleans in lists and maps take a byte each.
+    fn skip_element(&mut self, element_type: u8) -> Result<(), ExplorerError> {
+        match element_type {
+            1 | 2 => self.take(1).map(|_| ()),
+            element_type => self.skip(element_type),
+        }
+    }
+}
+
+// The compression codecs as numbered by the Parquet format specification.
+// The names are the ones of the `:compression` option of the writer.
+fn parquet_codec_to_str(codec: i32) -> &'static str {
+    match codec {
+        0 => "uncompressed",
+        1 => "snappy",
+        2 => "gzip",
+        3 => "lzo",
+        4 => "brotli",
+        5 => "lz4",
+        6 => "zstd",
+        7 => "lz4raw",
+        _ => "unknown",
+    }
+}
+
+// The encodings as numbered by the Parquet format specification.
+fn parquet_encoding_to_str(encoding: i32) -> &'static str {
+    match encoding {
+        0 => "plain",
+        2 => "plain_dictionary",
+        3 => "rle",
+        4 => "bit_packed",
+        5 => "delta_binary_packed",
+        6 => "delta_length_byte_array",
+        7 => "delta_byte_array",
+        8 => "rle_dictionary",
+        9 => "byte_stream_split",
+        _ => "unknown",
+    }
+}
+
+// Reads only the rows where `column <op> value` holds. The filter is pushed
+// down into the Parquet scan, which uses the min/max statistics of each
+// row group to skip the ones that cannot match. Row groups without
+// statistics for the column are read and filtered after reading.
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_from_parquet_filtered(
+    filename: &str,
+    column: &str,
+    op: &str,
+    value: ExExpr,
+    projection: Option<Vec<usize>>,
+) -> Result<ExDataFrame, ExplorerError> {
+    let value = value.clone_inner();
+    let predicate = match op {
+        "gt" => col(column).gt(value),
+        "lt" => col(column).lt(value),
+        "eq" => col(column).eq(value),
+        "ge" => col(column).gt_eq(value),
+        "le" => col(column).lt_eq(value),
+        other => {
+            return Err(ExplorerError::Other(format!(
+                "the operation {other} is not supported for filtering Parquet files"
+            )))
+        }
+    };
+
+    let lf = LazyFrame::scan_parquet(filename, Default::default())?;
+
+    let selection: Vec<Expr> = match projection {
+        Some(projection) => {
+            let schema = lf.schema()?;
+
+            projection
+                .iter()
+                .map(|index| match schema.get_at_index(*index) {
+                    Some((name, _dtype)) => Ok(col(name)),
+                    None => Err(ExplorerError::Other(format!(
+                        "column index {index} out of bounds in {filename}"
+                    ))),
+                })
+                .collect::<Result<_, _>>()?
+        }
+        None => vec![all()],
+    };
+
+    let mut df = lf.filter(predicate).select(selection).collect()?;
+
+    Ok(ExDataFrame::new(normalize_numeric_dtypes(&mut df)?))
+}
+
+// Reads all the Parquet files under `root_dir` into a single dataframe.
+// With `hive_partitioning`, directories named like "key=value" are
+// added as string columns to the rows of the files inside them.
+//
+// The `partition_filter` keeps only the directories whose partition values
+// are given, before any file is read. The values given for the same key are
+// alternatives, and the keys must all match. Only partition keys can be
+// filtered, the other columns must be filtered after reading.
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_from_parquet_dataset(
+    root_dir: &str,
+    columns: Option<Vec<String>>,
+    projection: Option<Vec<usize>>,
+    hive_partitioning: bool,
+    partition_filter: Vec<(String, String)>,
+) -> Result<ExDataFrame, ExplorerError> {
+    if !hive_partitioning && !partition_filter.is_empty() {
+        return Err(ExplorerError::Other(
+            "the partition filter requires hive partitioning".to_string(),
+        ));
+    }
+
+    let root = Path::new(root_dir);
+    let mut paths = vec![];
+    let mut partition_keys = vec![];
+    collect_parquet_files(root, &partition_filter, &mut partition_keys, &mut paths)?;
+    paths.sort();
+
+    for (key, _value) in &partition_filter {
+        if !partition_keys.contains(key) {
+            return Err(ExplorerError::Other(format!(
+                "cannot filter by {key:?} because it is not a partition key of the Parquet \
+                dataset at {root_dir}. Filter the dataframe after reading it instead"
+            )));
+        }
+    }
+
+    if paths.is_empty() && !partition_filter.is_empty() {
+        return Err(ExplorerError::Other(format!(
+            "no Parquet files in {root_dir} match the partition filter {partition_filter:?}"
+        )));
+    }
+
+    let mut first_partitions: Option<(PathBuf, Vec<String>)> = None;
+    let mut result: Option<DataFrame> = None;
+
+    for path in paths {
+        let partitions = if hive_partitioning {
+            hive_partitions(root, &path)
+        } else {
+            vec![]
+        };
+
+        let keys: Vec<String> = partitions.iter().map(|(key, _)| key.clone()).collect();
+
+        match &first_partitions {
+            Some((first_path, first_keys)) if first_keys != &keys => {
+                return Err(ExplorerError::Other(format!(
+                    "conflicting partitions in the Parquet dataset at {root_dir}: \
+                    {} is partitioned by {first_keys:?}, but {} is partitioned by {keys:?}",
+                    first_path.display(),
+                    path.display()
+                )))
+            }
+            Some(_) => (),
+            None => first_partitions = Some((path.clone(), keys.clone())),
+        }
+
+        // The partition columns are not stored in the files.
+        let file_columns = columns.as_ref().map(|columns| {
+            columns
+                .iter()
+                .filter(|name| !keys.contains(name))
+                .cloned()
+                .collect()
+        });
+
+        let file = open_file(&path)?;
+        let mut reader = ParquetReader::new(BufReader::new(file));
+
+        // When only partition columns are selected, no column is read from
+        // the file, so the number of rows comes from its metadata.
+        let (mut df, height) = match file_columns {
+            Some(file_columns) if file_columns.is_empty() => {
+                (DataFrame::default(), reader.num_rows()?)
+            }
+            file_columns => {
+                let df = reader
+                    .with_columns(file_columns)
+                    .with_projection(projection.clone())
+                    .finish()?;
+                let height = df.height();
+                (df, height)
+            }
+        };
+
+        for (key, value) in partitions {
+            df.with_column(Utf8Chunked::full(&key, &value, height).into_series())?;
+        }
+
+        if let Some(columns) = &columns {
+            df = df.select(columns)?;
+        }
+
+        match result.as_mut() {
+            Some(acc) => {
+                acc.vstack_mut(&df)?;
+            }
+            None => result = Some(df),
+        }
+    }
+
+    match result {
+        Some(mut df) => {
+            df.align_chunks();
+            Ok(ExDataFrame::new(normalize_numeric_dtypes(&mut df)?))
+        }
+        None => Err(ExplorerError::Other(format!(
+            "no Parquet files were found in {root_dir}"
+        ))),
+    }
+}
+
+// The "key=value" directories whose key is in the `partition_filter`, but
+// not with that value, are skipped without being listed. The keys of all
+// the partition directories seen are collected in `partition_keys`.
+fn collect_parquet_files(
+    dir: &Path,
+    partition_filter: &[(String, String)],
+    partition_keys: &mut Vec<String>,
+    paths: &mut Vec<PathBuf>,
+) -> Result<(), ExplorerError> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        if path.is_dir() {
+            let partition = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(hive_partition);
+
+            if let Some((key, value)) = partition {
+                if !partition_keys.contains(&key) {
+                    partition_keys.push(key.clone());
+                }
+
+                let mut allowed = partition_filter
+                    .iter()
+                    .filter(|(filter_key, _)| filter_key == &key)
+                    .peekable();
+
+                if allowed.peek().is_some()
+                    && !allowed.any(|(_, filter_value)| filter_value == &value)
+                {
+                    continue;
+                }
+            }
+
+            collect_parquet_files(&path, partition_filter, partition_keys, paths)?;
+        } else if path.extension().map_or(false, |ext| ext == "parquet") {
+            paths.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+// Extracts the "key=value" pairs from the directories between the root and the file.
+fn hive_partitions(root: &Path, path: &Path) -> Vec<(String, String)> {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+
+    relative
+        .parent()
+        .map(|parent| {
+            parent
+                .components()
+                .filter_map(|component| hive_partition(component.as_os_str().to_str()?))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// Splits a "key=value" directory name, unescaping both sides.
+fn hive_partition(name: &str) -> Option<(String, String)> {
+    let (key, value) = name.split_once('=')?;
+    Some((hive_unescape(key), hive_unescape(value)))
+}
+
+// Writes one file per partition under `root_dir/key=value/...`, which is
+// the layout read by `df_from_parquet_dataset` with hive partitioning.
+// The partition columns are only in the directory names, not in the files.
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_to_parquet_partitioned(
+    data: ExDataFrame,
+    root_dir: &str,
+    partition_by: Vec<String>,
+    ex_compression: ExParquetCompression,
+) -> Result<(), ExplorerError> {
+    if partition_by.is_empty() {
+        return Err(ExplorerError::Other(
+            "cannot write a partitioned Parquet dataset without partition columns".into(),
+        ));
+    }
+
+    let compression = ParquetCompression::try_from(ex_compression)?;
+
+    for partition in data.partition_by_stable(partition_by.clone(), true)? {
+        if partition.height() == 0 {
+            continue;
+        }
+
+        let mut dir = PathBuf::from(root_dir);
+
+        for name in &partition_by {
+            let value = partition.column(name)?.get(0)?;
+            dir.push(format!(
+                "{}={}",
+                hive_escape(name),
+                hive_partition_value(&value)
+            ));
+        }
+
+        std::fs::create_dir_all(&dir)
+            .map_err(|error| ExplorerError::file(&dir, "writing", error))?;
+
+        let mut body = partition.drop_many(&partition_by);
+        let file = create_file(dir.join("part-0.parquet"))?;
+
+        ParquetWriter::new(BufWriter::new(file))
+            .with_compression(compression)
+            .finish(&mut body)?;
+    }
+
+    Ok(())
+}
+
+// Spreads the rows over `n_files` files named `part-00000.parquet` and so on,
+// by hashing the `by` columns, so the files have similar sizes no matter how
+// many distinct keys there are. Rows with the same key end up in the same file
+// of a dataset, but the hash is not portable, so which file that is may change
+// across platforms and releases. Every file has the full schema, even without
+// rows, and the order of the rows within a file is not guaranteed.
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_to_parquet_sharded(
+    data: ExDataFrame,
+    dir: &str,
+    by: Vec<String>,
+    n_files: usize,
+    ex_compression: ExParquetCompression,
+) -> Result<(), ExplorerError> {
+    use polars::export::ahash::RandomState;
+
+    if by.is_empty() {
+        return Err(ExplorerError::Other(
+            "cannot write a sharded Parquet dataset without columns to hash".into(),
+        ));
+    }
+
+    if n_files == 0 {
+        return Err(ExplorerError::Other(
+            "the number of files must be greater than zero".into(),
+        ));
+    }
+
+    let compression = ParquetCompression::try_from(ex_compression)?;
+
+    let hashes = data
+        .select(&by)?
+        .hash_rows(Some(RandomState::with_seeds(0, 0, 0, 0)))?;
+
+    let mut shard_column = "__shard".to_string();
+    while data.get_column_names().contains(&shard_column.as_str()) {
+        shard_column.push('_');
+    }
+
+    let mut shards = hashes.apply(|hash| hash % n_files as u64).into_series();
+    shards.rename(&shard_column);
+
+    let mut sharded = data.clone_inner();
+    sharded.with_column(shards)?;
+
+    let mut shard_dfs: Vec<Option<DataFrame>> = vec![None; n_files];
+
+    for shard_df in sharded.partition_by(vec![shard_column.clone()], true)? {
+        let shard = shard_df.column(&shard_column)?.u64()?.get(0);
+
+        if let Some(shard) = shard {
+            shard_dfs[shard as usize] = Some(shard_df.drop(&shard_column)?);
+        }
+    }
+
+    std::fs::create_dir_all(dir)
+        .map_err(|error| ExplorerError::file(Path::new(dir), "writing", error))?;
+
+    for (shard, shard_df) in shard_dfs.into_iter().enumerate() {
+        let mut shard_df = shard_df.unwrap_or_else(|| data.clear());
+        let file = create_file(Path::new(dir).join(format!("part-{shard:05}.parquet")))?;
+
+        ParquetWriter::new(BufWriter::new(file))
+            .with_compression(compression)
+            .finish(&mut shard_df)?;
+    }
+
+    Ok(())
+}
+
+// Nil values go to the same directory that Hive uses for them.
+fn hive_partition_value(value: &AnyValue) -> String {
+    match value {
+        AnyValue::Null => "__HIVE_DEFAULT_PARTITION__".to_string(),
+        AnyValue::Utf8(value) => hive_escape(value),
+        other => hive_escape(&other.to_string()),
+    }
+}
+
+// Escapes the characters that Hive escapes in partition directory names as
+// `%XX`, so a value like "a/b" or "x=y" stays within a single directory.
+// The directory name always starts with the key, so it is never "." or "..".
+fn hive_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for char in value.chars() {
+        if char.is_ascii_control() || "\"#%'*/:=?\\{[]^".contains(char) {
+            escaped.push_str(&format!("%{:02X}", char as u32));
+        } else {
+            escaped.push(char);
+        }
+    }
+
+    escaped
+}
+
+// The `%XX` sequences that are not valid hex are kept as they are.
+fn hive_unescape(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(index) = rest.find('%') {
+        unescaped.push_str(&rest[..index]);
+        let code = rest
+            .get(index + 1..index + 3)
+            .filter(|code| code.bytes().all(|byte| byte.is_ascii_hexdigit()));
+
+        match code.and_then(|code| u8::from_str_radix(code, 16).ok()) {
+            Some(byte) => {
+                unescaped.push(char::from(byte));
+                rest = &rest[index + 3..];
+            }
+            None => {
+                unescaped.push('%');
+                rest = &rest[index + 1..];
+            }
+        }
+    }
+
+    unescaped.push_str(rest);
+    unescaped
+}
+
+// Reads a Parquet file served over HTTP(S). If the server supports range
+// requests, only the footer and the selected columns are downloaded.
+#[cfg(feature = "cloud")]
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_from_parquet_url(
+    url: &str,
+    columns: Option<Vec<String>>,
+    projection: Option<Vec<usize>>,
+) -> Result<ExDataFrame, ExplorerError> {
+    let invalid_url = |reason: &str| {
+        ExplorerError::Other(format!("invalid URL for a Parquet file {url}: {reason}"))
+    };
+
+    let mut base_url = reqwest::Url::parse(url).map_err(|error| invalid_url(&error.to_string()))?;
+    base_url.set_fragment(None);
+
+    // The store joins the object path to the base URL, keeping its query
+    // string, so signed URLs work too.
+    let object_name = base_url
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .filter(|segment| !segment.is_empty())
+        .map(str::to_string)
+        .ok_or_else(|| invalid_url("it has no file name"))?;
+
+    base_url
+        .path_segments_mut()
+        .map_err(|_| invalid_url("it cannot be a base URL"))?
+        .pop();
+
+    let object_path = object_store::path::Path::from_url_path(&object_name)
+        .map_err(|error| invalid_url(&error.to_string()))?;
+
+    let http_store = object_store::http::HttpBuilder::new()
+        .with_url(base_url.as_str())
+        .build()
+        .map_err(object_store_to_explorer_error)?;
+
+    let object_store: Box<dyn object_store::ObjectStore> = Box::new(http_store);
+    let cloud_reader = crate::cloud_reader::CloudReader::new(object_store, object_path)
+        .map_err(object_store_to_explorer_error)?;
+
+    read_parquet_ranges(cloud_reader, columns, projection)
+}
+
+// Parquet readers read up to this many bytes from the end of the file,
+// hoping to get the whole metadata at once.
+#[cfg(feature = "cloud")]
+const PARQUET_FOOTER_PREFETCH: u64 = 64 * 1024;
+
+// Reads a Parquet object through range requests: the footer is fetched
+// first, and then the byte ranges of the selected columns, all at once.
+// Nothing else is downloaded, unless the store doesn't support ranges.
+#[cfg(feature = "cloud")]
+fn read_parquet_ranges(
+    mut cloud_reader: crate::cloud_reader::CloudReader,
+    columns: Option<Vec<String>>,
+    projection: Option<Vec<usize>>,
+) -> Result<ExDataFrame, ExplorerError> {
+    use polars::export::arrow::io::parquet::read;
+
+    let length = cloud_reader.size();
+    let tail = length.saturating_sub(PARQUET_FOOTER_PREFETCH) as usize..length as usize;
+    let mut footer = cloud_reader
+        .get_ranges(&[tail.clone()])
+        .map_err(object_store_to_explorer_error)?;
+
+    // The file ends with the length of the metadata and the magic number.
+    let mut metadata_length = [0; 4];
+    footer.seek(SeekFrom::End(-8))?;
+    footer.read_exact(&mut metadata_length)?;
+    let footer_length = u64::from(u32::from_le_bytes(metadata_length)) + 8;
+
+    if footer_length > tail.len() as u64 {
+        let start = length.saturating_sub(footer_length) as usize;
+        footer = cloud_reader
+            .get_ranges(&[start..length as usize])
+            .map_err(object_store_to_explorer_error)?;
+    }
+
+    let metadata = read::read_metadata(&mut footer).map_err(PolarsError::from)?;
+    let schema = read::infer_schema(&metadata).map_err(PolarsError::from)?;
+
+    // The columns are validated like in the local reader, and they are
+    // read in the requested order.
+    let indices = match (columns, projection) {
+        (Some(columns), _) => columns
+            .iter()
+            .map(|name| {
+                schema
+                    .fields
+                    .iter()
+                    .position(|field| &field.name == name)
+                    .ok_or_else(|| {
+                        let names: Vec<&str> = schema
+                            .fields
+                            .iter()
+                            .map(|field| field.name.as_str())
+                            .collect();
+
+                        PolarsError::ColumnNotFound(
+                            format!("unable to find column {name:?}; valid columns: {names:?}")
+                                .into(),
+                        )
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+        (None, Some(projection)) => {
+            if let Some(index) = projection
+                .iter()
+                .find(|index| **index >= schema.fields.len())
+            {
+                return Err(ExplorerError::Other(format!(
+                    "projection index {index} is out of bounds for schema of length {}",
+                    schema.fields.len()
+                )));
+            }
+
+            projection
+        }
+        (None, None) => (0..schema.fields.len()).collect(),
+    };
+
+    let schema = polars::export::arrow::datatypes::Schema::from(
+        indices
+            .iter()
+            .map(|index| schema.fields[*index].clone())
+            .collect::<Vec<_>>(),
+    );
+
+    let mut ranges = vec![];
+
+    for row_group in &metadata.row_groups {
+        for field in &schema.fields {
+            for column in read::get_field_columns(row_group.columns(), &field.name) {
+                let (start, length) = column.byte_range();
+                ranges.push(start as usize..(start + length) as usize);
+            }
+        }
+    }
+
+    let reader = cloud_reader
+        .get_ranges(&ranges)
+        .map_err(object_store_to_explorer_error)?;
+
+    let file_reader = read::FileReader::new(
+        reader,
+        metadata.row_groups,
+        schema.clone(),
+        None,
+        None,
+        None,
+    );
+
+    let mut result: Option<DataFrame> = None;
+
+    for chunk in file_reader {
+        let chunk = chunk.map_err(PolarsError::from)?;
+        let df = DataFrame::try_from((chunk, schema.fields.as_slice()))?;
+
+        match result.as_mut() {
+            Some(acc) => {
+                acc.vstack_mut(&df)?;
+            }
+            None => result = Some(df),
+        }
+    }
+
+    // Without row groups there are no chunks, but the frame keeps the columns.
+    let mut df =
+        result.unwrap_or_else(|| DataFrame::from(&Schema::from_iter(schema.fields.iter())));
+    df.align_chunks();
+
+    Ok(ExDataFrame::new(normalize_numeric_dtypes(&mut df)?))
+}
+
+#[cfg(not(feature = "cloud"))]
+#[rustler::nif]
+pub fn df_from_parquet_url(
+    _url: &str,
+    _columns: Option<Vec<String>>,
+    _projection: Option<Vec<usize>>,
+) -> Result<ExDataFrame, ExplorerError> {
+    Err(ExplorerError::Other(format!(
+        "Explorer was compiled without the \"cloud\" feature enabled. \
+        This is mostly due to this feature being incompatible with your computer's architecture. \
+        Please read the section about precompilation in our README.md: https://github.com/elixir-explorer/explorer#precompilation"
+    )))
+}
+
+// The `data_page_size` is the limit in bytes of each data page. Smaller pages
+// make the files slightly bigger, but improve the granularity of predicate
+// pushdown when reading. When `None`, we keep the Polars default.
+//
+// Writing the statistics of each column can be slow for very wide
+// frames, so they are only written when `statistics` is true.
+//
+// The columns in `schema` are cast to the given dtypes before writing,
+// for readers that expect specific types. See `coerce_columns`.
+//
+// The `categorical_as` is how the categorical columns are written. See
+// `encode_parquet_categoricals`.
+#[rustler::nif(schedule = "DirtyIo")]
+#[allow(clippy::too_many_arguments)]
+pub fn df_to_parquet(
+    data: ExDataFrame,
+    filename: &str,
+    ex_compression: ExParquetCompression,
+    data_page_size: Option<usize>,
+    statistics: bool,
+    schema: Vec<(&str, &str)>,
+    categorical_as: &str,
+) -> Result<(), ExplorerError> {
+    let df = coerce_columns(data.clone_inner(), schema)?;
+    let mut df = encode_parquet_categoricals(df, categorical_as)?;
+    let file = create_file(filename)?;
+    let mut buf_writer = BufWriter::new(file);
+
+    let compression = ParquetCompression::try_from(ex_compression)?;
+
+    ParquetWriter::new(&mut buf_writer)
+        .with_compression(compression)
+        .with_data_pagesize_limit(data_page_size)
+        .with_statistics(statistics)
+        .finish(&mut df)?;
+    Ok(())
+}
+
+// Parquet files can't be appended to, so the existing file is read whole,
+// the new rows are stacked after its rows, and the file is rewritten. This
+// is meant for small accumulators, not big tables. The new file is written
+// next to the old one, flushed and synced to disk, and then renamed over it,
+// so readers never see a partial file. When the file doesn't exist, it is
+// written from scratch.
+//
+// There is no locking. When two appends to the same file run at the same
+// time, both read the same old rows and the last rename wins, so the rows
+// of the other append are lost. Callers must serialize the appends.
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_parquet_append(
+    filename: &str,
+    data: ExDataFrame,
+    ex_compression: ExParquetCompression,
+) -> Result<(), ExplorerError> {
+    let path = Path::new(filename);
+    let compression = ParquetCompression::try_from(ex_compression)?;
+
+    let mut df = if path.exists() {
+        let mut existing = ParquetReader::new(BufReader::new(open_file(path)?)).finish()?;
+        let (expected, actual) = (existing.schema(), data.schema());
+
+        if expected != actual {
+            return Err(ExplorerError::Other(format!(
+                "cannot append to {filename} because the schemas do not match. \
+                Expected {expected:?}, but got {actual:?}"
+            )));
+        }
+
+        existing.vstack_mut(&data)?;
+        existing.as_single_chunk_par();
+        existing
+    } else {
+        data.clone_inner()
+    };
+
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    let mut temp_file = tempfile::NamedTempFile::new_in(dir)?;
+    let mut buf_writer = BufWriter::new(temp_file.as_file_mut());
+
+    ParquetWriter::new(&mut buf_writer)
+        .with_compression(compression)
+        .finish(&mut df)?;
+
+    buf_writer
+        .into_inner()
+        .map_err(|error| error.into_error())?
+        .sync_all()?;
+
+    temp_file.persist(path).map_err(|error| error.error)?;
+    Ok(())
+}
+
+// Rewrites the Parquet file at `src` to `dst` with another compression. The
+// file is read and written with Arrow instead of Polars, so the Arrow schema,
+// the key-value metadata and the row groups are kept as they are. The pages
+// are still decoded and encoded again, since the Arrow writer can only write
+// arrays, not compressed pages. As with `df_parquet_append`, the new file is
+// written next to `dst` and renamed over it, so `dst` may be the same as `src`.
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_parquet_recompress(
+    src: &str,
+    dst: &str,
+    ex_compression: ExParquetCompression,
+) -> Result<(), ExplorerError> {
+    use polars::export::arrow::datatypes::PhysicalType as ArrowPhysicalType;
+    use polars::export::arrow::io::parquet::{read, write};
+
+    let compression = match ParquetCompression::try_from(ex_compression)? {
+        ParquetCompression::Uncompressed => write::CompressionOptions::Uncompressed,
+        ParquetCompression::Snappy => write::CompressionOptions::Snappy,
+        ParquetCompression::Gzip(level) => write::CompressionOptions::Gzip(level),
+        ParquetCompression::Lzo => write::CompressionOptions::Lzo,
+        ParquetCompression::Brotli(level) => write::CompressionOptions::Brotli(level),
+        ParquetCompression::Lz4Raw => write::CompressionOptions::Lz4Raw,
+        ParquetCompression::Zstd(level) => write::CompressionOptions::Zstd(level),
+    };
+
+    let mut buf_reader = BufReader::new(open_file(src)?);
+    let metadata = read::read_metadata(&mut buf_reader).map_err(PolarsError::from)?;
+    let schema = read::infer_schema(&metadata).map_err(PolarsError::from)?;
+
+    let key_value_metadata = schema
+        .metadata
+        .iter()
+        .map(|(key, value)| write::KeyValue {
+            key: key.clone(),
+            value: Some(value.clone()),
+        })
+        .collect();
+
+    let options = write::WriteOptions {
+        write_statistics: true,
+        compression,
+        version: write::Version::V2,
+        data_pagesize_limit: None,
+    };
+
+    // The dictionary arrays, like the categorical columns written by Polars,
+    // stay dictionary encoded. Everything else is written as plain values.
+    let encodings = schema
+        .fields
+        .iter()
+        .map(|field| {
+            write::transverse(&field.data_type, |data_type| {
+                match data_type.to_physical_type() {
+                    ArrowPhysicalType::Dictionary(_) => write::Encoding::RleDictionary,
+                    _ => write::Encoding::Plain,
+                }
+            })
+        })
+        .collect();
+
+    let reader = read::FileReader::new(
+        buf_reader,
+        metadata.row_groups,
+        schema.clone(),
+        None,
+        None,
+        None,
+    );
+
+    let row_groups = write::RowGroupIterator::try_new(reader, &schema, options, encodings)
+        .map_err(PolarsError::from)?;
+
+    let path = Path::new(dst);
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    let mut temp_file = tempfile::NamedTempFile::new_in(dir)?;
+    let mut parquet_writer =
+        write::FileWriter::try_new(BufWriter::new(temp_file.as_file_mut()), schema, options)
+            .map_err(PolarsError::from)?;
+
+    for row_group in row_groups {
+        let row_group = row_group.map_err(PolarsError::from)?;
+        parquet_writer.write(row_group).map_err(PolarsError::from)?;
+    }
+
+    parquet_writer
+        .end(Some(key_value_metadata))
+        .map_err(PolarsError::from)?;
+    parquet_writer.into_inner().flush()?;
+
+    temp_file.persist(path).map_err(|error| error.error)?;
+    Ok(())
+}
+
+// Checks that the dataframe has exactly the expected columns and dtypes,
+// failing with the first difference. The dataframe is not copied.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_validate_schema(
+    data: ExDataFrame,
+    expected_dtypes: Vec<(String, String)>,
+) -> Result<(), ExplorerError> {
+    for (name, dtype_str) in &expected_dtypes {
+        let expected = dtype_from_str(dtype_str)?;
+
+        let series = data.column(name).map_err(|_| {
+            ExplorerError::SchemaMismatch(SchemaMismatch::MissingColumn(name.clone()))
+        })?;
+
+        if series.dtype() != &expected {
+            return Err(ExplorerError::SchemaMismatch(SchemaMismatch::Dtype {
+                column: name.clone(),
+                expected: dtype_str.clone(),
+                actual: dtype_to_str(series.dtype()).unwrap_or_else(|_| series.dtype().to_string()),
+            }));
+        }
+    }
+
+    for name in data.get_column_names() {
+        if !expected_dtypes.iter().any(|(expected, _)| expected == name) {
+            return Err(ExplorerError::SchemaMismatch(SchemaMismatch::ExtraColumn(
+                name.to_string(),
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+// Converts a CSV file to Parquet, reading `batch_size` rows at a time and
+// writing each batch as it is read, so the whole file is never in memory.
+// The schema of the Parquet file is the one inferred for the first batch.
+#[rustler::nif(schedule = "DirtyIo")]
+#[allow(clippy::too_many_arguments)]
+pub fn df_csv_to_parquet(
+    csv_filename: &str,
+    parquet_filename: &str,
+    batch_size: usize,
+    infer_schema_length: Option<usize>,
+    has_header: bool,
+    delimiter_as_byte: u8,
+    dtypes: Vec<(&str, &str)>,
+    null_vals: Vec<String>,
+    ex_compression: ExParquetCompression,
+) -> Result<(), ExplorerError> {
+    let schema = schema_from_dtypes_pairs(dtypes)?;
+    let compression = ParquetCompression::try_from(ex_compression)?;
+
+    let new_reader = || -> Result<CsvReader<File>, ExplorerError> {
+        Ok(CsvReader::new(open_file(csv_filename)?)
+            .infer_schema(infer_schema_length)
+            .has_header(has_header)
+            .with_delimiter(delimiter_as_byte)
+            .with_dtypes(Some(schema.clone()))
+            .with_null_values(Some(NullValues::AllColumns(null_vals.clone()))))
+    };
+
+    let mut reader = new_reader()?
+        .with_chunk_size(batch_size)
+        .batched_read(None)?;
+
+    let file = create_file(parquet_filename)?;
+    let mut writer = None;
+
+    while let Some(batches) = reader.next_batches(1)? {
+        for df in batches {
+            if writer.is_none() {
+                writer = Some(
+                    ParquetWriter::new(BufWriter::new(file.try_clone()?))
+                        .with_compression(compression)
+                        .batched(&df.schema())?,
+                );
+            }
+
+            if let Some(writer) = writer.as_mut() {
+                writer.write_batch(&df)?;
+            }
+        }
+    }
+
+    match writer {
+        Some(mut writer) => {
+            writer.finish()?;
+        }
+        // Without any rows, only the columns are written.
+        None => {
+            let mut df = new_reader()?.with_n_rows(Some(0)).finish()?;
+
+            ParquetWriter::new(BufWriter::new(file))
+                .with_compression(compression)
+                .finish(&mut df)?;
+        }
+    }
+
+    Ok(())
+}
+
+// Casts the given columns, failing if any value would change on the way,
+// like an integer that overflows or a float with a fractional part.
+// Besides the dtypes of `dtype_from_str`, the sized numeric dtypes
+// ("i8" to "i32", "u8" to "u64" and "f32") are accepted here.
+fn coerce_columns(
+    mut df: DataFrame,
+    schema: Vec<(&str, &str)>,
+) -> Result<DataFrame, ExplorerError> {
+    for (name, dtype_str) in schema {
+        let dtype = match dtype_str {
+            "i8" => DataType::Int8,
+            "i16" => DataType::Int16,
+            "i32" => DataType::Int32,
+            "u8" => DataType::UInt8,
+            "u16" => DataType::UInt16,
+            "u32" => DataType::UInt32,
+            "u64" => DataType::UInt64,
+            "f32" => DataType::Float32,
+            other => dtype_from_str(other)?,
+        };
+
+        let series = df.column(name).map_err(|_| {
+            ExplorerError::Other(format!(
+                "cannot coerce column {name:?} because it does not exist"
+            ))
+        })?;
+
+        let lossy_error = || {
+            ExplorerError::Other(format!(
+                "cannot coerce column {name:?} from {} to {dtype_str} without losing values",
+                series.dtype()
+            ))
+        };
+
+        let coerced = series.strict_cast(&dtype).map_err(|_| lossy_error())?;
+
+        if !coerced.cast(series.dtype())?.series_equal_missing(series) {
+            return Err(lossy_error());
+        }
+
+        df.with_column(coerced)?;
+    }
+
+    Ok(df)
+}
+
+// Scheduled jobs may produce empty frames. With `skip_if_empty`, those are
+// not uploaded and `:skipped` is returned instead of `:written`, so no
+// zero-row object is left for the readers. The other cloud writers take
+// the same option.
+#[cfg(feature = "aws")]
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_to_parquet_cloud(
+    data: ExDataFrame,
+    ex_entry: ExS3Entry,
+    ex_compression: ExParquetCompression,
+    data_page_size: Option<usize>,
+    skip_if_empty: bool,
+    progress_pid: Option<LocalPid>,
+) -> Result<Atom, ExplorerError> {
+    if skip_if_empty && data.height() == 0 {
+        return Ok(atoms::skipped());
+    }
+
+    let mut cloud_writer = build_aws_s3_cloud_writer(ex_entry, progress_pid)?;
+
+    let compression = ParquetCompression::try_from(ex_compression)?;
+
+    ParquetWriter::new(&mut cloud_writer)
+        .with_compression(compression)
+        .with_data_pagesize_limit(data_page_size)
+        .finish(&mut data.clone())?;
+    Ok(atoms::written())
+}
+fn object_store_to_explorer_error(error: impl std::fmt::Debug) -> ExplorerError {
+    ExplorerError::Other(format!("Internal ObjectStore error: #{error:?}"))
+}
+
+#[cfg(feature = "aws")]
+fn build_aws_s3_cloud_writer(
+    ex_entry: ExS3Entry,
+    progress_pid: Option<LocalPid>,
+) -> Result<crate::cloud_writer::CloudWriter, ExplorerError> {
+    let aws_s3 = build_aws_s3_builder(ex_entry.bucket, &ex_entry.config)
+        .build()
+        .map_err(object_store_to_explorer_error)?;
+
+    let object_store: Box<dyn object_store::ObjectStore> = Box::new(aws_s3);
+    Ok(
+        crate::cloud_writer::CloudWriter::new(object_store, ex_entry.key.into())
+            .with_progress(progress_pid),
+    )
+}
+
+#[cfg(feature = "aws")]
+fn build_aws_s3_builder(bucket: String, config: &ExS3Config) -> object_store::aws::AmazonS3Builder {
+    let mut aws_builder = object_store::aws::AmazonS3Builder::new()
+        .with_region(&config.region)
+        .with_bucket_name(bucket)
+        .with_access_key_id(&config.access_key_id)
+        .with_secret_access_key(&config.secret_access_key);
+
+    if let Some(endpoint) = &config.endpoint {
+        aws_builder = aws_builder.with_allow_http(true).with_endpoint(endpoint);
+    }
+
+    if let Some(token) = &config.token {
+        aws_builder = aws_builder.with_token(token);
+    }
+
+    aws_builder
+}
+
+// Downloads the object to a local file, so it can be read with any of the local readers.
+// The object is streamed in chunks, so it is never entirely in memory.
+// Returns the number of bytes written.
+//
+// The chunks are written to a temporary file next to `local_path`, which is
+// renamed over it once the download completes. If the download fails, the
+// temporary file is removed and `local_path` is left untouched.
+#[cfg(feature = "aws")]
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn cloud_download(ex_entry: ExS3Entry, local_path: &str) -> Result<u64, ExplorerError> {
+    use futures::StreamExt;
+    use object_store::ObjectStore;
+
+    let aws_s3 = build_aws_s3_builder(ex_entry.bucket, &ex_entry.config)
+        .build()
+        .map_err(object_store_to_explorer_error)?;
+    let path: object_store::path::Path = ex_entry.key.into();
+
+    let dir = match Path::new(local_path).parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    let mut temp_file = tempfile::NamedTempFile::new_in(dir)?;
+    let mut buf_writer = BufWriter::new(temp_file.as_file_mut());
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .enable_io()
+        .build()?;
+
+    let bytes_written = runtime.block_on(async {
+        let mut stream = aws_s3
+            .get(&path)
+            .await
+            .map_err(object_store_to_explorer_error)?
+            .into_stream();
+
+        let mut bytes_written: u64 = 0;
+
+        while let Some(bytes) = stream.next().await {
+            let bytes = bytes.map_err(object_store_to_explorer_error)?;
+            buf_writer.write_all(&bytes)?;
+            bytes_written += bytes.len() as u64;
+        }
+
+        buf_writer.flush()?;
+        Ok::<u64, ExplorerError>(bytes_written)
+    })?;
+
+    drop(buf_writer);
+    temp_file.persist(local_path).map_err(|error| error.error)?;
+    Ok(bytes_written)
+}
+
+#[cfg(not(feature = "aws"))]
+#[rustler::nif]
+pub fn cloud_download(_ex_entry: ExS3Entry, _local_path: &str) -> Result<u64, ExplorerError> {
+    Err(ExplorerError::Other(format!(
+        "Explorer was compiled without the \"aws\" feature enabled. \
+        This is mostly due to this feature being incompatible with your computer's architecture. \
+        Please read the section about precompilation in our README.md: https://github.com/elixir-explorer/explorer#precompilation"
+    )))
+}
+
+// Reads a Parquet object from S3. The metadata is read from the footer,
+// so only the selected columns are downloaded afterwards.
+#[cfg(feature = "aws")]
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_from_parquet_cloud(
+    ex_entry: ExS3Entry,
+    columns: Option<Vec<String>>,
+    projection: Option<Vec<usize>>,
+) -> Result<ExDataFrame, ExplorerError> {
+    let aws_s3 = build_aws_s3_builder(ex_entry.bucket.clone(), &ex_entry.config)
+        .build()
+        .map_err(object_store_to_explorer_error)?;
+
+    let object_store: Box<dyn object_store::ObjectStore> = Box::new(aws_s3);
+    let cloud_reader = crate::cloud_reader::CloudReader::new(object_store, ex_entry.key.into())
+        .map_err(object_store_to_explorer_error)?;
+
+    read_parquet_ranges(cloud_reader, columns, projection)
+}
+
+#[cfg(not(feature = "aws"))]
+#[rustler::nif]
+pub fn df_from_parquet_cloud(
+    _ex_entry: ExS3Entry,
+    _columns: Option<Vec<String>>,
+    _projection: Option<Vec<usize>>,
+) -> Result<ExDataFrame, ExplorerError> {
+    Err(ExplorerError::Other(format!(
+        "Explorer was compiled without the \"aws\" feature enabled. \
+        This is mostly due to this feature being incompatible with your computer's architecture. \
+        Please read the section about precompilation in our README.md: https://github.com/elixir-explorer/explorer#precompilation"
+    )))
+}
+
+// Reads all the Parquet files under the prefix given as the key of the entry,
+// in the order of their keys, and concatenates them. This is the cloud
+// counterpart of `df_from_parquet_dataset`, without the hive partitioning.
+// All files must have the same schema.
+#[cfg(feature = "aws")]
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_from_parquet_cloud_prefix(
+    ex_entry: ExS3Entry,
+    columns: Option<Vec<String>>,
+) -> Result<ExDataFrame, ExplorerError> {
+    use futures::StreamExt;
+    use object_store::ObjectStore;
+
+    let aws_s3 = build_aws_s3_builder(ex_entry.bucket.clone(), &ex_entry.config)
+        .build()
+        .map_err(object_store_to_explorer_error)?;
+    let prefix: object_store::path::Path = ex_entry.key.clone().into();
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .enable_io()
+        .build()?;
+
+    runtime.block_on(async {
+        let mut stream = aws_s3
+            .list(Some(&prefix))
+            .await
+            .map_err(object_store_to_explorer_error)?;
+
+        let mut locations = vec![];
+
+        while let Some(meta) = stream.next().await {
+            let meta = meta.map_err(object_store_to_explorer_error)?;
+
+            if meta.location.as_ref().ends_with(".parquet") {
+                locations.push(meta.location);
+            }
+        }
+
+        locations.sort();
+
+        let mut first: Option<(object_store::path::Path, Schema)> = None;
+        let mut result: Option<DataFrame> = None;
+
+        for location in locations {
+            let read_error = |error: &dyn std::fmt::Display| {
+                ExplorerError::Other(format!(
+                    "could not read s3://{}/{location}: {error}",
+                    ex_entry.bucket
+                ))
+            };
+
+            let bytes = aws_s3
+                .get(&location)
+                .await
+                .map_err(|error| read_error(&error))?
+                .bytes()
+                .await
+                .map_err(|error| read_error(&error))?;
+
+            let df = ParquetReader::new(Cursor::new(bytes))
+                .with_columns(columns.clone())
+                .finish()
+                .map_err(|error| read_error(&error))?;
+
+            match &first {
+                Some((first_location, first_schema)) if first_schema != &df.schema() => {
+                    return Err(ExplorerError::Other(format!(
+                        "the schema of s3://{bucket}/{location} does not match \
+                        the schema of s3://{bucket}/{first_location}",
+                        bucket = ex_entry.bucket
+                    )))
+                }
+                Some(_) => (),
+                None => first = Some((location.clone(), df.schema())),
+            }
+
+            match result.as_mut() {
+                Some(acc) => {
+                    acc.vstack_mut(&df)?;
+                }
+                None => result = Some(df),
+            }
+        }
+
+        match result {
+            Some(mut df) => {
+                df.align_chunks();
+                Ok(ExDataFrame::new(normalize_numeric_dtypes(&mut df)?))
+            }
+            None => Err(ExplorerError::Other(format!(
+                "no Parquet files were found in {ex_entry}"
+            ))),
+        }
+    })
+}
+
+#[cfg(not(feature = "aws"))]
+#[rustler::nif]
+pub fn df_from_parquet_cloud_prefix(
+    _ex_entry: ExS3Entry,
+    _columns: Option<Vec<String>>,
+) -> Result<ExDataFrame, ExplorerError> {
+    Err(ExplorerError::Other(format!(
+        "Explorer was compiled without the \"aws\" feature enabled. \
+        This is mostly due to this feature being incompatible with your computer's architecture. \
+        Please read the section about precompilation in our README.md: https://github.com/elixir-explorer/explorer#precompilation"
+    )))
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_dump_parquet(
+    env: Env,
+    data: ExDataFrame,
+    ex_compression: ExParquetCompression,
+    data_page_size: Option<usize>,
+    statistics: bool,
+) -> Result<Binary, ExplorerError> {
+    let mut buf = vec![];
+
+    let compression = ParquetCompression::try_from(ex_compression)?;
+
+    ParquetWriter::new(&mut buf)
+        .with_compression(compression)
+        .with_data_pagesize_limit(data_page_size)
+        .with_statistics(statistics)
+        .finish(&mut data.clone())?;
+
+    let mut values_binary = NewBinary::new(env, buf.len());
+    values_binary.copy_from_slice(&buf);
+
+    Ok(values_binary.into())
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_load_parquet(binary: Binary) -> Result<ExDataFrame, ExplorerError> {
+    let cursor = Cursor::new(binary.as_slice());
+    let reader = ParquetReader::new(cursor);
+
+    finish_reader(reader)
+}
+
+// ============ Any format ============ //
+
+// Reads the first `n` rows of a file together with the dtypes of its columns,
+// so a preview takes a single call. The `format` is one of "csv", "parquet",
+// "ipc" and "ndjson". The read stops after `n` rows, so its cost doesn't grow
+// with the size of the file. Parquet files are read by row groups, and only
+// the row groups holding the first `n` rows are read.
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_preview(
+    filename: &str,
+    format: &str,
+    n: usize,
+) -> Result<(ExDataFrame, Vec<(String, String)>), ExplorerError> {
+    let file = open_file(filename)?;
+
+    let mut df = match format {
+        "csv" => CsvReader::new(file).with_n_rows(Some(n)).finish()?,
+        "parquet" => ParquetReader::new(BufReader::new(file))
+            .with_n_rows(Some(n))
+            .finish()?,
+        "ipc" => IpcReader::new(BufReader::new(file))
+            .with_n_rows(Some(n))
+            .finish()?,
+        #[cfg(feature = "ndjson")]
+        "ndjson" => {
+            // Only the first lines are given to the reader.
+            let mut lines = vec![];
+            let mut reader = BufReader::new(file);
+
+            for _ in 0..n {
+                if reader.read_until(b'\n', &mut lines)? == 0 {
+                    break;
+                }
+            }
+
+            JsonReader::new(Cursor::new(lines))
+                .with_json_format(JsonFormat::JsonLines)
+                .finish()?
+        }
+        #[cfg(not(feature = "ndjson"))]
+        "ndjson" => {
+            return Err(ExplorerError::Other(
+                "Explorer was compiled without the \"ndjson\" feature enabled".into(),
+            ))
+        }
+        other => {
+            return Err(ExplorerError::Other(format!(
+                "the format {other} is not supported for previews. \
+                The supported formats are: csv, parquet, ipc and ndjson"
+            )))
+        }
+    };
+
+    let df = normalize_numeric_dtypes(&mut df)?;
+
+    let dtypes = df
+        .schema()
+        .iter()
+        .map(|(name, dtype)| (name.to_string(), dtype.to_string()))
+        .collect();
+
+    Ok((ExDataFrame::new(df), dtypes))
+}
+
+// Picks the writer from the extension of the filename, for when the format
+// is only known at runtime. The options have the same defaults as the NIF
+// of each writer.
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_to_file(
+    data: ExDataFrame,
+    filename: &str,
+    opts: ExWriteOptions,
+) -> Result<(), ExplorerError> {
+    let extension = Path::new(filename)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default();
+
+    match extension {
+        "csv" => write_csv_file(
+            &data,
+            filename,
+            opts.header.unwrap_or(true),
+            opts.delimiter.unwrap_or(b','),
+            true,
+            false,
+            false,
+            None,
+            None,
+            None,
+            None,
+            true,
+            CsvSpecialFloats::default(),
+        ),
+        "parquet" => {
+            let compression = match decode_write_compression::<ExParquetCompression>(
+                opts.compression,
+                "Parquet",
+            )? {
+                Some(compression) => ParquetCompression::try_from(compression)?,
+                None => ParquetCompression::Uncompressed,
+            };
+
+            let file = create_file(filename)?;
+            ParquetWriter::new(BufWriter::new(file))
+                .with_compression(compression)
+                .finish(&mut data.clone_inner())?;
+            Ok(())
+        }
+        "arrow" => {
+            let compression = match decode_write_compression::<String>(opts.compression, "IPC")? {
+                Some(compression) => Some(decode_ipc_compression(&compression)?),
+                None => None,
+            };
+
+            let file = create_file(filename)?;
+            write_ipc(
+                &mut BufWriter::new(file),
+                &mut data.clone_inner(),
+                compression,
+                vec![],
+            )
+        }
+        #[cfg(feature = "ndjson")]
+        "ndjson" => {
+            let compression = decode_write_compression::<String>(opts.compression, "NDJSON")?;
+            let file = create_file(filename)?;
+            write_ndjson(BufWriter::new(file), &data, compression.as_deref())
+        }
+        #[cfg(not(feature = "ndjson"))]
+        "ndjson" => Err(ExplorerError::Other(
+            "Explorer was compiled without the \"ndjson\" feature enabled".into(),
+        )),
+        _ => Err(ExplorerError::Other(format!(
+            "cannot write {filename} because its extension is not supported. \
+            The supported extensions are: .csv, .parquet, .arrow and .ndjson"
+        ))),
+    }
+}
+
+fn decode_write_compression<'a, T: rustler::Decoder<'a>>(
+    compression: Option<rustler::Term<'a>>,
+    format: &str,
+) -> Result<Option<T>, ExplorerError> {
+    match compression {
+        None => Ok(None),
+        Some(term) => term.decode().map(Some).map_err(|_| {
+            ExplorerError::Other(format!("invalid compression {term:?} for {format}"))
+        }),
+    }
+}
+
+// ============ IPC ============ //
+
+// The file is opened up to `open_retries` more times on transient errors,
+// see `open_file_with_retries`.
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_from_ipc(
+    filename: &str,
+    columns: Option<Vec<String>>,
+    projection: Option<Vec<usize>>,
+    normalize: bool,
+    rename: Vec<(String, String)>,
+    open_retries: usize,
+) -> Result<ExDataFrame, ExplorerError> {
+    let file = open_file_with_retries(filename, open_retries)?;
+    let buf_reader = BufReader::new(file);
+    let reader = IpcReader::new(buf_reader)
+        .with_columns(columns)
+        .with_projection(projection);
+
+    let df = if normalize {
+        finish_reader(reader)?
+    } else {
+        finish_reader_raw(reader)?
+    };
+
+    rename_columns(df, rename)
+}
+
+// When `chunk_size` is `None`, the existing chunks of the dataframe are kept.
+// The `metadata` is written as the custom metadata of the Arrow schema.
+// The `dictionary_encode` columns are written as dictionaries, see
+// `dictionary_encode_columns`.
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_to_ipc(
+    data: ExDataFrame,
+    filename: &str,
+    compression: Option<&str>,
+    chunk_size: Option<usize>,
+    metadata: Vec<(String, String)>,
+    dictionary_encode: Vec<String>,
+) -> Result<(), ExplorerError> {
+    let compression = match compression {
+        Some(algo) => Some(decode_ipc_compression(algo)?),
+        None => None,
+    };
+
+    let mut df = match chunk_size {
+        Some(chunk_size) => split_in_chunks(&data, chunk_size)?,
+        None => data.clone_inner(),
+    };
+
+    dictionary_encode_columns(&mut df, &dictionary_encode)?;
+
+    let file = create_file(filename)?;
+    let mut buf_writer = BufWriter::new(file);
+    write_ipc(&mut buf_writer, &mut df, compression, metadata)
+}
+
+// Casts the string columns to categoricals, which Arrow writes as
+// dictionaries. Repetitive strings are stored once, so the file is smaller
+// without the cost of decompressing it. They are read back as categoricals.
+fn dictionary_encode_columns(df: &mut DataFrame, columns: &[String]) -> Result<(), ExplorerError> {
+    for name in columns {
+        let series = df.column(name)?;
+
+        match series.dtype() {
+            DataType::Utf8 => {
+                let encoded = series.cast(&DataType::Categorical(None))?;
+                df.replace(name, encoded)?;
+            }
+            DataType::Categorical(_) => (),
+            dtype => {
+                return Err(ExplorerError::Other(format!(
+                    "cannot dictionary encode the column {name} because it has dtype {dtype}. \
+                    Only string columns can be dictionary encoded"
+                )))
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// The `IpcWriter` of Polars can't set the custom metadata of the schema,
+// so we use the Arrow writer when there is metadata. Without metadata,
+// the `IpcWriter` is used, so the output stays the same.
+fn write_ipc<W: Write>(
+    writer: &mut W,
+    df: &mut DataFrame,
+    compression: Option<IpcCompression>,
+    metadata: Vec<(String, String)>,
+) -> Result<(), ExplorerError> {
+    use polars::export::arrow::io::ipc::write;
+
+    if metadata.is_empty() {
+        IpcWriter::new(writer)
+            .with_compression(compression)
+            .finish(df)?;
+
+        return Ok(());
+    }
+
+    let schema = df
+        .schema()
+        .to_arrow()
+        .with_metadata(metadata.into_iter().collect());
+    let options = write::WriteOptions {
+        compression: compression.map(|compression| compression.into()),
+    };
+
+    let mut ipc_writer =
+        write::FileWriter::try_new(writer, schema, None, options).map_err(PolarsError::from)?;
+
+    df.align_chunks();
+
+    for chunk in df.iter_chunks() {
+        ipc_writer.write(&chunk, None).map_err(PolarsError::from)?;
+    }
+
+    ipc_writer.finish().map_err(PolarsError::from)?;
+    Ok(())
+}
+
+// Each chunk is written as a record batch, so we split the dataframe in
+// chunks of at most `chunk_size` rows to keep the record batches bounded.
+fn split_in_chunks(df: &DataFrame, chunk_size: usize) -> Result<DataFrame, ExplorerError> {
+    if chunk_size == 0 {
+        return Err(ExplorerError::Other(
+            "the chunk size must be greater than zero".into(),
+        ));
+    }
+
+    let mut df = df.clone();
+    df.as_single_chunk_par();
+
+    let mut result = df.slice(0, chunk_size);
+    let mut offset = chunk_size;
+
+    while offset < df.height() {
+        result.vstack_mut(&df.slice(offset as i64, chunk_size))?;
+        offset += chunk_size;
+    }
+
+    Ok(result)
+}
+
+#[cfg(feature = "aws")]
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_to_ipc_cloud(
+    data: ExDataFrame,
+    ex_entry: ExS3Entry,
+    compression: Option<&str>,
+    skip_if_empty: bool,
+    progress_pid: Option<LocalPid>,
+) -> Result<Atom, ExplorerError> {
+    if skip_if_empty && data.height() == 0 {
+        return Ok(atoms::skipped());
+    }
+
+    let compression = match compression {
+        Some(algo) => Some(decode_ipc_compression(algo)?),
+        None => None,
+    };
+
+    let mut cloud_writer = build_aws_s3_cloud_writer(ex_entry, progress_pid)?;
+
+    IpcWriter::new(&mut cloud_writer)
+        .with_compression(compression)
+        .finish(&mut data.clone())?;
+    Ok(atoms::written())
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_dump_ipc<'a>(
+    env: Env<'a>,
+    data: ExDataFrame,
+    compression: Option<&str>,
+    metadata: Vec<(String, String)>,
+    dictionary_encode: Vec<String>,
+) -> Result<Binary<'a>, ExplorerError> {
+    let mut buf = vec![];
+
+    let compression = match compression {
+        Some(algo) => Some(decode_ipc_compression(algo)?),
+        None => None,
+    };
+
+    let mut df = data.clone_inner();
+    dictionary_encode_columns(&mut df, &dictionary_encode)?;
+    write_ipc(&mut buf, &mut df, compression, metadata)?;
+
+    let mut values_binary = NewBinary::new(env, buf.len());
+    values_binary.copy_from_slice(&buf);
+
+    Ok(values_binary.into())
+}
+
+// Arrow compresses each buffer on its own, without a dictionary. So the IPC
+// file is written uncompressed and then compressed as a whole with the zstd
+// dictionary. The result is a zstd frame rather than an IPC file, which can
+// only be loaded by `df_load_ipc_zstd_dict` with the same dictionary.
+// See `df_train_zstd_dict`.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_dump_ipc_zstd_dict<'a>(
+    env: Env<'a>,
+    data: ExDataFrame,
+    zstd_dict: Binary,
+    metadata: Vec<(String, String)>,
+    dictionary_encode: Vec<String>,
+) -> Result<Binary<'a>, ExplorerError> {
+    let mut buf = vec![];
+
+    let mut df = data.clone_inner();
+    dictionary_encode_columns(&mut df, &dictionary_encode)?;
+
+    let mut encoder = zstd::Encoder::with_dictionary(&mut buf, 0, zstd_dict.as_slice())?;
+    write_ipc(&mut encoder, &mut df, None, metadata)?;
+    encoder.finish()?;
+
+    let mut values_binary = NewBinary::new(env, buf.len());
+    values_binary.copy_from_slice(&buf);
+
+    Ok(values_binary.into())
+}
+
+// Same as `df_dump_ipc`, but also returns the names and dtypes of the
+// columns, so the receiver can check them before decoding the binary.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_dump_ipc_with_schema<'a>(
+    env: Env<'a>,
+    data: ExDataFrame,
+    compression: Option<&str>,
+) -> Result<(Binary<'a>, Vec<(String, String)>), ExplorerError> {
+    let mut buf = vec![];
+
+    let compression = match compression {
+        Some(algo) => Some(decode_ipc_compression(algo)?),
+        None => None,
+    };
+
+    let schema = data
+        .schema()
+        .iter()
+        .map(|(name, dtype)| (name.to_string(), dtype.to_string()))
+        .collect();
+
+    write_ipc(&mut buf, &mut data.clone(), compression, vec![])?;
+
+    let mut values_binary = NewBinary::new(env, buf.len());
+    values_binary.copy_from_slice(&buf);
+
+    Ok((values_binary.into(), schema))
+}
+
+// Dumps each chunk of the dataframe as an uncompressed IPC binary with a
+// single record batch, together with the names and dtypes of the columns.
+// The binaries are in the order of the chunks, so concatenating the frames
+// they hold gives back the rows in their original order. The chunks are
+// kept as they are, unless the columns are chunked differently, in which
+// case they are first merged into a single chunk.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_dump_arrow_chunks<'a>(
+    env: Env<'a>,
+    data: ExDataFrame,
+) -> Result<(Vec<Binary<'a>>, Vec<(String, String)>), ExplorerError> {
+    use polars::export::arrow::io::ipc::write;
+
+    let mut df = data.clone_inner();
+    df.align_chunks();
+
+    let schema = df.schema();
+    let arrow_schema = schema.to_arrow();
+    let mut binaries = vec![];
+
+    for chunk in df.iter_chunks() {
+        let mut buf = vec![];
+        let options = write::WriteOptions { compression: None };
+        let mut ipc_writer =
+            write::FileWriter::try_new(&mut buf, arrow_schema.clone(), None, options)
+                .map_err(PolarsError::from)?;
+
+        ipc_writer.write(&chunk, None).map_err(PolarsError::from)?;
+        ipc_writer.finish().map_err(PolarsError::from)?;
+
+        let mut values_binary = NewBinary::new(env, buf.len());
+        values_binary.copy_from_slice(&buf);
+        binaries.push(values_binary.into());
+    }
+
+    let dtypes = schema
+        .iter()
+        .map(|(name, dtype)| (name.to_string(), dtype.to_string()))
+        .collect();
+
+    Ok((binaries, dtypes))
+}
+
+// Splits the dataframe into `n_shards` row ranges and dumps each one as an
+// IPC binary with the full schema, so each shard can be loaded on its own.
+// When the columns have exactly `n_shards` aligned chunks, the shards follow
+// the chunks. Otherwise the rows are split in ranges whose lengths differ by
+// at most one. With more shards than rows, the last shards are empty, but
+// still have the schema.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_dump_ipc_shards<'a>(
+    env: Env<'a>,
+    data: ExDataFrame,
+    n_shards: usize,
+    compression: Option<&str>,
+) -> Result<Vec<Binary<'a>>, ExplorerError> {
+    if n_shards == 0 {
+        return Err(ExplorerError::Other(
+            "the number of shards must be greater than zero".into(),
+        ));
+    }
+
+    let compression = match compression {
+        Some(algo) => Some(decode_ipc_compression(algo)?),
+        None => None,
+    };
+
+    let mut offset = 0;
+    let mut binaries = Vec::with_capacity(n_shards);
+
+    for length in shard_lengths(&data, n_shards) {
+        let mut shard = data.slice(offset as i64, length);
+        offset += length;
+
+        let mut buf = vec![];
+        write_ipc(&mut buf, &mut shard, compression, vec![])?;
+
+        let mut values_binary = NewBinary::new(env, buf.len());
+        values_binary.copy_from_slice(&buf);
+        binaries.push(values_binary.into());
+    }
+
+    Ok(binaries)
+}
+
+fn shard_lengths(df: &DataFrame, n_shards: usize) -> Vec<usize> {
+    let columns = df.get_columns();
+
+    if let Some(first) = columns.first() {
+        let aligned = columns
+            .iter()
+            .all(|column| column.chunk_lengths().eq(first.chunk_lengths()));
+
+        if aligned && first.n_chunks() == n_shards {
+            return first.chunk_lengths().collect();
+        }
+    }
+
+    let height = df.height();
+
+    (0..n_shards)
+        .map(|shard| height / n_shards + usize::from(shard < height % n_shards))
+        .collect()
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_load_ipc(
+    binary: Binary,
+    columns: Option<Vec<String>>,
+    projection: Option<Vec<usize>>,
+) -> Result<ExDataFrame, ExplorerError> {
+    let reader = IpcReader::new(Cursor::new(binary.as_slice()))
+        .with_columns(columns)
+        .with_projection(projection);
+
+    finish_reader(reader)
+}
+
+// The `zstd_dict` must be the dictionary the binary was dumped with.
+// See `df_dump_ipc_zstd_dict`.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_load_ipc_zstd_dict(
+    binary: Binary,
+    zstd_dict: Binary,
+    columns: Option<Vec<String>>,
+    projection: Option<Vec<usize>>,
+) -> Result<ExDataFrame, ExplorerError> {
+    let mut decompressed = vec![];
+
+    zstd::Decoder::with_dictionary(binary.as_slice(), zstd_dict.as_slice())?
+        .read_to_end(&mut decompressed)?;
+
+    let reader = IpcReader::new(Cursor::new(decompressed))
+        .with_columns(columns)
+        .with_projection(projection);
+
+    finish_reader(reader)
+}
+
+// Trains a zstd dictionary of at most `dict_size` bytes from sample
+// binaries, such as IPC binaries dumped without compression. The samples
+// should be similar to the binaries that will be compressed with it.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_train_zstd_dict<'a>(
+    env: Env<'a>,
+    samples: Vec<Binary>,
+    dict_size: usize,
+) -> Result<Binary<'a>, ExplorerError> {
+    let samples: Vec<&[u8]> = samples.iter().map(|sample| sample.as_slice()).collect();
+
+    let dict = zstd::dict::from_samples(&samples, dict_size).map_err(|error| {
+        ExplorerError::Other(format!("could not train a zstd dictionary: {error}"))
+    })?;
 
-    finish_reader(reader)
+    let mut values_binary = NewBinary::new(env, dict.len());
+    values_binary.copy_from_slice(&dict);
+
+    Ok(values_binary.into())
 }
 
-pub fn schema_from_dtypes_pairs(dtypes: Vec<(&str, &str)>) -> Result<Arc<Schema>, ExplorerError> {
-    let mut schema = Schema::new();
-    for (name, dtype_str) in dtypes {
-        let dtype = dtype_from_str(dtype_str)?;
-        schema.with_column(name.into(), dtype);
+// Loads binaries dumped by `df_dump_ipc` and stacks them in order. All of them
+// must have the same schema as the first one, after selecting the columns.
+#[rustler::nif(schedule = "DirtyCpu")]
+pub fn df_load_ipc_concat(
+    binaries: Vec<Binary>,
+    columns: Option<Vec<String>>,
+    projection: Option<Vec<usize>>,
+) -> Result<ExDataFrame, ExplorerError> {
+    let mut result: Option<DataFrame> = None;
+
+    for (index, binary) in binaries.iter().enumerate() {
+        let df = IpcReader::new(Cursor::new(binary.as_slice()))
+            .with_columns(columns.clone())
+            .with_projection(projection.clone())
+            .finish()?;
+
+        match result.as_mut() {
+            Some(acc) => {
+                let (expected, actual) = (acc.schema(), df.schema());
+
+                if expected != actual {
+                    return Err(ExplorerError::Other(format!(
+                        "the IPC binary at index {index} does not match the schema of the first one. \
+                        Expected {expected:?}, but got {actual:?}"
+                    )));
+                }
+
+                acc.vstack_mut(&df)?;
+            }
+            None => result = Some(df),
+        }
+    }
+
+    match result {
+        Some(mut df) => {
+            df.align_chunks();
+            Ok(ExDataFrame::new(normalize_numeric_dtypes(&mut df)?))
+        }
+        None => Err(ExplorerError::Other(
+            "expected at least one IPC binary to concatenate".into(),
+        )),
     }
-    Ok(Arc::new(schema))
 }
 
-fn dtype_from_str(dtype: &str) -> Result<DataType, ExplorerError> {
-    match dtype {
-        "binary" => Ok(DataType::Binary),
-        "bool" => Ok(DataType::Boolean),
-        "cat" => Ok(DataType::Categorical(None)),
-        "date" => Ok(DataType::Date),
-        "datetime[ms]" => Ok(DataType::Datetime(TimeUnit::Milliseconds, None)),
-        "datetime[ns]" => Ok(DataType::Datetime(TimeUnit::Nanoseconds, None)),
-        "datetime[μs]" => Ok(DataType::Datetime(TimeUnit::Microseconds, None)),
-        "f64" => Ok(DataType::Float64),
-        "i64" => Ok(DataType::Int64),
-        "str" => Ok(DataType::Utf8),
-        _ => Err(ExplorerError::Internal("Unrecognised datatype".into())),
+// Reads only the schema in the footer of the IPC file, without reading any record batch.
+// The dtypes are returned in the same format that `dtype_from_str` understands.
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_ipc_schema(filename: &str) -> Result<Vec<(String, String)>, ExplorerError> {
+    use polars::export::arrow::io::ipc::read;
+
+    let file = open_file(filename)?;
+    let mut buf_reader = BufReader::new(file);
+    let metadata = read::read_file_metadata(&mut buf_reader).map_err(PolarsError::from)?;
+
+    arrow_schema_to_dtype_pairs(&metadata.schema)
+}
+
+// Returns the custom metadata of the schema in the footer of the IPC file,
+// which is set with the `metadata` of `df_to_ipc`.
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_ipc_metadata(filename: &str) -> Result<Vec<(String, String)>, ExplorerError> {
+    use polars::export::arrow::io::ipc::read;
+
+    let file = open_file(filename)?;
+    let mut buf_reader = BufReader::new(file);
+    let metadata = read::read_file_metadata(&mut buf_reader).map_err(PolarsError::from)?;
+
+    Ok(metadata.schema.metadata.into_iter().collect())
+}
+
+fn arrow_schema_to_dtype_pairs(
+    schema: &polars::export::arrow::datatypes::Schema,
+) -> Result<Vec<(String, String)>, ExplorerError> {
+    schema
+        .fields
+        .iter()
+        .map(|field| {
+            let dtype = DataType::from(&field.data_type);
+            Ok((field.name.clone(), dtype_to_str(&dtype)?))
+        })
+        .collect()
+}
+
+fn decode_ipc_compression(compression: &str) -> Result<IpcCompression, ExplorerError> {
+    match compression {
+        "lz4" => Ok(IpcCompression::LZ4),
+        "zstd" => Ok(IpcCompression::ZSTD),
+        other => Err(ExplorerError::Other(format!(
+            "the algorithm {other} is not supported for IPC compression"
+        ))),
     }
 }
 
+// ============ IPC Streaming ============ //
+
+// The file is opened up to `open_retries` more times on transient errors,
+// see `open_file_with_retries`.
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_from_ipc_stream(
+    filename: &str,
+    columns: Option<Vec<String>>,
+    projection: Option<Vec<usize>>,
+    open_retries: usize,
+) -> Result<ExDataFrame, ExplorerError> {
+    let file = open_file_with_retries(filename, open_retries)?;
+    let buf_reader = BufReader::new(file);
+    let reader = IpcStreamReader::new(buf_reader)
+        .with_columns(columns)
+        .with_projection(projection);
+
+    finish_reader(reader)
+}
+
 #[rustler::nif(schedule = "DirtyIo")]
-pub fn df_to_csv(
+pub fn df_to_ipc_stream(
     data: ExDataFrame,
     filename: &str,
-    has_headers: bool,
-    delimiter: u8,
+    compression: Option<&str>,
 ) -> Result<(), ExplorerError> {
-    let file = File::create(filename)?;
-    let mut buf_writer = BufWriter::new(file);
-    CsvWriter::new(&mut buf_writer)
-        .has_header(has_headers)
-        .with_delimiter(delimiter)
+    let compression = match compression {
+        Some(algo) => Some(decode_ipc_stream_compression(algo)?),
+        None => None,
+    };
+
+    let mut file = create_file(filename)?;
+    IpcStreamWriter::new(&mut file)
+        .with_compression(compression)
         .finish(&mut data.clone())?;
     Ok(())
 }
 
 #[cfg(feature = "aws")]
 #[rustler::nif(schedule = "DirtyIo")]
-pub fn df_to_csv_cloud(
+pub fn df_to_ipc_stream_cloud(
     data: ExDataFrame,
     ex_entry: ExS3Entry,
-    has_headers: bool,
-    delimiter: u8,
-) -> Result<(), ExplorerError> {
-    let mut cloud_writer = build_aws_s3_cloud_writer(ex_entry)?;
+    compression: Option<&str>,
+    skip_if_empty: bool,
+    progress_pid: Option<LocalPid>,
+) -> Result<Atom, ExplorerError> {
+    if skip_if_empty && data.height() == 0 {
+        return Ok(atoms::skipped());
+    }
 
-    CsvWriter::new(&mut cloud_writer)
-        .has_header(has_headers)
-        .with_delimiter(delimiter)
+    let compression = match compression {
+        Some(algo) => Some(decode_ipc_stream_compression(algo)?),
+        None => None,
+    };
+
+    let mut cloud_writer = build_aws_s3_cloud_writer(ex_entry, progress_pid)?;
+
+    IpcStreamWriter::new(&mut cloud_writer)
+        .with_compression(compression)
         .finish(&mut data.clone())?;
-    Ok(())
+    Ok(atoms::written())
 }
 
 #[rustler::nif(schedule = "DirtyCpu")]
-pub fn df_dump_csv(
-    env: Env,
+pub fn df_dump_ipc_stream<'a>(
+    env: Env<'a>,
     data: ExDataFrame,
-    has_headers: bool,
-    delimiter: u8,
-) -> Result<Binary, ExplorerError> {
+    compression: Option<&str>,
+) -> Result<Binary<'a>, ExplorerError> {
     let mut buf = vec![];
 
-    CsvWriter::new(&mut buf)
-        .has_header(has_headers)
-        .with_delimiter(delimiter)
+    let compression = match compression {
+        Some(algo) => Some(decode_ipc_stream_compression(algo)?),
+        None => None,
+    };
+
+    IpcStreamWriter::new(&mut buf)
+        .with_compression(compression)
         .finish(&mut data.clone())?;
 
     let mut values_binary = NewBinary::new(env, buf.len());
@@ -157,148 +5469,438 @@ pub fn df_dump_csv(
 }
 
 #[rustler::nif(schedule = "DirtyCpu")]
-#[allow(clippy::too_many_arguments)]
-pub fn df_load_csv(
+pub fn df_load_ipc_stream(
     binary: Binary,
-    infer_schema_length: Option<usize>,
-    has_header: bool,
-    stop_after_n_rows: Option<usize>,
-    skip_rows: usize,
+    columns: Option<Vec<String>>,
     projection: Option<Vec<usize>>,
-    delimiter_as_byte: u8,
-    do_rechunk: bool,
-    column_names: Option<Vec<String>>,
-    dtypes: Vec<(&str, &str)>,
-    encoding: &str,
-    null_vals: Vec<String>,
-    parse_dates: bool,
-    eol_delimiter: Option<u8>,
 ) -> Result<ExDataFrame, ExplorerError> {
-    let encoding = match encoding {
-        "utf8-lossy" => CsvEncoding::LossyUtf8,
-        _ => CsvEncoding::Utf8,
+    let cursor = Cursor::new(binary.as_slice());
+    let reader = IpcStreamReader::new(cursor)
+        .with_columns(columns)
+        .with_projection(projection);
+
+    finish_reader(reader)
+}
+
+// The stream writer allows appending multiple dataframes, as consecutive
+// record batches, to the same IPC stream. All of them must have the same schema.
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_ipc_stream_writer_open(
+    filename: &str,
+    compression: Option<&str>,
+) -> Result<ResourceArc<ExIpcStreamWriterRef>, ExplorerError> {
+    use polars::export::arrow::io::ipc::write::{StreamWriter, WriteOptions};
+
+    let compression = match compression {
+        Some(algo) => Some(decode_ipc_stream_compression(algo)?),
+        None => None,
     };
 
-    let cursor = Cursor::new(binary.as_slice());
+    let file = create_file(filename)?;
+    let writer = StreamWriter::new(BufWriter::new(file), WriteOptions { compression });
 
-    let reader = CsvReader::new(cursor)
-        .infer_schema(infer_schema_length)
-        .has_header(has_header)
-        .with_try_parse_dates(parse_dates)
-        .with_n_rows(stop_after_n_rows)
-        .with_delimiter(delimiter_as_byte)
-        .with_skip_rows(skip_rows)
-        .with_projection(projection)
-        .with_rechunk(do_rechunk)
-        .with_encoding(encoding)
-        .with_columns(column_names)
-        .with_dtypes(Some(schema_from_dtypes_pairs(dtypes)?))
-        .with_null_values(Some(NullValues::AllColumns(null_vals)))
-        .with_end_of_line_char(eol_delimiter.unwrap_or(b'\n'));
+    Ok(ResourceArc::new(ExIpcStreamWriterRef::new(
+        ExIpcStreamWriter {
+            writer,
+            schema: None,
+        },
+    )))
+}
 
-    finish_reader(reader)
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_ipc_stream_writer_write(
+    resource: ResourceArc<ExIpcStreamWriterRef>,
+    data: ExDataFrame,
+) -> Result<(), ExplorerError> {
+    let mut guard = resource
+        .0
+        .lock()
+        .map_err(|_| ExplorerError::Internal("the IPC stream writer lock is poisoned".into()))?;
+
+    let stream_writer = guard
+        .as_mut()
+        .ok_or_else(|| ExplorerError::Other("the IPC stream writer is already closed".into()))?;
+
+    let schema = data.schema();
+
+    match &stream_writer.schema {
+        Some(expected) if expected != &schema => {
+            return Err(ExplorerError::Other(format!(
+                "cannot write to the IPC stream because the schemas do not match. \
+                Expected {expected:?}, but got {schema:?}"
+            )))
+        }
+        Some(_) => (),
+        None => {
+            stream_writer
+                .writer
+                .start(&schema.to_arrow(), None)
+                .map_err(PolarsError::from)?;
+            stream_writer.schema = Some(schema);
+        }
+    }
+
+    let mut df = data.clone_inner();
+    df.align_chunks();
+
+    for chunk in df.iter_chunks() {
+        stream_writer
+            .writer
+            .write(&chunk, None)
+            .map_err(PolarsError::from)?;
+    }
+
+    Ok(())
 }
 
-// ============ Parquet ============ //
+// Writes the end of the stream. Dropping the writer without closing it
+// leaves the stream without the end-of-stream marker.
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_ipc_stream_writer_close(
+    resource: ResourceArc<ExIpcStreamWriterRef>,
+) -> Result<(), ExplorerError> {
+    let mut guard = resource
+        .0
+        .lock()
+        .map_err(|_| ExplorerError::Internal("the IPC stream writer lock is poisoned".into()))?;
+
+    let mut stream_writer = guard
+        .take()
+        .ok_or_else(|| ExplorerError::Other("the IPC stream writer is already closed".into()))?;
+
+    // An empty stream still needs a schema to be valid.
+    if stream_writer.schema.is_none() {
+        stream_writer
+            .writer
+            .start(&Schema::new().to_arrow(), None)
+            .map_err(PolarsError::from)?;
+    }
+
+    stream_writer.writer.finish().map_err(PolarsError::from)?;
+    stream_writer.writer.into_inner().flush()?;
+
+    Ok(())
+}
 
+// Reads only the schema at the start of the IPC stream, without reading any record batch.
 #[rustler::nif(schedule = "DirtyIo")]
-pub fn df_from_parquet(
+pub fn df_ipc_stream_schema(filename: &str) -> Result<Vec<(String, String)>, ExplorerError> {
+    use polars::export::arrow::io::ipc::read;
+
+    let file = open_file(filename)?;
+    let mut buf_reader = BufReader::new(file);
+    let metadata = read::read_stream_metadata(&mut buf_reader).map_err(PolarsError::from)?;
+
+    arrow_schema_to_dtype_pairs(&metadata.schema)
+}
+
+fn decode_ipc_stream_compression(compression: &str) -> Result<IpcStreamCompression, ExplorerError> {
+    match compression {
+        "lz4" => Ok(IpcStreamCompression::LZ4),
+        "zstd" => Ok(IpcStreamCompression::ZSTD),
+        other => Err(ExplorerError::Other(format!(
+            "the algorithm {other} is not supported for IPC stream compression"
+        ))),
+    }
+}
+
+// ============ NDJSON ============ //
+
+// The columns are discovered in the order their keys appear in the file,
+// which is not stable across files. The `column_order` pins the order, so
+// the dataframes of multiple files can be concatenated. See `order_columns`.
+//
+// With `ignore_errors`, the lines that are not JSON objects are skipped
+// instead of failing the whole read. The number of skipped lines is
+// returned along with the dataframe, so callers can tell when there are
+// too many of them. It is always zero when `ignore_errors` is false.
+//
+// The `dtypes` pin the dtypes of some columns, including nested ones like
+// "list[f64]". See `cast_ndjson_columns`.
+#[cfg(feature = "ndjson")]
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_from_ndjson(
     filename: &str,
-    stop_after_n_rows: Option<usize>,
-    column_names: Option<Vec<String>>,
-    projection: Option<Vec<usize>>,
+    infer_schema_length: Option<usize>,
+    batch_size: usize,
+    column_order: Option<Vec<String>>,
+    ignore_errors: bool,
+    open_retries: usize,
+    dtypes: Vec<(&str, &str)>,
+) -> Result<(ExDataFrame, usize), ExplorerError> {
+    let mut file = open_file_with_retries(filename, open_retries)?;
+    let mut magic = vec![];
+    (&mut file).take(4).read_to_end(&mut magic)?;
+    file.rewind()?;
+
+    let file = match decompress_ndjson(&file, &magic)? {
+        Some(decompressed) => decompressed,
+        None => file,
+    };
+
+    if ignore_errors {
+        let (valid_lines, skipped) = filter_valid_ndjson_lines(BufReader::new(file))?;
+
+        let df = read_ndjson(
+            Cursor::new(valid_lines),
+            infer_schema_length,
+            batch_size,
+            column_order,
+        )?;
+
+        Ok((cast_ndjson_columns(df, dtypes)?, skipped))
+    } else {
+        let df = read_ndjson(
+            BufReader::new(file),
+            infer_schema_length,
+            batch_size,
+            column_order,
+        )?;
+
+        Ok((cast_ndjson_columns(df, dtypes)?, 0))
+    }
+}
+
+// The dtypes of the nested values are inferred from the values, so a list
+// of whole numbers is read as "list[i64]", even if it should be floats.
+// Struct dtypes are cast field by field, in the order of the declared
+// fields, which are renamed to the declared names.
+// The columns are cast strictly after reading, so a value that doesn't
+// fit the declared dtype is an error instead of becoming nil.
+#[cfg(feature = "ndjson")]
+fn cast_ndjson_columns(
+    df: ExDataFrame,
+    dtypes: Vec<(&str, &str)>,
 ) -> Result<ExDataFrame, ExplorerError> {
-    let file = File::open(filename)?;
-    let buf_reader = BufReader::new(file);
+    if dtypes.is_empty() {
+        return Ok(df);
+    }
+
+    let mut df = df.clone_inner();
+
+    for (name, dtype_str) in dtypes {
+        let dtype = dtype_from_str(dtype_str)?;
+        let series = df.column(name).map_err(|_| {
+            ExplorerError::Other(format!(
+                "cannot cast column {name:?} to {dtype_str} because it does not exist"
+            ))
+        })?;
+
+        let cast = series.strict_cast(&dtype).map_err(|error| {
+            ExplorerError::Other(format!(
+                "cannot cast column {name:?} from {} to {dtype_str}: {error}",
+                series.dtype()
+            ))
+        })?;
+
+        df.with_column(cast)?;
+    }
+
+    Ok(ExDataFrame::new(df))
+}
+
+#[cfg(feature = "ndjson")]
+fn read_ndjson<R>(
+    reader: R,
+    infer_schema_length: Option<usize>,
+    batch_size: usize,
+    column_order: Option<Vec<String>>,
+) -> Result<ExDataFrame, ExplorerError>
+where
+    R: polars::io::mmap::MmapBytesReader,
+{
+    let reader = JsonReader::new(reader)
+        .with_json_format(JsonFormat::JsonLines)
+        .with_batch_size(batch_size)
+        .infer_schema_len(infer_schema_length);
+
+    match column_order {
+        None => finish_reader(reader),
+        Some(column_order) => {
+            let mut df = order_columns(reader.finish()?, &column_order)?;
+
+            Ok(ExDataFrame::new(normalize_numeric_dtypes(&mut df)?))
+        }
+    }
+}
+
+// Keeps only the lines that parse as JSON objects, and counts the others.
+// Blank lines are ignored without being counted, as the reader does.
+#[cfg(feature = "ndjson")]
+fn filter_valid_ndjson_lines(reader: impl BufRead) -> Result<(Vec<u8>, usize), ExplorerError> {
+    let mut valid_lines = vec![];
+    let mut skipped = 0;
+
+    for line in reader.split(b'\n') {
+        let line = line?;
+
+        if line.iter().all(u8::is_ascii_whitespace) {
+            continue;
+        }
+
+        match serde_json::from_slice::<serde_json::Value>(&line) {
+            Ok(serde_json::Value::Object(_)) => {
+                valid_lines.extend_from_slice(&line);
+                valid_lines.push(b'\n');
+            }
+            _ => skipped += 1,
+        }
+    }
+
+    Ok((valid_lines, skipped))
+}
+
+// The given columns come first, in the given order, and the remaining
+// ones follow sorted by name. The given columns that are missing from
+// the dataframe are added with nil values, as strings.
+#[cfg(feature = "ndjson")]
+fn order_columns(mut df: DataFrame, column_order: &[String]) -> Result<DataFrame, ExplorerError> {
+    let height = df.height();
+
+    for name in column_order {
+        if !df.get_column_names().contains(&name.as_str()) {
+            df.with_column(Series::full_null(name, height, &DataType::Utf8))?;
+        }
+    }
 
-    let reader = ParquetReader::new(buf_reader)
-        .with_n_rows(stop_after_n_rows)
-        .with_columns(column_names)
-        .with_projection(projection);
+    let mut remaining: Vec<String> = df
+        .get_column_names()
+        .into_iter()
+        .filter(|name| !column_order.iter().any(|ordered| ordered == name))
+        .map(|name| name.to_string())
+        .collect();
 
-    finish_reader(reader)
+    remaining.sort();
+
+    let names: Vec<String> = column_order.iter().cloned().chain(remaining).collect();
+
+    Ok(df.select(names)?)
 }
 
+#[cfg(feature = "ndjson")]
 #[rustler::nif(schedule = "DirtyIo")]
-pub fn df_to_parquet(
+pub fn df_to_ndjson(
     data: ExDataFrame,
     filename: &str,
-    ex_compression: ExParquetCompression,
+    compression: Option<&str>,
 ) -> Result<(), ExplorerError> {
-    let file = File::create(filename)?;
-    let mut buf_writer = BufWriter::new(file);
+    let file = create_file(filename)?;
+    let buf_writer = BufWriter::new(file);
 
-    let compression = ParquetCompression::try_from(ex_compression)?;
+    write_ndjson(buf_writer, &data, compression)
+}
 
-    ParquetWriter::new(&mut buf_writer)
-        .with_compression(compression)
-        .finish(&mut data.clone())?;
-    Ok(())
+// NDJSON files can be compressed as a whole with "gzip" or "zstd".
+// The reader detects compressed files by their magic bytes, so they
+// are read back without any option. See `decompress_ndjson`.
+#[cfg(feature = "ndjson")]
+enum NdjsonCompression {
+    Gzip,
+    Zstd,
 }
 
-#[cfg(feature = "aws")]
-#[rustler::nif(schedule = "DirtyIo")]
-pub fn df_to_parquet_cloud(
-    data: ExDataFrame,
-    ex_entry: ExS3Entry,
-    ex_compression: ExParquetCompression,
+#[cfg(feature = "ndjson")]
+fn decode_ndjson_compression(compression: &str) -> Result<NdjsonCompression, ExplorerError> {
+    match compression {
+        "gzip" => Ok(NdjsonCompression::Gzip),
+        "zstd" => Ok(NdjsonCompression::Zstd),
+        other => Err(ExplorerError::Other(format!(
+            "the algorithm {other} is not supported for NDJSON compression"
+        ))),
+    }
+}
+
+#[cfg(feature = "ndjson")]
+fn write_ndjson(
+    writer: impl Write,
+    data: &DataFrame,
+    compression: Option<&str>,
 ) -> Result<(), ExplorerError> {
-    let mut cloud_writer = build_aws_s3_cloud_writer(ex_entry)?;
+    let compression = match compression {
+        Some(algo) => Some(decode_ndjson_compression(algo)?),
+        None => None,
+    };
 
-    let compression = ParquetCompression::try_from(ex_compression)?;
+    match compression {
+        None => finish_ndjson_writer(writer, data),
+        Some(NdjsonCompression::Gzip) => {
+            let mut encoder = flate2::write::GzEncoder::new(writer, flate2::Compression::default());
+            finish_ndjson_writer(&mut encoder, data)?;
+            encoder.finish()?;
+            Ok(())
+        }
+        Some(NdjsonCompression::Zstd) => {
+            let mut encoder = zstd::Encoder::new(writer, 0)?;
+            finish_ndjson_writer(&mut encoder, data)?;
+            encoder.finish()?;
+            Ok(())
+        }
+    }
+}
 
-    ParquetWriter::new(&mut cloud_writer)
-        .with_compression(compression)
+#[cfg(feature = "ndjson")]
+fn finish_ndjson_writer(mut writer: impl Write, data: &DataFrame) -> Result<(), ExplorerError> {
+    JsonWriter::new(&mut writer)
+        .with_json_format(JsonFormat::JsonLines)
         .finish(&mut data.clone())?;
     Ok(())
 }
-fn object_store_to_explorer_error(error: impl std::fmt::Debug) -> ExplorerError {
-    ExplorerError::Other(format!("Internal ObjectStore error: #{error:?}"))
-}
 
-#[cfg(feature = "aws")]
-fn build_aws_s3_cloud_writer(
-    ex_entry: ExS3Entry,
-) -> Result<crate::cloud_writer::CloudWriter, ExplorerError> {
-    let config = ex_entry.config;
-    let mut aws_builder = object_store::aws::AmazonS3Builder::new()
-        .with_region(config.region)
-        .with_bucket_name(ex_entry.bucket)
-        .with_access_key_id(config.access_key_id)
-        .with_secret_access_key(config.secret_access_key);
+// When the `magic` bytes at the start of the contents are those of gzip
+// or zstd, streams the decompressed contents into an anonymous temporary
+// file, so we don't need to hold them in memory. Returns `None` when the
+// contents are not compressed.
+#[cfg(feature = "ndjson")]
+fn decompress_ndjson<'a>(
+    compressed: impl Read + 'a,
+    magic: &[u8],
+) -> Result<Option<File>, ExplorerError> {
+    let mut decoder: Box<dyn Read + 'a> = if magic.starts_with(&[0x1f, 0x8b]) {
+        Box::new(flate2::read::MultiGzDecoder::new(BufReader::new(
+            compressed,
+        )))
+    } else if magic.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+        Box::new(zstd::Decoder::new(compressed)?)
+    } else {
+        return Ok(None);
+    };
 
-    if let Some(endpoint) = config.endpoint {
-        aws_builder = aws_builder.with_allow_http(true).with_endpoint(endpoint);
-    }
+    let mut decompressed = tempfile::tempfile()?;
+    std::io::copy(&mut decoder, &mut decompressed)?;
+    decompressed.rewind()?;
+    Ok(Some(decompressed))
+}
 
-    if let Some(token) = config.token {
-        aws_builder = aws_builder.with_token(token);
+#[cfg(all(feature = "ndjson", feature = "aws"))]
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_to_ndjson_cloud(
+    data: ExDataFrame,
+    ex_entry: ExS3Entry,
+    skip_if_empty: bool,
+    progress_pid: Option<LocalPid>,
+) -> Result<Atom, ExplorerError> {
+    if skip_if_empty && data.height() == 0 {
+        return Ok(atoms::skipped());
     }
 
-    let aws_s3 = aws_builder
-        .build()
-        .map_err(object_store_to_explorer_error)?;
+    let mut cloud_writer = build_aws_s3_cloud_writer(ex_entry, progress_pid)?;
 
-    let object_store: Box<dyn object_store::ObjectStore> = Box::new(aws_s3);
-    Ok(crate::cloud_writer::CloudWriter::new(
-        object_store,
-        ex_entry.key.into(),
-    ))
+    JsonWriter::new(&mut cloud_writer)
+        .with_json_format(JsonFormat::JsonLines)
+        .finish(&mut data.clone())?;
+    Ok(atoms::written())
 }
 
+#[cfg(feature = "ndjson")]
 #[rustler::nif(schedule = "DirtyCpu")]
-pub fn df_dump_parquet(
+pub fn df_dump_ndjson(
     env: Env,
     data: ExDataFrame,
-    ex_compression: ExParquetCompression,
+    compression: Option<&str>,
 ) -> Result<Binary, ExplorerError> {
     let mut buf = vec![];
 
-    let compression = ParquetCompression::try_from(ex_compression)?;
-
-    ParquetWriter::new(&mut buf)
-        .with_compression(compression)
-        .finish(&mut data.clone())?;
+    write_ndjson(&mut buf, &data, compression)?;
 
     let mut values_binary = NewBinary::new(env, buf.len());
     values_binary.copy_from_slice(&buf);
@@ -306,293 +5908,938 @@ pub fn df_dump_parquet(
     Ok(values_binary.into())
 }
 
+#[cfg(feature = "ndjson")]
 #[rustler::nif(schedule = "DirtyCpu")]
-pub fn df_load_parquet(binary: Binary) -> Result<ExDataFrame, ExplorerError> {
-    let cursor = Cursor::new(binary.as_slice());
-    let reader = ParquetReader::new(cursor);
-
-    finish_reader(reader)
+pub fn df_load_ndjson(
+    binary: Binary,
+    infer_schema_length: Option<usize>,
+    batch_size: usize,
+) -> Result<ExDataFrame, ExplorerError> {
+    match decompress_ndjson(binary.as_slice(), binary.as_slice())? {
+        Some(decompressed) => read_ndjson(decompressed, infer_schema_length, batch_size, None),
+        None => read_ndjson(
+            Cursor::new(binary.as_slice()),
+            infer_schema_length,
+            batch_size,
+            None,
+        ),
+    }
 }
 
-// ============ IPC ============ //
+// ============ NDJSON Batched ============ //
+
+// The most lines the schema is inferred from when no length is given.
+#[cfg(feature = "ndjson")]
+const NDJSON_MAX_SCHEMA_LINES: usize = 100_000;
 
+// The schema is inferred from the first `infer_schema_length` lines, or
+// from the first `NDJSON_MAX_SCHEMA_LINES` when it is `None`, and reused
+// for every batch. See `infer_ndjson_schema`.
+#[cfg(feature = "ndjson")]
 #[rustler::nif(schedule = "DirtyIo")]
-pub fn df_from_ipc(
+pub fn df_ndjson_batched_open(
     filename: &str,
-    columns: Option<Vec<String>>,
-    projection: Option<Vec<usize>>,
-) -> Result<ExDataFrame, ExplorerError> {
-    let file = File::open(filename)?;
-    let buf_reader = BufReader::new(file);
-    let reader = IpcReader::new(buf_reader)
-        .with_columns(columns)
-        .with_projection(projection);
+    infer_schema_length: Option<usize>,
+    batch_size: usize,
+) -> Result<ResourceArc<ExNdjsonBatchedReaderRef>, ExplorerError> {
+    let mut reader = BufReader::new(open_file(filename)?);
+    let max_lines = infer_schema_length.unwrap_or(NDJSON_MAX_SCHEMA_LINES);
+    let schema = infer_ndjson_schema(&mut reader, max_lines)?;
+    reader.rewind()?;
 
-    finish_reader(reader)
+    Ok(ResourceArc::new(ExNdjsonBatchedReaderRef::new(
+        crate::datatypes::ExNdjsonBatchedReader {
+            reader,
+            schema: Arc::new(schema),
+            batch_size,
+        },
+    )))
 }
 
-#[rustler::nif(schedule = "DirtyIo")]
-pub fn df_to_ipc(
-    data: ExDataFrame,
-    filename: &str,
-    compression: Option<&str>,
-) -> Result<(), ExplorerError> {
-    let compression = match compression {
-        Some(algo) => Some(decode_ipc_compression(algo)?),
-        None => None,
-    };
+// Infers the schema one line at a time, so only a single line is held in
+// memory and no dataframe is built. The dtypes of a column are unified
+// across lines: integers and floats become floats, nil takes the dtype of
+// the other values, and any other mix becomes strings. Blank lines are
+// not counted.
+#[cfg(feature = "ndjson")]
+fn infer_ndjson_schema(
+    reader: &mut impl BufRead,
+    max_lines: usize,
+) -> Result<Schema, ExplorerError> {
+    let mut schema = Schema::new();
+    let mut line = vec![];
+    let mut count = 0;
 
-    let file = File::create(filename)?;
-    let mut buf_writer = BufWriter::new(file);
-    IpcWriter::new(&mut buf_writer)
-        .with_compression(compression)
-        .finish(&mut data.clone())?;
-    Ok(())
+    while count < max_lines {
+        line.clear();
+
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            break;
+        }
+
+        if line.iter().all(u8::is_ascii_whitespace) {
+            continue;
+        }
+
+        count += 1;
+
+        let fields = match serde_json::from_slice::<serde_json::Value>(&line) {
+            Ok(serde_json::Value::Object(fields)) => fields,
+            _ => {
+                return Err(ExplorerError::Other(format!(
+                    "cannot infer the NDJSON schema, the line {count} is not a JSON object"
+                )))
+            }
+        };
+
+        for (name, value) in &fields {
+            let dtype = json_value_dtype(value);
+            let dtype = match schema.get(name) {
+                Some(current) => unify_json_dtypes(current, &dtype),
+                None => dtype,
+            };
+
+            schema.with_column(name.as_str().into(), dtype);
+        }
+    }
+
+    Ok(schema)
 }
 
-#[cfg(feature = "aws")]
+#[cfg(feature = "ndjson")]
+fn json_value_dtype(value: &serde_json::Value) -> DataType {
+    match value {
+        serde_json::Value::Null => DataType::Null,
+        serde_json::Value::Bool(_) => DataType::Boolean,
+        serde_json::Value::Number(number) if number.is_i64() => DataType::Int64,
+        serde_json::Value::Number(_) => DataType::Float64,
+        serde_json::Value::String(_) => DataType::Utf8,
+        serde_json::Value::Array(values) => {
+            let inner = values
+                .iter()
+                .map(json_value_dtype)
+                .reduce(|left, right| unify_json_dtypes(&left, &right))
+                .unwrap_or(DataType::Null);
+
+            DataType::List(Box::new(inner))
+        }
+        serde_json::Value::Object(fields) => DataType::Struct(
+            fields
+                .iter()
+                .map(|(name, value)| Field::new(name, json_value_dtype(value)))
+                .collect(),
+        ),
+    }
+}
+
+#[cfg(feature = "ndjson")]
+fn unify_json_dtypes(left: &DataType, right: &DataType) -> DataType {
+    match (left, right) {
+        (left, right) if left == right => left.clone(),
+        (DataType::Null, other) | (other, DataType::Null) => other.clone(),
+        (DataType::Int64, DataType::Float64) | (DataType::Float64, DataType::Int64) => {
+            DataType::Float64
+        }
+        (DataType::List(left), DataType::List(right)) => {
+            DataType::List(Box::new(unify_json_dtypes(left, right)))
+        }
+        (DataType::Struct(left), DataType::Struct(right)) => {
+            let mut fields = left.clone();
+
+            for field in right {
+                match fields
+                    .iter_mut()
+                    .find(|current| current.name() == field.name())
+                {
+                    Some(current) => {
+                        current.coerce(unify_json_dtypes(current.data_type(), field.data_type()))
+                    }
+                    None => fields.push(field.clone()),
+                }
+            }
+
+            DataType::Struct(fields)
+        }
+        _ => DataType::Utf8,
+    }
+}
+
+// Returns the next batch, or `None` once the reader is exhausted.
+#[cfg(feature = "ndjson")]
 #[rustler::nif(schedule = "DirtyIo")]
-pub fn df_to_ipc_cloud(
-    data: ExDataFrame,
-    ex_entry: ExS3Entry,
-    compression: Option<&str>,
-) -> Result<(), ExplorerError> {
-    let compression = match compression {
-        Some(algo) => Some(decode_ipc_compression(algo)?),
-        None => None,
-    };
+pub fn df_ndjson_batched_next(
+    reader: ResourceArc<ExNdjsonBatchedReaderRef>,
+    cancel_token: Option<ResourceArc<ExCancelTokenRef>>,
+) -> Result<Option<ExDataFrame>, ExplorerError> {
+    check_cancelled(&cancel_token)?;
 
-    let mut cloud_writer = build_aws_s3_cloud_writer(ex_entry)?;
+    let mut guard = reader.0.lock().map_err(|_| {
+        ExplorerError::Internal("the NDJSON batched reader lock is poisoned".into())
+    })?;
+    let batched = &mut *guard;
 
-    IpcWriter::new(&mut cloud_writer)
-        .with_compression(compression)
-        .finish(&mut data.clone())?;
-    Ok(())
+    let (lines, count) = read_ndjson_lines(&mut batched.reader, batched.batch_size)?;
+
+    if count == 0 {
+        return Ok(None);
+    }
+
+    check_cancelled(&cancel_token)?;
+
+    let mut df = JsonReader::new(Cursor::new(lines))
+        .with_json_format(JsonFormat::JsonLines)
+        .with_schema(batched.schema.clone())
+        .finish()?;
+
+    Ok(Some(ExDataFrame::new(normalize_numeric_dtypes(&mut df)?)))
 }
 
-#[rustler::nif(schedule = "DirtyCpu")]
-pub fn df_dump_ipc<'a>(
-    env: Env<'a>,
-    data: ExDataFrame,
-    compression: Option<&str>,
-) -> Result<Binary<'a>, ExplorerError> {
-    let mut buf = vec![];
+// Reads up to `max_lines` lines that are not blank. Returns the lines
+// read, along with how many there are.
+#[cfg(feature = "ndjson")]
+fn read_ndjson_lines(
+    reader: &mut impl BufRead,
+    max_lines: usize,
+) -> Result<(Vec<u8>, usize), ExplorerError> {
+    let mut lines = vec![];
+    let mut count = 0;
 
-    let compression = match compression {
-        Some(algo) => Some(decode_ipc_compression(algo)?),
-        None => None,
-    };
+    while count < max_lines {
+        let start = lines.len();
 
-    IpcWriter::new(&mut buf)
-        .with_compression(compression)
-        .finish(&mut data.clone())?;
+        if reader.read_until(b'\n', &mut lines)? == 0 {
+            break;
+        }
 
-    let mut values_binary = NewBinary::new(env, buf.len());
-    values_binary.copy_from_slice(&buf);
+        if lines[start..].iter().all(u8::is_ascii_whitespace) {
+            lines.truncate(start);
+        } else {
+            count += 1;
+        }
+    }
 
-    Ok(values_binary.into())
+    Ok((lines, count))
+}
+
+// ============ ORC ============ //
+
+#[cfg(feature = "orc")]
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_from_orc(
+    filename: &str,
+    columns: Option<Vec<String>>,
+    projection: Option<Vec<usize>>,
+) -> Result<ExDataFrame, ExplorerError> {
+    let file = open_file(filename)?;
+    let mut buf_reader = BufReader::new(file);
+
+    read_orc(&mut buf_reader, columns, projection)
 }
 
+#[cfg(feature = "orc")]
 #[rustler::nif(schedule = "DirtyCpu")]
-pub fn df_load_ipc(
+pub fn df_load_orc(
     binary: Binary,
     columns: Option<Vec<String>>,
     projection: Option<Vec<usize>>,
 ) -> Result<ExDataFrame, ExplorerError> {
-    let cursor = Cursor::new(binary.as_slice());
-    let reader = IpcReader::new(cursor)
-        .with_columns(columns)
-        .with_projection(projection);
+    let mut cursor = Cursor::new(binary.as_slice());
 
-    finish_reader(reader)
+    read_orc(&mut cursor, columns, projection)
 }
 
-fn decode_ipc_compression(compression: &str) -> Result<IpcCompression, ExplorerError> {
-    match compression {
-        "lz4" => Ok(IpcCompression::LZ4),
-        "zstd" => Ok(IpcCompression::ZSTD),
-        other => Err(ExplorerError::Other(format!(
-            "the algorithm {other} is not supported for IPC compression"
-        ))),
+// Reads every stripe of the selected columns, and then concatenates
+// the chunks of each column into a series.
+#[cfg(feature = "orc")]
+fn read_orc<R: std::io::Read + Seek>(
+    reader: &mut R,
+    columns: Option<Vec<String>>,
+    projection: Option<Vec<usize>>,
+) -> Result<ExDataFrame, ExplorerError> {
+    use polars::export::arrow::io::orc::{format, read};
+
+    let metadata = format::read::read_metadata(reader).map_err(orc_to_explorer_error)?;
+    let schema = read::infer_schema(&metadata.footer).map_err(orc_to_explorer_error)?;
+
+    let indices: Vec<usize> = match (columns, projection) {
+        (Some(columns), _) => columns
+            .iter()
+            .map(|name| {
+                schema
+                    .fields
+                    .iter()
+                    .position(|field| &field.name == name)
+                    .ok_or_else(|| {
+                        ExplorerError::Other(format!("column {name} not found in the ORC file"))
+                    })
+            })
+            .collect::<Result<_, _>>()?,
+        (None, Some(projection)) => projection,
+        (None, None) => (0..schema.fields.len()).collect(),
+    };
+
+    let mut series = Vec::with_capacity(indices.len());
+
+    for index in indices {
+        let field = schema.fields.get(index).ok_or_else(|| {
+            ExplorerError::Other(format!(
+                "column index {index} out of bounds in the ORC file"
+            ))
+        })?;
+
+        let mut chunks = Vec::with_capacity(metadata.stripes.len());
+
+        for stripe in 0..metadata.stripes.len() {
+            // The column with id 0 is the root struct, so the fields start at 1.
+            let column = format::read::read_stripe_column(
+                reader,
+                &metadata,
+                stripe,
+                (index + 1) as u32,
+                vec![],
+            )
+            .map_err(orc_to_explorer_error)?;
+
+            let chunk = read::deserialize(field.data_type.clone(), &column)
+                .map_err(orc_to_explorer_error)?;
+
+            chunks.push(chunk);
+        }
+
+        series.push(Series::try_from((field.name.as_str(), chunks))?);
     }
+
+    let mut df = DataFrame::new(series)?;
+
+    Ok(ExDataFrame::new(normalize_numeric_dtypes(&mut df)?))
 }
 
-// ============ IPC Streaming ============ //
+#[cfg(feature = "orc")]
+fn orc_to_explorer_error(error: impl std::fmt::Debug) -> ExplorerError {
+    ExplorerError::Other(format!("Internal ORC error: #{error:?}"))
+}
 
+// ============ Excel ============ //
+
+#[cfg(feature = "excel")]
 #[rustler::nif(schedule = "DirtyIo")]
-pub fn df_from_ipc_stream(
+pub fn df_from_xlsx(
     filename: &str,
-    columns: Option<Vec<String>>,
-    projection: Option<Vec<usize>>,
+    sheet: ExXlsxSheet,
+    has_header: bool,
+    skip_rows: usize,
 ) -> Result<ExDataFrame, ExplorerError> {
-    let file = File::open(filename)?;
-    let buf_reader = BufReader::new(file);
-    let reader = IpcStreamReader::new(buf_reader)
-        .with_columns(columns)
-        .with_projection(projection);
+    use calamine::{open_workbook, Reader, Xlsx};
 
-    finish_reader(reader)
+    let mut workbook: Xlsx<_> = open_workbook(filename).map_err(excel_to_explorer_error)?;
+
+    let range = match &sheet {
+        ExXlsxSheet::Index(index) => workbook.worksheet_range_at(*index),
+        ExXlsxSheet::Name(name) => workbook.worksheet_range(name),
+    };
+
+    let range = match range {
+        Some(range) => range.map_err(excel_to_explorer_error)?,
+        None => {
+            let sheet = match sheet {
+                ExXlsxSheet::Index(index) => format!("at index {index}"),
+                ExXlsxSheet::Name(name) => format!("named {name}"),
+            };
+
+            return Err(ExplorerError::Other(format!(
+                "could not find the sheet {sheet} in {filename}. The available sheets are: {:?}",
+                workbook.sheet_names()
+            )));
+        }
+    };
+
+    Ok(ExDataFrame::new(xlsx_range_to_df(
+        &range, has_header, skip_rows,
+    )?))
 }
 
+// Reads every sheet of the workbook, keyed by the sheet name.
+// Empty sheets are read as empty dataframes instead of being skipped.
+#[cfg(feature = "excel")]
 #[rustler::nif(schedule = "DirtyIo")]
-pub fn df_to_ipc_stream(
-    data: ExDataFrame,
+pub fn df_from_xlsx_all(
     filename: &str,
-    compression: Option<&str>,
-) -> Result<(), ExplorerError> {
-    let compression = match compression {
-        Some(algo) => Some(decode_ipc_stream_compression(algo)?),
-        None => None,
+    has_header: bool,
+) -> Result<std::collections::HashMap<String, ExDataFrame>, ExplorerError> {
+    use calamine::{open_workbook, Reader, Xlsx};
+
+    let mut workbook: Xlsx<_> = open_workbook(filename).map_err(excel_to_explorer_error)?;
+
+    workbook
+        .worksheets()
+        .into_iter()
+        .map(|(name, range)| {
+            let df = xlsx_range_to_df(&range, has_header, 0)?;
+            Ok((name, ExDataFrame::new(df)))
+        })
+        .collect()
+}
+
+#[cfg(feature = "excel")]
+fn xlsx_range_to_df(
+    range: &calamine::Range<calamine::DataType>,
+    has_header: bool,
+    skip_rows: usize,
+) -> Result<DataFrame, ExplorerError> {
+    let mut rows = range.rows().skip(skip_rows);
+
+    let names: Vec<String> = if has_header {
+        match rows.next() {
+            Some(header) => header.iter().map(|cell| cell.to_string()).collect(),
+            None => vec![],
+        }
+    } else {
+        (1..=range.width())
+            .map(|index| format!("column_{index}"))
+            .collect()
+    };
+
+    let rows: Vec<&[calamine::DataType]> = rows.collect();
+
+    let series = names
+        .iter()
+        .enumerate()
+        .map(|(index, name)| {
+            let cells: Vec<&calamine::DataType> = rows
+                .iter()
+                .map(|row| row.get(index).unwrap_or(&calamine::DataType::Empty))
+                .collect();
+
+            xlsx_cells_to_series(name, &cells)
+        })
+        .collect::<Result<Vec<Series>, ExplorerError>>()?;
+
+    Ok(DataFrame::new(series)?)
+}
+
+// Infers the dtype of the column in the same spirit of the CSV reader:
+// integers, floats, booleans and datetimes are kept when all the cells
+// agree, otherwise everything is read as strings. Empty cells are nils.
+#[cfg(feature = "excel")]
+fn xlsx_cells_to_series(
+    name: &str,
+    cells: &[&calamine::DataType],
+) -> Result<Series, ExplorerError> {
+    use calamine::DataType as Cell;
+
+    let non_empty = || cells.iter().filter(|cell| !cell.is_empty());
+
+    let series = if non_empty().all(|cell| matches!(cell, Cell::Int(_))) {
+        let values: Vec<Option<i64>> = cells.iter().map(|cell| cell.get_int()).collect();
+        Series::new(name, values)
+    } else if non_empty().all(|cell| matches!(cell, Cell::Int(_) | Cell::Float(_))) {
+        let values: Vec<Option<f64>> = cells
+            .iter()
+            .map(|cell| {
+                cell.get_float()
+                    .or_else(|| cell.get_int().map(|int| int as f64))
+            })
+            .collect();
+        Series::new(name, values)
+    } else if non_empty().all(|cell| matches!(cell, Cell::Bool(_))) {
+        let values: Vec<Option<bool>> = cells.iter().map(|cell| cell.get_bool()).collect();
+        Series::new(name, values)
+    } else if non_empty().all(|cell| matches!(cell, Cell::DateTime(_) | Cell::DateTimeIso(_))) {
+        let values: Vec<Option<i64>> = cells
+            .iter()
+            .map(|cell| {
+                cell.as_datetime()
+                    .map(|datetime| datetime.timestamp_millis())
+            })
+            .collect();
+        Series::new(name, values).cast(&DataType::Datetime(TimeUnit::Milliseconds, None))?
+    } else {
+        let values: Vec<Option<String>> = cells
+            .iter()
+            .map(|cell| match cell {
+                Cell::Empty => None,
+                other => Some(other.to_string()),
+            })
+            .collect();
+        Series::new(name, values)
     };
 
-    let mut file = File::create(filename)?;
-    IpcStreamWriter::new(&mut file)
-        .with_compression(compression)
-        .finish(&mut data.clone())?;
-    Ok(())
+    Ok(series)
 }
 
-#[cfg(feature = "aws")]
+// Writes a single sheet, with the column names as the header row.
+// Dates and datetimes are written as real Excel dates.
+#[cfg(feature = "excel")]
 #[rustler::nif(schedule = "DirtyIo")]
-pub fn df_to_ipc_stream_cloud(
+pub fn df_to_xlsx(
     data: ExDataFrame,
-    ex_entry: ExS3Entry,
-    compression: Option<&str>,
+    filename: &str,
+    sheet_name: Option<String>,
 ) -> Result<(), ExplorerError> {
-    let compression = match compression {
-        Some(algo) => Some(decode_ipc_stream_compression(algo)?),
-        None => None,
-    };
+    use rust_xlsxwriter::{Format, Workbook};
 
-    let mut cloud_writer = build_aws_s3_cloud_writer(ex_entry)?;
+    let mut workbook = Workbook::new();
+    let worksheet = workbook.add_worksheet();
+    worksheet
+        .set_name(sheet_name.as_deref().unwrap_or("Sheet1"))
+        .map_err(excel_to_explorer_error)?;
 
-    IpcStreamWriter::new(&mut cloud_writer)
-        .with_compression(compression)
-        .finish(&mut data.clone())?;
-    Ok(())
-}
+    let date_format = Format::new().set_num_format("yyyy-mm-dd");
+    let datetime_format = Format::new().set_num_format("yyyy-mm-dd hh:mm:ss");
 
-#[rustler::nif(schedule = "DirtyCpu")]
-pub fn df_dump_ipc_stream<'a>(
-    env: Env<'a>,
-    data: ExDataFrame,
-    compression: Option<&str>,
-) -> Result<Binary<'a>, ExplorerError> {
-    let mut buf = vec![];
+    for (col_idx, series) in data.get_columns().iter().enumerate() {
+        let col = u16::try_from(col_idx)?;
 
-    let compression = match compression {
-        Some(algo) => Some(decode_ipc_stream_compression(algo)?),
-        None => None,
-    };
+        worksheet
+            .write_string(0, col, series.name())
+            .map_err(excel_to_explorer_error)?;
 
-    IpcStreamWriter::new(&mut buf)
-        .with_compression(compression)
-        .finish(&mut data.clone())?;
+        match series.dtype() {
+            DataType::Int64 => {
+                for (row_idx, value) in series.i64()?.into_iter().enumerate() {
+                    if let Some(value) = value {
+                        worksheet
+                            .write_number(u32::try_from(row_idx + 1)?, col, value as f64)
+                            .map_err(excel_to_explorer_error)?;
+                    }
+                }
+            }
+            DataType::Float64 => {
+                for (row_idx, value) in series.f64()?.into_iter().enumerate() {
+                    if let Some(value) = value {
+                        worksheet
+                            .write_number(u32::try_from(row_idx + 1)?, col, value)
+                            .map_err(excel_to_explorer_error)?;
+                    }
+                }
+            }
+            DataType::Boolean => {
+                for (row_idx, value) in series.bool()?.into_iter().enumerate() {
+                    if let Some(value) = value {
+                        worksheet
+                            .write_boolean(u32::try_from(row_idx + 1)?, col, value)
+                            .map_err(excel_to_explorer_error)?;
+                    }
+                }
+            }
+            DataType::Date => {
+                for (row_idx, value) in series.date()?.as_date_iter().enumerate() {
+                    if let Some(value) = value {
+                        worksheet
+                            .write_date(u32::try_from(row_idx + 1)?, col, &value, &date_format)
+                            .map_err(excel_to_explorer_error)?;
+                    }
+                }
+            }
+            DataType::Datetime(_, _) => {
+                for (row_idx, value) in series.datetime()?.as_datetime_iter().enumerate() {
+                    if let Some(value) = value {
+                        worksheet
+                            .write_datetime(
+                                u32::try_from(row_idx + 1)?,
+                                col,
+                                &value,
+                                &datetime_format,
+                            )
+                            .map_err(excel_to_explorer_error)?;
+                    }
+                }
+            }
+            _ => {
+                let strings = series.cast(&DataType::Utf8)?;
 
-    let mut values_binary = NewBinary::new(env, buf.len());
-    values_binary.copy_from_slice(&buf);
+                for (row_idx, value) in strings.utf8()?.into_iter().enumerate() {
+                    if let Some(value) = value {
+                        worksheet
+                            .write_string(u32::try_from(row_idx + 1)?, col, value)
+                            .map_err(excel_to_explorer_error)?;
+                    }
+                }
+            }
+        }
+    }
 
-    Ok(values_binary.into())
+    workbook.save(filename).map_err(excel_to_explorer_error)?;
+
+    Ok(())
 }
 
-#[rustler::nif(schedule = "DirtyCpu")]
-pub fn df_load_ipc_stream(
-    binary: Binary,
+#[cfg(feature = "excel")]
+fn excel_to_explorer_error(error: impl std::fmt::Debug) -> ExplorerError {
+    ExplorerError::Other(format!("Internal Excel error: #{error:?}"))
+}
+
+// ============ Delta Lake ============ //
+
+// Reads the active Parquet files of the current snapshot (or the given version)
+// of a Delta table. The partition values are added as string columns.
+// The `config` is only necessary for "s3://" tables.
+#[cfg(feature = "delta")]
+#[rustler::nif(schedule = "DirtyIo")]
+pub fn df_from_delta(
+    table_uri: &str,
+    version: Option<i64>,
     columns: Option<Vec<String>>,
-    projection: Option<Vec<usize>>,
+    config: Option<ExS3Config>,
 ) -> Result<ExDataFrame, ExplorerError> {
-    let cursor = Cursor::new(binary.as_slice());
-    let reader = IpcStreamReader::new(cursor)
-        .with_columns(columns)
-        .with_projection(projection);
+    let storage_options = match &config {
+        Some(config) => delta_storage_options(config),
+        None => std::collections::HashMap::new(),
+    };
 
-    finish_reader(reader)
+    let mut builder =
+        deltalake::DeltaTableBuilder::from_uri(table_uri).with_storage_options(storage_options);
+
+    if let Some(version) = version {
+        builder = builder.with_version(version);
+    }
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .enable_io()
+        .build()?;
+
+    let table = runtime
+        .block_on(builder.load())
+        .map_err(delta_to_explorer_error)?;
+
+    let partition_columns = table
+        .get_metadata()
+        .map_err(delta_to_explorer_error)?
+        .partition_columns
+        .clone();
+
+    let table_root = table_uri.trim_end_matches('/');
+
+    let lazy_frames = table
+        .get_state()
+        .files()
+        .iter()
+        .map(|add| {
+            let uri = format!("{table_root}/{}", add.path);
+            let path = uri.strip_prefix("file://").unwrap_or(&uri).to_string();
+
+            let options = ScanArgsParquet {
+                cloud_options: config.as_ref().map(|config| config.to_cloud_options()),
+                ..Default::default()
+            };
+
+            let partition_exprs: Vec<Expr> = partition_columns
+                .iter()
+                .map(|name| {
+                    let value = match add.partition_values.get(name).cloned().flatten() {
+                        Some(value) => lit(value),
+                        None => lit(NULL),
+                    };
+
+                    value.cast(DataType::Utf8).alias(name)
+                })
+                .collect();
+
+            Ok(LazyFrame::scan_parquet(path, options)?.with_columns(partition_exprs))
+        })
+        .collect::<Result<Vec<LazyFrame>, ExplorerError>>()?;
+
+    if lazy_frames.is_empty() {
+        return Ok(ExDataFrame::new(DataFrame::default()));
+    }
+
+    let mut lf = concat(lazy_frames, true, true)?;
+
+    if let Some(columns) = columns {
+        lf = lf.select(columns.iter().map(|name| col(name)).collect::<Vec<_>>());
+    }
+
+    let mut df = lf.collect()?;
+
+    Ok(ExDataFrame::new(normalize_numeric_dtypes(&mut df)?))
 }
 
-fn decode_ipc_stream_compression(compression: &str) -> Result<IpcStreamCompression, ExplorerError> {
-    match compression {
-        "lz4" => Ok(IpcStreamCompression::LZ4),
-        "zstd" => Ok(IpcStreamCompression::ZSTD),
-        other => Err(ExplorerError::Other(format!(
-            "the algorithm {other} is not supported for IPC stream compression"
-        ))),
+#[cfg(feature = "delta")]
+fn delta_storage_options(config: &ExS3Config) -> std::collections::HashMap<String, String> {
+    let mut options = std::collections::HashMap::from([
+        (
+            "AWS_ACCESS_KEY_ID".to_string(),
+            config.access_key_id.clone(),
+        ),
+        (
+            "AWS_SECRET_ACCESS_KEY".to_string(),
+            config.secret_access_key.clone(),
+        ),
+        ("AWS_REGION".to_string(), config.region.clone()),
+    ]);
+
+    if let Some(endpoint) = &config.endpoint {
+        options.insert("AWS_ENDPOINT_URL".to_string(), endpoint.clone());
+        options.insert("AWS_ALLOW_HTTP".to_string(), "true".to_string());
+    }
+
+    if let Some(token) = &config.token {
+        options.insert("AWS_SESSION_TOKEN".to_string(), token.clone());
     }
+
+    options
 }
 
-// ============ NDJSON ============ //
+#[cfg(feature = "delta")]
+fn delta_to_explorer_error(error: impl std::fmt::Debug) -> ExplorerError {
+    ExplorerError::Other(format!("Internal Delta Lake error: #{error:?}"))
+}
 
-#[cfg(feature = "ndjson")]
+// ============ Database ============ //
+
+// Runs the query with ConnectorX, which loads the result as Arrow data
+// directly. The `protocol` is one of the protocols supported by ConnectorX
+// for the given database, like "binary" or "cursor" for Postgres.
+//
+// The Polars destination of ConnectorX is tied to another Polars release,
+// so the result is read as arrow2 chunks, which share the arrow2 version
+// of Polars, and each chunk is stacked onto the data frame.
+#[cfg(feature = "database")]
 #[rustler::nif(schedule = "DirtyIo")]
-pub fn df_from_ndjson(
-    filename: &str,
-    infer_schema_length: Option<usize>,
-    batch_size: usize,
+pub fn df_from_sql(
+    connection_uri: &str,
+    query: &str,
+    protocol: Option<&str>,
 ) -> Result<ExDataFrame, ExplorerError> {
-    let file = File::open(filename)?;
-    let buf_reader = BufReader::new(file);
-    let reader = JsonReader::new(buf_reader)
-        .with_json_format(JsonFormat::JsonLines)
-        .with_batch_size(batch_size)
-        .infer_schema_len(infer_schema_length);
+    use connectorx::prelude::{get_arrow2, CXQuery, SourceConn};
 
-    finish_reader(reader)
+    let mut source_conn = SourceConn::try_from(connection_uri)
+        .map_err(|error| sql_to_explorer_error(query, error))?;
+
+    if let Some(protocol) = protocol {
+        source_conn.set_protocol(protocol);
+    }
+
+    let queries = [CXQuery::from(query)];
+
+    let destination = get_arrow2(&source_conn, None, &queries)
+        .map_err(|error| sql_to_explorer_error(query, error))?;
+
+    let schema = destination.arrow_schema();
+    let chunks = destination
+        .arrow()
+        .map_err(|error| sql_to_explorer_error(query, error))?;
+
+    let mut df = DataFrame::from(&Schema::from_iter(schema.fields.iter()));
+
+    for chunk in chunks {
+        df.vstack_mut(&DataFrame::try_from((chunk, schema.fields.as_slice()))?)?;
+    }
+
+    df.as_single_chunk_par();
+
+    Ok(ExDataFrame::new(normalize_numeric_dtypes(&mut df)?))
 }
 
-#[cfg(feature = "ndjson")]
-#[rustler::nif(schedule = "DirtyIo")]
-pub fn df_to_ndjson(data: ExDataFrame, filename: &str) -> Result<(), ExplorerError> {
-    let file = File::create(filename)?;
-    let mut buf_writer = BufWriter::new(file);
+// The query is truncated, since it may be huge and it ends up in logs.
+#[cfg(feature = "database")]
+fn sql_to_explorer_error(query: &str, error: impl std::fmt::Debug) -> ExplorerError {
+    const MAX_QUERY_CHARS: usize = 100;
 
-    JsonWriter::new(&mut buf_writer)
-        .with_json_format(JsonFormat::JsonLines)
-        .finish(&mut data.clone())?;
-    Ok(())
+    let truncated: String = query.chars().take(MAX_QUERY_CHARS).collect();
+    let ellipsis = if query.chars().count() > MAX_QUERY_CHARS {
+        "..."
+    } else {
+        ""
+    };
+
+    ExplorerError::Other(format!(
+        "Internal database error while running {truncated}{ellipsis}: #{error:?}"
+    ))
 }
 
-#[cfg(all(feature = "ndjson", feature = "aws"))]
+// ============ Arrow Flight ============ //
+
+// Issues a `DoGet` for the ticket and assembles the streamed record batches.
+// Arrow Flight uses the Arrow implementation of the `arrow` crates, so the
+// batches are written as an IPC stream, which is then read by Polars.
+// Endpoints with the "https" scheme use TLS. When the `token` is given,
+// it is sent as a bearer token in the "authorization" header.
+#[cfg(feature = "flight")]
 #[rustler::nif(schedule = "DirtyIo")]
-pub fn df_to_ndjson_cloud(data: ExDataFrame, ex_entry: ExS3Entry) -> Result<(), ExplorerError> {
-    let mut cloud_writer = build_aws_s3_cloud_writer(ex_entry)?;
+pub fn df_from_flight(
+    endpoint: &str,
+    ticket: Binary,
+    token: Option<String>,
+) -> Result<ExDataFrame, ExplorerError> {
+    use arrow_flight::{FlightClient, Ticket};
+    use futures::StreamExt;
 
-    JsonWriter::new(&mut cloud_writer)
-        .with_json_format(JsonFormat::JsonLines)
-        .finish(&mut data.clone())?;
-    Ok(())
+    let ticket = Ticket::new(ticket.as_slice().to_vec());
+
+    let mut channel = tonic::transport::Endpoint::from_shared(endpoint.to_string())
+        .map_err(flight_to_explorer_error)?;
+
+    if endpoint.starts_with("https://") {
+        channel = channel
+            .tls_config(tonic::transport::ClientTlsConfig::new())
+            .map_err(flight_to_explorer_error)?;
+    }
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+
+    let buf = runtime.block_on(async {
+        let channel = channel.connect().await.map_err(flight_to_explorer_error)?;
+        let mut client = FlightClient::new(channel);
+
+        if let Some(token) = token {
+            client
+                .add_header("authorization", &format!("Bearer {token}"))
+                .map_err(flight_to_explorer_error)?;
+        }
+
+        let mut stream = client
+            .do_get(ticket)
+            .await
+            .map_err(flight_to_explorer_error)?;
+
+        let mut buf = vec![];
+        let mut writer: Option<arrow_ipc::writer::StreamWriter<&mut Vec<u8>>> = None;
+
+        while let Some(batch) = stream.next().await {
+            let batch = batch.map_err(flight_to_explorer_error)?;
+
+            if writer.is_none() {
+                writer = Some(
+                    arrow_ipc::writer::StreamWriter::try_new(&mut buf, &batch.schema())
+                        .map_err(flight_to_explorer_error)?,
+                );
+            }
+
+            if let Some(writer) = writer.as_mut() {
+                writer.write(&batch).map_err(flight_to_explorer_error)?;
+            }
+        }
+
+        if let Some(mut writer) = writer {
+            writer.finish().map_err(flight_to_explorer_error)?;
+        }
+
+        Ok::<Vec<u8>, ExplorerError>(buf)
+    })?;
+
+    // The stream had no batches, so there is not even a schema.
+    if buf.is_empty() {
+        return Ok(ExDataFrame::new(DataFrame::default()));
+    }
+
+    finish_reader(IpcStreamReader::new(Cursor::new(buf)))
 }
 
-#[cfg(feature = "ndjson")]
-#[rustler::nif(schedule = "DirtyCpu")]
-pub fn df_dump_ndjson(env: Env, data: ExDataFrame) -> Result<Binary, ExplorerError> {
-    let mut buf = vec![];
+#[cfg(feature = "flight")]
+fn flight_to_explorer_error(error: impl std::fmt::Debug) -> ExplorerError {
+    ExplorerError::Other(format!("Internal Arrow Flight error: #{error:?}"))
+}
 
-    JsonWriter::new(&mut buf)
-        .with_json_format(JsonFormat::JsonLines)
-        .finish(&mut data.clone())?;
+// ============ For when the feature is not enabled ============ //
 
-    let mut values_binary = NewBinary::new(env, buf.len());
-    values_binary.copy_from_slice(&buf);
+#[cfg(not(feature = "flight"))]
+#[rustler::nif]
+pub fn df_from_flight(
+    _endpoint: &str,
+    _ticket: Binary,
+    _token: Option<String>,
+) -> Result<ExDataFrame, ExplorerError> {
+    Err(ExplorerError::Other(format!(
+        "Explorer was compiled without the \"flight\" feature enabled. \
+        Please read the section about precompilation in our README.md: https://github.com/elixir-explorer/explorer#precompilation"
+    )))
+}
 
-    Ok(values_binary.into())
+#[cfg(not(feature = "database"))]
+#[rustler::nif]
+pub fn df_from_sql(
+    _connection_uri: &str,
+    _query: &str,
+    _protocol: Option<&str>,
+) -> Result<ExDataFrame, ExplorerError> {
+    Err(ExplorerError::Other(format!(
+        "Explorer was compiled without the \"database\" feature enabled. \
+        Please read the section about precompilation in our README.md: https://github.com/elixir-explorer/explorer#precompilation"
+    )))
 }
 
-#[cfg(feature = "ndjson")]
-#[rustler::nif(schedule = "DirtyCpu")]
-pub fn df_load_ndjson(
-    binary: Binary,
-    infer_schema_length: Option<usize>,
-    batch_size: usize,
+#[cfg(not(feature = "delta"))]
+#[rustler::nif]
+pub fn df_from_delta(
+    _table_uri: &str,
+    _version: Option<i64>,
+    _columns: Option<Vec<String>>,
+    _config: Option<ExS3Config>,
 ) -> Result<ExDataFrame, ExplorerError> {
-    let cursor = Cursor::new(binary.as_slice());
-    let reader = JsonReader::new(cursor)
-        .with_json_format(JsonFormat::JsonLines)
-        .with_batch_size(batch_size)
-        .infer_schema_len(infer_schema_length);
+    Err(ExplorerError::Other(format!(
+        "Explorer was compiled without the \"delta\" feature enabled. \
+        Please read the section about precompilation in our README.md: https://github.com/elixir-explorer/explorer#precompilation"
+    )))
+}
 
-    finish_reader(reader)
+#[cfg(not(feature = "excel"))]
+#[rustler::nif]
+pub fn df_to_xlsx(
+    _data: ExDataFrame,
+    _filename: &str,
+    _sheet_name: Option<String>,
+) -> Result<(), ExplorerError> {
+    Err(ExplorerError::Other(format!(
+        "Explorer was compiled without the \"excel\" feature enabled. \
+        Please read the section about precompilation in our README.md: https://github.com/elixir-explorer/explorer#precompilation"
+    )))
 }
 
-// ============ For when the feature is not enabled ============ //
+#[cfg(not(feature = "excel"))]
+#[rustler::nif]
+pub fn df_from_xlsx(
+    _filename: &str,
+    _sheet: ExXlsxSheet,
+    _has_header: bool,
+    _skip_rows: usize,
+) -> Result<ExDataFrame, ExplorerError> {
+    Err(ExplorerError::Other(format!(
+        "Explorer was compiled without the \"excel\" feature enabled. \
+        Please read the section about precompilation in our README.md: https://github.com/elixir-explorer/explorer#precompilation"
+    )))
+}
+
+#[cfg(not(feature = "excel"))]
+#[rustler::nif]
+pub fn df_from_xlsx_all(
+    _filename: &str,
+    _has_header: bool,
+) -> Result<std::collections::HashMap<String, ExDataFrame>, ExplorerError> {
+    Err(ExplorerError::Other(format!(
+        "Explorer was compiled without the \"excel\" feature enabled. \
+        Please read the section about precompilation in our README.md: https://github.com/elixir-explorer/explorer#precompilation"
+    )))
+}
+
+#[cfg(not(feature = "orc"))]
+#[rustler::nif]
+pub fn df_from_orc(
+    _filename: &str,
+    _columns: Option<Vec<String>>,
+    _projection: Option<Vec<usize>>,
+) -> Result<ExDataFrame, ExplorerError> {
+    Err(ExplorerError::Other(format!(
+        "Explorer was compiled without the \"orc\" feature enabled. \
+        Please read the section about precompilation in our README.md: https://github.com/elixir-explorer/explorer#precompilation"
+    )))
+}
+
+#[cfg(not(feature = "orc"))]
+#[rustler::nif]
+pub fn df_load_orc(
+    _binary: Binary,
+    _columns: Option<Vec<String>>,
+    _projection: Option<Vec<usize>>,
+) -> Result<ExDataFrame, ExplorerError> {
+    Err(ExplorerError::Other(format!(
+        "Explorer was compiled without the \"orc\" feature enabled. \
+        Please read the section about precompilation in our README.md: https://github.com/elixir-explorer/explorer#precompilation"
+    )))
+}
 
 #[cfg(not(feature = "ndjson"))]
 #[rustler::nif]
@@ -600,7 +6847,38 @@ pub fn df_from_ndjson(
     _filename: &str,
     _infer_schema_length: Option<usize>,
     _batch_size: usize,
-) -> Result<ExDataFrame, ExplorerError> {
+    _column_order: Option<Vec<String>>,
+    _ignore_errors: bool,
+    _open_retries: usize,
+    _dtypes: Vec<(&str, &str)>,
+) -> Result<(ExDataFrame, usize), ExplorerError> {
+    Err(ExplorerError::Other(format!(
+        "Explorer was compiled without the \"ndjson\" feature enabled. \
+        This is mostly due to this feature being incompatible with your computer's architecture. \
+        Please read the section about precompilation in our README.md: https://github.com/elixir-explorer/explorer#precompilation"
+    )))
+}
+
+#[cfg(not(feature = "ndjson"))]
+#[rustler::nif]
+pub fn df_ndjson_batched_open(
+    _filename: &str,
+    _infer_schema_length: Option<usize>,
+    _batch_size: usize,
+) -> Result<ResourceArc<ExNdjsonBatchedReaderRef>, ExplorerError> {
+    Err(ExplorerError::Other(format!(
+        "Explorer was compiled without the \"ndjson\" feature enabled. \
+        This is mostly due to this feature being incompatible with your computer's architecture. \
+        Please read the section about precompilation in our README.md: https://github.com/elixir-explorer/explorer#precompilation"
+    )))
+}
+
+#[cfg(not(feature = "ndjson"))]
+#[rustler::nif]
+pub fn df_ndjson_batched_next(
+    _reader: ResourceArc<ExNdjsonBatchedReaderRef>,
+    _cancel_token: Option<ResourceArc<ExCancelTokenRef>>,
+) -> Result<Option<ExDataFrame>, ExplorerError> {
     Err(ExplorerError::Other(format!(
         "Explorer was compiled without the \"ndjson\" feature enabled. \
         This is mostly due to this feature being incompatible with your computer's architecture. \
@@ -610,7 +6888,11 @@ pub fn df_from_ndjson(
 
 #[cfg(not(feature = "ndjson"))]
 #[rustler::nif]
-pub fn df_to_ndjson(_data: ExDataFrame, _filename: &str) -> Result<(), ExplorerError> {
+pub fn df_to_ndjson(
+    _data: ExDataFrame,
+    _filename: &str,
+    _compression: Option<&str>,
+) -> Result<(), ExplorerError> {
     Err(ExplorerError::Other(format!(
         "Explorer was compiled without the \"ndjson\" feature enabled. \
         This is mostly due to this feature being incompatible with your computer's architecture. \
@@ -620,7 +6902,10 @@ pub fn df_to_ndjson(_data: ExDataFrame, _filename: &str) -> Result<(), ExplorerE
 
 #[cfg(not(feature = "ndjson"))]
 #[rustler::nif]
-pub fn df_dump_ndjson(_data: ExDataFrame) -> Result<Binary<'static>, ExplorerError> {
+pub fn df_dump_ndjson(
+    _data: ExDataFrame,
+    _compression: Option<&str>,
+) -> Result<Binary<'static>, ExplorerError> {
     Err(ExplorerError::Other(format!(
         "Explorer was compiled without the \"ndjson\" feature enabled. \
         This is mostly due to this feature being incompatible with your computer's architecture. \
@@ -648,7 +6933,10 @@ pub fn df_to_parquet_cloud(
     _data: ExDataFrame,
     _ex_entry: ExS3Entry,
     _ex_compression: ExParquetCompression,
-) -> Result<(), ExplorerError> {
+    _data_page_size: Option<usize>,
+    _skip_if_empty: bool,
+    _progress_pid: Option<LocalPid>,
+) -> Result<Atom, ExplorerError> {
     Err(ExplorerError::Other(format!(
         "Explorer was compiled without the \"aws\" feature enabled. \
         This is mostly due to this feature being incompatible with your computer's architecture. \
@@ -658,12 +6946,23 @@ pub fn df_to_parquet_cloud(
 
 #[cfg(not(feature = "aws"))]
 #[rustler::nif]
+#[allow(clippy::too_many_arguments)]
 pub fn df_to_csv_cloud(
-    data: ExDataFrame,
-    ex_entry: ExS3Entry,
-    has_headers: bool,
-    delimiter: u8,
-) -> Result<(), ExplorerError> {
+    _data: ExDataFrame,
+    _ex_entry: ExS3Entry,
+    _has_headers: bool,
+    _delimiter: u8,
+    _include_bom: bool,
+    _columns: Option<Vec<String>>,
+    _binary_encoding: Option<&str>,
+    _line_terminator: Option<String>,
+    _header_names: Option<Vec<String>>,
+    _trailing_newline: bool,
+    _nan_value: Option<String>,
+    _inf_value: Option<String>,
+    _skip_if_empty: bool,
+    _progress_pid: Option<LocalPid>,
+) -> Result<Atom, ExplorerError> {
     Err(ExplorerError::Other(format!(
         "Explorer was compiled without the \"aws\" feature enabled. \
         This is mostly due to this feature being incompatible with your computer's architecture. \
@@ -677,7 +6976,9 @@ pub fn df_to_ipc_cloud(
     _data: ExDataFrame,
     _ex_entry: ExS3Entry,
     _compression: Option<&str>,
-) -> Result<(), ExplorerError> {
+    _skip_if_empty: bool,
+    _progress_pid: Option<LocalPid>,
+) -> Result<Atom, ExplorerError> {
     Err(ExplorerError::Other(format!(
         "Explorer was compiled without the \"aws\" feature enabled. \
         This is mostly due to this feature being incompatible with your computer's architecture. \
@@ -691,7 +6992,9 @@ pub fn df_to_ipc_stream_cloud(
     _data: ExDataFrame,
     _ex_entry: ExS3Entry,
     _compression: Option<&str>,
-) -> Result<(), ExplorerError> {
+    _skip_if_empty: bool,
+    _progress_pid: Option<LocalPid>,
+) -> Result<Atom, ExplorerError> {
     Err(ExplorerError::Other(format!(
         "Explorer was compiled without the \"aws\" feature enabled. \
         This is mostly due to this feature being incompatible with your computer's architecture. \
@@ -701,7 +7004,12 @@ pub fn df_to_ipc_stream_cloud(
 
 #[cfg(not(any(feature = "ndjson", feature = "aws")))]
 #[rustler::nif(schedule = "DirtyIo")]
-pub fn df_to_ndjson_cloud(data: ExDataFrame, ex_entry: ExS3Entry) -> Result<(), ExplorerError> {
+pub fn df_to_ndjson_cloud(
+    data: ExDataFrame,
+    ex_entry: ExS3Entry,
+    _skip_if_empty: bool,
+    _progress_pid: Option<LocalPid>,
+) -> Result<Atom, ExplorerError> {
     Err(ExplorerError::Other(format!(
         "Explorer was compiled without the \"aws\" and \"ndjson\" features enabled. \
         This is mostly due to these feature being incompatible with your computer's architecture. \
@@ -2,13 +2,18 @@ use crate::atoms;
 use crate::ExplorerError;
 use chrono::prelude::*;
 
+use polars::io::csv::OwnedBatchedCsvReader;
 #[cfg(feature = "cloud")]
 use polars::prelude::cloud::CloudOptions;
 use polars::prelude::*;
-use rustler::{Atom, NifStruct, NifTaggedEnum, ResourceArc};
+use rustler::{
+    Atom, Decoder, Encoder, NifMap, NifResult, NifStruct, NifUntaggedEnum, ResourceArc, Term,
+};
 use std::convert::TryInto;
 use std::fmt;
 use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 
 #[cfg(feature = "aws")]
 use std::str::FromStr;
@@ -21,6 +26,61 @@ pub struct ExExprRef(pub Expr);
 pub struct ExLazyFrameRef(pub LazyFrame);
 pub struct ExSeriesRef(pub Series);
 
+// Holds the structs exported through the Arrow C Data Interface.
+// They are boxed, so their addresses are stable and can be given to
+// other libraries, which are valid until this resource is released.
+pub struct ExArrowCExportRef {
+    pub array: Box<polars::export::arrow::ffi::ArrowArray>,
+    pub schema: Box<polars::export::arrow::ffi::ArrowSchema>,
+}
+
+// The raw pointers inside of the exported structs are only read by
+// the consumers, and the release callbacks are thread-safe by spec.
+unsafe impl Send for ExArrowCExportRef {}
+unsafe impl Sync for ExArrowCExportRef {}
+
+// The batched CSV reader owns the file handle, which is closed when
+// the resource is garbage collected, even if we stop reading mid-way.
+pub struct ExCsvBatchedReaderRef(pub Mutex<OwnedBatchedCsvReader>);
+
+// The batched Parquet reader yields one row group at a time. The metadata
+// of the row groups left to read is kept along with the file.
+pub struct ExParquetBatchedReaderRef(pub Mutex<ExParquetBatchedReader>);
+
+pub struct ExParquetBatchedReader {
+    pub reader: std::io::BufReader<std::fs::File>,
+    pub row_groups: std::vec::IntoIter<polars::export::arrow::io::parquet::read::RowGroupMetaData>,
+    pub fields: Vec<polars::export::arrow::datatypes::Field>,
+}
+
+// The batched NDJSON reader infers the schema once, when it is opened,
+// and reads the next `batch_size` lines of the file on every batch.
+pub struct ExNdjsonBatchedReaderRef(pub Mutex<ExNdjsonBatchedReader>);
+
+pub struct ExNdjsonBatchedReader {
+    pub reader: std::io::BufReader<std::fs::File>,
+    pub schema: SchemaRef,
+    pub batch_size: usize,
+}
+
+// The IPC stream writer starts the stream with the schema of the first
+// dataframe written to it, and becomes `None` once it is closed.
+pub struct ExIpcStreamWriterRef(pub Mutex<Option<ExIpcStreamWriter>>);
+
+pub struct ExIpcStreamWriter {
+    pub writer:
+        polars::export::arrow::io::ipc::write::StreamWriter<std::io::BufWriter<std::fs::File>>,
+    pub schema: Option<Schema>,
+}
+
+// A schema parsed once from the dtypes, so it can be reused across reads.
+pub struct ExSchemaRef(pub Arc<Schema>);
+
+// A flag that is set from another process with `df_cancel`, and
+// checked by the readers to abort a long read.
+#[derive(Default)]
+pub struct ExCancelTokenRef(pub Arc<AtomicBool>);
+
 // The structs that start with "Ex" are related to the modules in Elixir.
 // Some of them are just wrappers around Polars data structs.
 // For example, a "ExDataFrame" is a wrapper around Polars' "DataFrame".
@@ -78,6 +138,46 @@ impl ExSeriesRef {
     }
 }
 
+impl ExSchemaRef {
+    pub fn new(schema: Arc<Schema>) -> Self {
+        Self(schema)
+    }
+}
+
+impl ExCancelTokenRef {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+impl ExIpcStreamWriterRef {
+    pub fn new(writer: ExIpcStreamWriter) -> Self {
+        Self(Mutex::new(Some(writer)))
+    }
+}
+
+impl ExParquetBatchedReaderRef {
+    pub fn new(reader: ExParquetBatchedReader) -> Self {
+        Self(Mutex::new(reader))
+    }
+}
+
+impl ExNdjsonBatchedReaderRef {
+    pub fn new(reader: ExNdjsonBatchedReader) -> Self {
+        Self(Mutex::new(reader))
+    }
+}
+
+impl ExCsvBatchedReaderRef {
+    pub fn new(reader: OwnedBatchedCsvReader) -> Self {
+        Self(Mutex::new(reader))
+    }
+}
+
 impl ExDataFrame {
     pub fn new(df: DataFrame) -> Self {
         Self {
@@ -365,7 +465,6 @@ impl From<NaiveTime> for ExTime {
 // In Elixir this would be represented like this:
 // * `:uncompressed` for `ExParquetCompression::Uncompressed`
 // * `{:brotli, 7}` for `ExParquetCompression::Brotli(Some(7))`
-#[derive(NifTaggedEnum)]
 pub enum ExParquetCompression {
     Brotli(Option<u32>),
     Gzip(Option<u8>),
@@ -375,6 +474,54 @@ pub enum ExParquetCompression {
     Zstd(Option<i32>),
 }
 
+const PARQUET_COMPRESSIONS: &str = "brotli, gzip, lz4raw, snappy, uncompressed and zstd";
+
+// This is decoded by hand instead of derived with `NifTaggedEnum`, because
+// the derived decoder fails with a bare `ArgumentError` for an unknown codec.
+// Here it raises a message that lists the supported codecs, and a level given
+// to a codec that doesn't take one is reported as such.
+impl<'a> Decoder<'a> for ExParquetCompression {
+    fn decode(term: Term<'a>) -> NifResult<Self> {
+        let (name, level) = match term.decode::<(Atom, Term<'a>)>() {
+            Ok((name, level)) => (name, Some(level)),
+            Err(_) => (term.decode::<Atom>()?, None),
+        };
+        let name = name.to_term(term.get_env()).atom_to_string()?;
+
+        let compression = match (name.as_str(), level) {
+            ("brotli", level) => ExParquetCompression::Brotli(decode_level(level)?),
+            ("gzip", level) => ExParquetCompression::Gzip(decode_level(level)?),
+            ("zstd", level) => ExParquetCompression::Zstd(decode_level(level)?),
+            ("lz4raw", None) => ExParquetCompression::Lz4raw,
+            ("snappy", None) => ExParquetCompression::Snappy,
+            ("uncompressed", None) => ExParquetCompression::Uncompressed,
+            ("lz4raw" | "snappy" | "uncompressed", Some(_)) => {
+                return Err(raise_message(format!(
+                    "{name} compression does not support a compression level"
+                )))
+            }
+            (other, _) => {
+                return Err(raise_message(format!(
+                "unsupported parquet compression {other:?}, expected one of {PARQUET_COMPRESSIONS}"
+            )))
+            }
+        };
+
+        Ok(compression)
+    }
+}
+
+fn decode_level<'a, T: Decoder<'a>>(level: Option<Term<'a>>) -> NifResult<Option<T>> {
+    match level {
+        Some(level) => level.decode(),
+        None => Ok(None),
+    }
+}
+
+fn raise_message(message: String) -> rustler::Error {
+    rustler::Error::RaiseTerm(Box::new(message))
+}
+
 impl TryFrom<ExParquetCompression> for ParquetCompression {
     type Error = ExplorerError;
 
@@ -411,6 +558,24 @@ impl TryFrom<ExParquetCompression> for ParquetCompression {
     }
 }
 
+// The dialect detected by `df_csv_sniff`, which is a map in Elixir.
+// The fields can be given as they are to the CSV readers.
+#[derive(NifMap)]
+pub struct ExCsvDialect {
+    pub delimiter: u8,
+    pub quote_char: u8,
+    pub has_header: bool,
+}
+
+// In Elixir this would be represented like this:
+// * `0` for `ExXlsxSheet::Index(0)`
+// * `"Sheet1"` for `ExXlsxSheet::Name("Sheet1")`
+#[derive(NifUntaggedEnum)]
+pub enum ExXlsxSheet {
+    Index(usize),
+    Name(String),
+}
+
 // =========================
 // ====== FSS Structs ======
 // =========================
@@ -425,6 +590,117 @@ pub struct ExS3Config {
     pub token: Option<String>,
 }
 
+// The options of `df_to_file`, given as a map. Every key is optional, and
+// each writer only looks at the options that apply to its format. The
+// compression is decoded by the writer, since each format has its own.
+pub struct ExWriteOptions<'a> {
+    pub compression: Option<Term<'a>>,
+    pub header: Option<bool>,
+    pub delimiter: Option<u8>,
+}
+
+impl<'a> Decoder<'a> for ExWriteOptions<'a> {
+    fn decode(term: Term<'a>) -> NifResult<Self> {
+        Ok(ExWriteOptions {
+            compression: decode_optional_field(term, atoms::compression())?,
+            header: decode_optional_field(term, atoms::header())?,
+            delimiter: decode_optional_field(term, atoms::delimiter())?,
+        })
+    }
+}
+
+fn decode_optional_field<'a, T: Decoder<'a>>(term: Term<'a>, key: Atom) -> NifResult<Option<T>> {
+    match term.map_get(key.encode(term.get_env())) {
+        Ok(value) => value.decode(),
+        Err(_) => Ok(None),
+    }
+}
+
+// The options of `df_from_csv`, given as a map. Every key is optional, and
+// a missing key takes the default below. Unlike a missing key, a key given
+// as nil is decoded as `None`, so `infer_schema_length: nil` still reads
+// the entire file to infer the schema.
+pub struct ExCsvReadOptions<'a> {
+    pub infer_schema_length: Option<usize>,
+    pub has_header: bool,
+    pub max_rows: Option<usize>,
+    pub skip_rows: usize,
+    pub projection: Option<Vec<usize>>,
+    pub delimiter: u8,
+    pub rechunk: bool,
+    pub columns: Option<Vec<String>>,
+    pub dtypes: Vec<(&'a str, &'a str)>,
+    pub encoding: &'a str,
+    pub nil_values: Vec<String>,
+    pub parse_dates: bool,
+    pub eol_delimiter: Option<u8>,
+    pub low_memory: bool,
+    pub truncate_ragged_lines: bool,
+    pub true_values: Vec<String>,
+    pub false_values: Vec<String>,
+    pub binary_encoding: Option<&'a str>,
+    pub rename: Vec<(String, String)>,
+    pub fallback_dtypes: Vec<(&'a str, &'a str)>,
+    pub all_strings: bool,
+    pub strip_bom: bool,
+    pub date_format: Option<String>,
+    pub datetime_format: Option<String>,
+    pub time_format: Option<String>,
+    pub strict_date_formats: bool,
+    pub fixed_columns: bool,
+    pub nan_values: Vec<String>,
+    pub inf_values: Vec<String>,
+    pub special_floats_as_null: bool,
+    pub header_row: Option<usize>,
+    pub cancel_token: Option<ResourceArc<ExCancelTokenRef>>,
+}
+
+impl<'a> Decoder<'a> for ExCsvReadOptions<'a> {
+    fn decode(term: Term<'a>) -> NifResult<Self> {
+        Ok(ExCsvReadOptions {
+            infer_schema_length: decode_field_or(term, atoms::infer_schema_length(), Some(1000))?,
+            has_header: decode_field_or(term, atoms::has_header(), true)?,
+            max_rows: decode_field_or(term, atoms::max_rows(), None)?,
+            skip_rows: decode_field_or(term, atoms::skip_rows(), 0)?,
+            projection: decode_field_or(term, atoms::projection(), None)?,
+            delimiter: decode_field_or(term, atoms::delimiter(), b',')?,
+            rechunk: decode_field_or(term, atoms::rechunk(), true)?,
+            columns: decode_field_or(term, atoms::columns(), None)?,
+            dtypes: decode_field_or(term, atoms::dtypes(), vec![])?,
+            encoding: decode_field_or(term, atoms::encoding(), "utf8")?,
+            nil_values: decode_field_or(term, atoms::nil_values(), vec![])?,
+            parse_dates: decode_field_or(term, atoms::parse_dates(), false)?,
+            eol_delimiter: decode_field_or(term, atoms::eol_delimiter(), None)?,
+            low_memory: decode_field_or(term, atoms::low_memory(), false)?,
+            truncate_ragged_lines: decode_field_or(term, atoms::truncate_ragged_lines(), false)?,
+            true_values: decode_field_or(term, atoms::true_values(), vec![])?,
+            false_values: decode_field_or(term, atoms::false_values(), vec![])?,
+            binary_encoding: decode_field_or(term, atoms::binary_encoding(), None)?,
+            rename: decode_field_or(term, atoms::rename(), vec![])?,
+            fallback_dtypes: decode_field_or(term, atoms::fallback_dtypes(), vec![])?,
+            all_strings: decode_field_or(term, atoms::all_strings(), false)?,
+            strip_bom: decode_field_or(term, atoms::strip_bom(), true)?,
+            date_format: decode_field_or(term, atoms::date_format(), None)?,
+            datetime_format: decode_field_or(term, atoms::datetime_format(), None)?,
+            time_format: decode_field_or(term, atoms::time_format(), None)?,
+            strict_date_formats: decode_field_or(term, atoms::strict_date_formats(), true)?,
+            fixed_columns: decode_field_or(term, atoms::fixed_columns(), false)?,
+            nan_values: decode_field_or(term, atoms::nan_values(), vec![])?,
+            inf_values: decode_field_or(term, atoms::inf_values(), vec![])?,
+            special_floats_as_null: decode_field_or(term, atoms::special_floats_as_null(), false)?,
+            header_row: decode_field_or(term, atoms::header_row(), None)?,
+            cancel_token: decode_field_or(term, atoms::cancel_token(), None)?,
+        })
+    }
+}
+
+fn decode_field_or<'a, T: Decoder<'a>>(term: Term<'a>, key: Atom, default: T) -> NifResult<T> {
+    match term.map_get(key.encode(term.get_env())) {
+        Ok(value) => value.decode(),
+        Err(_) => Ok(default),
+    }
+}
+
 #[derive(NifStruct, Clone, Debug)]
 #[module = "FSS.S3.Entry"]
 pub struct ExS3Entry {
@@ -9,6 +9,13 @@ rustler::atoms! {
     polars,
     internal,
     other,
+    file,
+    file_not_found,
+    csv_parse,
+    schema_mismatch,
+    missing_column,
+    extra_column,
+    dtype,
     try_from_int,
     parquet,
     unknown
@@ -22,6 +29,29 @@ pub enum ExplorerError {
     Utf8(#[from] std::string::FromUtf8Error),
     #[error("Polars Error: {0}")]
     Polars(#[from] polars::prelude::PolarsError),
+    #[error("IO Error while {action} {path}: {source}")]
+    File {
+        path: String,
+        action: &'static str,
+        source: io::Error,
+    },
+    #[error("File Not Found Error while {action} {path}: {source}")]
+    FileNotFound {
+        path: String,
+        action: &'static str,
+        source: io::Error,
+    },
+    #[error(
+        "CSV Parse Error: could not parse {value:?} as {dtype} in column {column:?} at row {row}"
+    )]
+    CsvParse {
+        column: String,
+        row: usize,
+        value: String,
+        dtype: String,
+    },
+    #[error("Schema Mismatch Error: {0}")]
+    SchemaMismatch(SchemaMismatch),
     #[error("Internal Error: {0}")]
     Internal(String),
     #[error("Generic Error: {0}")]
@@ -34,8 +64,64 @@ pub enum ExplorerError {
     Unknown(#[from] anyhow::Error),
 }
 
+// The first difference found between a dataframe and the expected schema.
+#[derive(Error, Debug)]
+pub enum SchemaMismatch {
+    #[error("missing column {0:?}")]
+    MissingColumn(String),
+    #[error("unexpected column {0:?}")]
+    ExtraColumn(String),
+    #[error("expected column {column:?} to have dtype {expected}, but got {actual}")]
+    Dtype {
+        column: String,
+        expected: String,
+        actual: String,
+    },
+}
+
+impl ExplorerError {
+    // Wraps an IO error with the path of the file and the action we were
+    // doing, which is either "reading" or "writing".
+    pub fn file(path: &std::path::Path, action: &'static str, source: io::Error) -> Self {
+        let path = path.display().to_string();
+
+        match source.kind() {
+            io::ErrorKind::NotFound => Self::FileNotFound {
+                path,
+                action,
+                source,
+            },
+            _ => Self::File {
+                path,
+                action,
+                source,
+            },
+        }
+    }
+}
+
+// The schema mismatches are encoded as tagged tuples, so they can be
+// matched on, like `{:schema_mismatch, {:dtype, column, expected, actual}}`.
+// Every other error is encoded as its message.
 impl Encoder for ExplorerError {
     fn encode<'b>(&self, env: Env<'b>) -> Term<'b> {
-        format!("{self}").encode(env)
+        match self {
+            Self::SchemaMismatch(mismatch) => (schema_mismatch(), mismatch).encode(env),
+            _ => format!("{self}").encode(env),
+        }
+    }
+}
+
+impl Encoder for SchemaMismatch {
+    fn encode<'b>(&self, env: Env<'b>) -> Term<'b> {
+        match self {
+            Self::MissingColumn(column) => (missing_column(), column).encode(env),
+            Self::ExtraColumn(column) => (extra_column(), column).encode(env),
+            Self::Dtype {
+                column,
+                expected,
+                actual,
+            } => (dtype(), column, expected, actual).encode(env),
+        }
     }
 }
@@ -1,9 +1,8 @@
 use polars::prelude::*;
-use std::fs::File;
 use std::io::BufWriter;
 use std::result::Result;
 
-use crate::dataframe::io::schema_from_dtypes_pairs;
+use crate::dataframe::io::{create_file, schema_from_dtypes_pairs};
 use crate::datatypes::{ExParquetCompression, ExS3Entry};
 use crate::{ExLazyFrame, ExplorerError};
 
@@ -95,7 +94,7 @@ pub fn lf_to_parquet(
     } else {
         let mut df = lf.collect()?;
 
-        let file = File::create(filename)?;
+        let file = create_file(filename)?;
         let mut buf_writer = BufWriter::new(file);
 
         ParquetWriter::new(&mut buf_writer)
@@ -139,7 +138,7 @@ pub fn lf_to_ipc(
         Ok(())
     } else {
         let mut df = lf.collect()?;
-        let file = File::create(filename)?;
+        let file = create_file(filename)?;
         let mut buf_writer = BufWriter::new(file);
         IpcWriter::new(&mut buf_writer)
             .with_compression(compression)
@@ -14,6 +14,8 @@ use rustler::{Env, Term};
 #[global_allocator]
 static GLOBAL: MiMalloc = MiMalloc;
 
+#[cfg(feature = "cloud")]
+mod cloud_reader;
 #[cfg(feature = "cloud")]
 mod cloud_writer;
 
@@ -28,8 +30,9 @@ mod series;
 use dataframe::io::*;
 use dataframe::*;
 pub use datatypes::{
-    ExDataFrame, ExDataFrameRef, ExExpr, ExExprRef, ExLazyFrame, ExLazyFrameRef, ExSeries,
-    ExSeriesRef,
+    ExArrowCExportRef, ExCancelTokenRef, ExCsvBatchedReaderRef, ExDataFrame, ExDataFrameRef,
+    ExExpr, ExExprRef, ExIpcStreamWriterRef, ExLazyFrame, ExLazyFrameRef, ExNdjsonBatchedReaderRef,
+    ExParquetBatchedReaderRef, ExSchemaRef, ExSeries, ExSeriesRef,
 };
 pub use error::ExplorerError;
 use expressions::*;
@@ -43,6 +46,13 @@ fn on_load(env: Env, _info: Term) -> bool {
     rustler::resource!(ExExprRef, env);
     rustler::resource!(ExLazyFrameRef, env);
     rustler::resource!(ExSeriesRef, env);
+    rustler::resource!(ExCsvBatchedReaderRef, env);
+    rustler::resource!(ExArrowCExportRef, env);
+    rustler::resource!(ExIpcStreamWriterRef, env);
+    rustler::resource!(ExParquetBatchedReaderRef, env);
+    rustler::resource!(ExSchemaRef, env);
+    rustler::resource!(ExNdjsonBatchedReaderRef, env);
+    rustler::resource!(ExCancelTokenRef, env);
     true
 }
 
@@ -62,51 +72,139 @@ mod atoms {
         calendar,
         nan,
         infinity,
-        neg_infinity
+        neg_infinity,
+        upload_progress,
+        compression,
+        header,
+        delimiter,
+        infer_schema_length,
+        has_header,
+        max_rows,
+        skip_rows,
+        projection,
+        rechunk,
+        columns,
+        dtypes,
+        encoding,
+        nil_values,
+        parse_dates,
+        eol_delimiter,
+        low_memory,
+        truncate_ragged_lines,
+        true_values,
+        false_values,
+        binary_encoding,
+        rename,
+        fallback_dtypes,
+        all_strings,
+        strip_bom,
+        date_format,
+        datetime_format,
+        time_format,
+        strict_date_formats,
+        fixed_columns,
+        nan_values,
+        inf_values,
+        special_floats_as_null,
+        header_row,
+        cancel_token,
+        written,
+        skipped
     }
 }
 
 rustler::init!(
     "Elixir.Explorer.PolarsBackend.Native",
     [
+        cloud_download,
         df_from_arrow_stream_pointer,
+        df_export_arrow_c,
         df_arrange,
         df_arrange_with,
+        df_column_stats,
         df_concat_columns,
         df_concat_rows,
+        df_csv_batched_open,
+        df_csv_batched_next,
+        df_csv_to_parquet,
+        df_cancel_token_new,
+        df_cancel,
+        df_csv_infer_schema,
+        df_csv_sniff,
+        schema_build,
         df_describe,
         df_nil_count,
         df_distinct,
         df_drop,
         df_drop_nils,
         df_dtypes,
+        df_dump_arrow_chunks,
         df_dump_csv,
         df_dump_ndjson,
         df_dump_parquet,
+        df_dump_tsv,
         df_dump_ipc,
+        df_dump_ipc_zstd_dict,
+        df_dump_ipc_shards,
+        df_dump_ipc_with_schema,
         df_dump_ipc_stream,
         df_filter_with,
         df_from_csv,
+        df_from_csv_range,
+        df_from_csv_resumable,
+        df_from_csv_tail,
+        df_from_csv_with_schema,
+        df_from_delta,
+        df_from_flight,
         df_from_ipc,
         df_from_ipc_stream,
         df_from_ndjson,
+        df_from_orc,
         df_from_parquet,
+        df_from_parquet_cloud,
+        df_from_parquet_cloud_prefix,
+        df_from_parquet_dataset,
+        df_from_parquet_filtered,
+        df_from_parquet_url,
         df_from_series,
+        df_from_sql,
+        df_from_tsv,
+        df_from_xlsx,
+        df_from_xlsx_all,
         df_group_indices,
         df_groups,
         df_head,
+        df_import_arrow_c,
+        df_ipc_metadata,
+        df_ipc_schema,
+        df_ipc_stream_schema,
+        df_ipc_stream_writer_close,
+        df_ipc_stream_writer_open,
+        df_ipc_stream_writer_write,
         df_join,
         df_load_csv,
         df_load_ndjson,
+        df_load_orc,
         df_load_parquet,
         df_load_ipc,
+        df_load_ipc_zstd_dict,
+        df_load_ipc_concat,
         df_load_ipc_stream,
         df_mask,
         df_mutate_with_exprs,
         df_n_rows,
+        df_ndjson_batched_next,
+        df_ndjson_batched_open,
+        df_parquet_append,
+        df_parquet_batched_next,
+        df_parquet_batched_open,
+        df_parquet_column_info,
+        df_parquet_recompress,
+        df_parquet_verify,
         df_names,
         df_pivot_longer,
         df_pivot_wider,
+        df_preview,
         df_pull,
         df_put_column,
         df_rename_columns,
@@ -123,6 +221,7 @@ rustler::init!(
         df_to_csv,
         df_to_csv_cloud,
         df_to_dummies,
+        df_to_file,
         df_to_ipc,
         df_to_ipc_cloud,
         df_to_ipc_stream,
@@ -132,6 +231,12 @@ rustler::init!(
         df_to_ndjson_cloud,
         df_to_parquet,
         df_to_parquet_cloud,
+        df_to_parquet_partitioned,
+        df_to_parquet_sharded,
+        df_to_tsv,
+        df_to_xlsx,
+        df_train_zstd_dict,
+        df_validate_schema,
         df_width,
         // expressions
         expr_atom,
@@ -52,7 +52,7 @@ defmodule Explorer.DataFrame.CSVTest do
 
   test "from_csv/2 error" do
     assert_raise RuntimeError,
-                 ~r/No such file or directory/,
+                 ~r/File Not Found Error while reading unknown: No such file or directory/,
                  fn ->
                    DF.from_csv!("unknown")
                  end
@@ -182,6 +182,23 @@ defmodule Explorer.DataFrame.CSVTest do
              }
     end
 
+    @tag :tmp_dir
+    test "dtypes with a malformed value", config do
+      csv =
+        tmp_csv(config.tmp_dir, """
+        a,b
+        1,2
+        3,4
+        5,N/A
+        """)
+
+      assert_raise RuntimeError,
+                   ~r/could not parse "N\/A" as i64 in column "b" at row 2/,
+                   fn ->
+                     DF.from_csv!(csv, dtypes: %{b: :integer})
+                   end
+    end
+
     @tag :tmp_dir
     test "dtypes - parse datetime", config do
       csv =
@@ -437,6 +454,37 @@ defmodule Explorer.DataFrame.CSVTest do
              }
     end
 
+    @tag :tmp_dir
+    test "encoding - latin1 and windows-1252", config do
+      csv = tmp_csv(config.tmp_dir, <<"name,price\nJos", 0xE9, ",", 0x80, "5\n">>)
+
+      for encoding <- ["latin1", "windows-1252"] do
+        df = DF.from_csv!(csv, encoding: encoding)
+        assert DF.to_columns(df, atom_keys: true) == %{name: ["José"], price: ["€5"]}
+      end
+
+      assert {:error, message} = DF.from_csv(csv, encoding: "ascii")
+      assert message =~ "the encoding ascii is not supported for CSV"
+    end
+
+    @tag :tmp_dir
+    test "encoding - utf16", config do
+      contents = "name\nJosé\n"
+      little = :unicode.characters_to_binary(contents, :utf8, {:utf16, :little})
+      big = :unicode.characters_to_binary(contents, :utf8, {:utf16, :big})
+
+      for {encoding, data} <- [
+            {"utf16", <<0xFF, 0xFE>> <> little},
+            {"utf16", <<0xFE, 0xFF>> <> big},
+            {"utf16", little},
+            {"utf16-le", little},
+            {"utf16-be", big}
+          ] do
+        df = DF.from_csv!(tmp_csv(config.tmp_dir, data), encoding: encoding)
+        assert DF.to_columns(df, atom_keys: true) == %{name: ["José"]}
+      end
+    end
+
     @tag :tmp_dir
     test "parse floats with nans and infinity", config do
       csv =
@@ -524,6 +572,57 @@ defmodule Explorer.DataFrame.CSVTest do
       assert DF.to_columns(saved_df) == DF.to_columns(Explorer.Datasets.wine())
     end
 
+    @tag :cloud_integration
+    @tag :tmp_dir
+    test "writes the same bytes to S3 as to a local file", %{
+      df: df,
+      s3_config: config,
+      tmp_dir: tmp_dir
+    } do
+      path = "s3://test-bucket/test-writes/wine-#{System.monotonic_time()}.csv"
+      {:ok, entry} = FSS.S3.parse(path, config: config)
+      local_path = Path.join(tmp_dir, "local.csv")
+      downloaded_path = Path.join(tmp_dir, "downloaded.csv")
+
+      assert {:ok, _} =
+               Explorer.PolarsBackend.Native.df_to_csv(
+                 df.data,
+                 local_path,
+                 true,
+                 ?;,
+                 true,
+                 false,
+                 ["class", "alcohol"],
+                 nil,
+                 "\r\n",
+                 ["Class", "Alcohol"],
+                 false,
+                 nil,
+                 nil
+               )
+
+      assert {:ok, _} =
+               Explorer.PolarsBackend.Native.df_to_csv_cloud(
+                 df.data,
+                 entry,
+                 true,
+                 ?;,
+                 true,
+                 ["class", "alcohol"],
+                 nil,
+                 "\r\n",
+                 ["Class", "Alcohol"],
+                 false,
+                 nil,
+                 nil,
+                 false,
+                 nil
+               )
+
+      assert :ok = Explorer.FSS.download(entry, downloaded_path)
+      assert File.read!(downloaded_path) == File.read!(local_path)
+    end
+
     @tag :cloud_integration
     test "returns an error in case file is not found in S3 bucket", %{s3_config: s3_config} do
       path = "s3://test-bucket/test-writes/file-does-not-exist.csv"
@@ -207,6 +207,44 @@ defmodule Explorer.DataFrame.ParquetTest do
     end
   end
 
+  describe "decimals" do
+    setup %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "decimal.parquet")
+      df = DF.new(a: [1.25, 2.5, nil], b: [1, 2, 3])
+
+      assert {:ok, _} =
+               Explorer.PolarsBackend.Native.df_to_parquet(
+                 df.data,
+                 path,
+                 :uncompressed,
+                 nil,
+                 false,
+                 [{"a", "decimal[38,4]"}],
+                 "dictionary"
+               )
+
+      [path: path]
+    end
+
+    @tag :tmp_dir
+    test "are not read as floats by the eager readers", %{path: path} do
+      assert {:error, message} = DF.from_parquet(path)
+      assert message =~ ~s(decimal columns are not supported, got: "a" (decimal[38,4]))
+
+      assert {:error, message} = DF.load_parquet(File.read!(path))
+      assert message =~ ~s(decimal columns are not supported, got: "a" (decimal[38,4]))
+    end
+
+    @tag :tmp_dir
+    test "are not read as floats by the lazy readers", %{path: path} do
+      assert {:error, message} = DF.from_parquet(path, lazy: true)
+      assert message =~ ~s(decimal columns are not supported, got: "a" (decimal[38,4]))
+
+      assert {:error, message} = DF.load_parquet(File.read!(path), lazy: true)
+      assert message =~ ~s(decimal columns are not supported, got: "a" (decimal[38,4]))
+    end
+  end
+
   test "dump_parquet/1 without compression" do
     df = Explorer.Datasets.iris() |> DF.slice(0, 10)
 
@@ -278,6 +316,30 @@ defmodule Explorer.DataFrame.ParquetTest do
         assert_equal_from_path(df, parquet_path)
       end
     end
+
+    @tag :tmp_dir
+    test "writes the columns with snappy", %{df: df, tmp_dir: tmp_dir} do
+      parquet_path = Path.join(tmp_dir, "test.parquet")
+
+      assert :ok = DF.to_parquet(df, parquet_path, compression: {:snappy, nil})
+
+      assert {:ok, [{_name, codecs, _encodings} | _]} =
+               Explorer.PolarsBackend.Native.df_parquet_column_info(parquet_path)
+
+      assert codecs == ["snappy"]
+      assert_equal_from_path(df, parquet_path)
+    end
+
+    @tag :tmp_dir
+    test "fails when a level is given to snappy", %{df: df, tmp_dir: tmp_dir} do
+      parquet_path = Path.join(tmp_dir, "test.parquet")
+
+      assert_raise ArgumentError,
+                   "snappy compression does not support a compression level, got 1",
+                   fn -> DF.to_parquet(df, parquet_path, compression: {:snappy, 1}) end
+
+      refute File.exists?(parquet_path)
+    end
   end
 
   describe "to_parquet/3 - cloud" do
@@ -302,5 +364,43 @@ defmodule Explorer.DataFrame.ParquetTest do
 
       assert DF.to_columns(saved_df) == DF.to_columns(Explorer.Datasets.wine())
     end
+
+    @tag :cloud_integration
+    test "skips writing an empty frame to S3", %{df: df} do
+      config = %FSS.S3.Config{
+        access_key_id: "test",
+        secret_access_key: "test",
+        endpoint: "http://localhost:4566",
+        region: "us-east-1"
+      }
+
+      path = "s3://test-bucket/test-writes/empty-#{System.monotonic_time()}.parquet"
+      {:ok, entry} = FSS.S3.parse(path, config: config)
+      empty_df = DF.head(df, 0)
+
+      assert {:ok, :skipped} =
+               Explorer.PolarsBackend.Native.df_to_parquet_cloud(
+                 empty_df.data,
+                 entry,
+                 :snappy,
+                 nil,
+                 true,
+                 nil
+               )
+
+      assert {:error, _} = DF.from_parquet(path, config: config)
+
+      assert {:ok, :written} =
+               Explorer.PolarsBackend.Native.df_to_parquet_cloud(
+                 empty_df.data,
+                 entry,
+                 :snappy,
+                 nil,
+                 false,
+                 nil
+               )
+
+      assert DF.n_rows(DF.from_parquet!(path, config: config)) == 0
+    end
   end
 end
@@ -0,0 +1,2573 @@
+defmodule Explorer.PolarsBackend.NativeTest do
+  use ExUnit.Case, async: true
+
+  alias Explorer.PolarsBackend.Native
+  alias Explorer.PolarsBackend.Shared
+
+  defp from_csv(path, opts \\ []), do: Native.df_from_csv(path, Map.new(opts))
+
+  describe "df_load_csv/21" do
+    @ragged_csv """
+    a,b
+    1,2
+    3,4,5
+    6,7
+    """
+
+    defp load_csv(contents, opts) do
+      Native.df_load_csv(
+        contents,
+        1000,
+        Keyword.get(opts, :has_header, true),
+        nil,
+        0,
+        nil,
+        ?,,
+        true,
+        nil,
+        opts[:dtypes] || [],
+        opts[:encoding] || "utf8",
+        [],
+        false,
+        nil,
+        opts[:low_memory] || false,
+        opts[:truncate_ragged_lines] || false,
+        opts[:true_values] || [],
+        opts[:false_values] || [],
+        opts[:binary_encoding],
+        opts[:all_strings] || false,
+        Keyword.get(opts, :strip_bom, true)
+      )
+    end
+
+    test "fails with ragged lines by default" do
+      assert {:error, _} = load_csv(@ragged_csv, [])
+    end
+
+    test "drops the overflow fields when truncating ragged lines" do
+      assert {:ok, df} = load_csv(@ragged_csv, truncate_ragged_lines: true)
+
+      df = Shared.create_dataframe(df)
+
+      assert Explorer.DataFrame.to_columns(df, atom_keys: true) == %{
+               a: [1, 3, 6],
+               b: [2, 4, 7]
+             }
+    end
+
+    test "reads the same data in low memory mode" do
+      csv = "a,b\n" <> Enum.map_join(1..1000, "", &"#{&1},x#{&1}\n")
+
+      assert {:ok, df} = load_csv(csv, [])
+      assert {:ok, low_memory_df} = load_csv(csv, low_memory: true)
+
+      assert Explorer.DataFrame.to_columns(Shared.create_dataframe(low_memory_df)) ==
+               Explorer.DataFrame.to_columns(Shared.create_dataframe(df))
+    end
+
+    test "parses custom boolean spellings in the declared boolean columns" do
+      csv = """
+      a,b
+      1,Y
+      2,N
+      3,maybe
+      """
+
+      assert {:ok, df} =
+               load_csv(csv,
+                 dtypes: [{"b", "bool"}],
+                 true_values: ["Y"],
+                 false_values: ["N"]
+               )
+
+      df = Shared.create_dataframe(df)
+
+      assert Explorer.DataFrame.to_columns(df, atom_keys: true) == %{
+               a: [1, 2, 3],
+               b: [true, false, nil]
+             }
+
+      assert Explorer.DataFrame.dump_csv!(df) == "a,b\n1,true\n2,false\n3,\n"
+    end
+
+    test "fails with malformed list dtypes" do
+      assert {:error, message} = load_csv("a\n[1]\n", dtypes: [{"a", "list[i64"}])
+      assert message =~ "Expected the syntax list[<type>]"
+
+      assert {:error, message} = load_csv("a\n[1]\n", dtypes: [{"a", "list[foo]"}])
+      assert message =~ "invalid inner type foo in the list dtype list[foo]"
+    end
+
+    test "fails with malformed struct dtypes" do
+      assert {:error, message} = load_csv("a\n1\n", dtypes: [{"a", "struct{b:list[i64}"}])
+      assert message =~ "Expected the syntax struct{<name>:<type>,...}"
+
+      assert {:error, message} = load_csv("a\n1\n", dtypes: [{"a", "struct{b:i64,c:foo}"}])
+      assert message =~ "invalid type foo for the field c"
+    end
+
+    test "reads every column as a string without inference" do
+      csv = "a,b,c\n1,2.5,true\n01,x,\n"
+
+      assert {:ok, df} = load_csv(csv, all_strings: true)
+
+      assert Explorer.DataFrame.to_columns(Shared.create_dataframe(df), atom_keys: true) == %{
+               a: ["1", "01"],
+               b: ["2.5", "x"],
+               c: ["true", nil]
+             }
+
+      assert {:ok, df} = load_csv(csv, all_strings: true, has_header: false)
+
+      assert Explorer.DataFrame.to_columns(Shared.create_dataframe(df), atom_keys: true) == %{
+               column_1: ["a", "1", "01"],
+               column_2: ["b", "2.5", "x"],
+               column_3: ["c", "true", nil]
+             }
+
+      assert {:ok, df} = load_csv(csv, all_strings: true, dtypes: [{"a", "i64"}])
+
+      assert Explorer.DataFrame.dtypes(Shared.create_dataframe(df)) == %{
+               "a" => :integer,
+               "b" => :string,
+               "c" => :string
+             }
+    end
+
+    test "strips a leading BOM from the first column name" do
+      csv = "\uFEFFid,name\n1,ada\n"
+
+      assert {:ok, df} = load_csv(csv, [])
+      assert Explorer.DataFrame.names(Shared.create_dataframe(df)) == ["id", "name"]
+
+      utf16 = <<0xFF, 0xFE>> <> :unicode.characters_to_binary("id\n1\n", :utf8, {:utf16, :little})
+      assert {:ok, df} = load_csv(utf16, [])
+      assert Explorer.DataFrame.to_columns(Shared.create_dataframe(df)) == %{"id" => [1]}
+    end
+
+    test "detects the endianness from a UTF-16 BOM that is not stripped" do
+      utf16 = <<0xFE, 0xFF>> <> :unicode.characters_to_binary("id\n1\n", :utf8, {:utf16, :big})
+
+      assert {:ok, df} = load_csv(utf16, encoding: "utf16", strip_bom: false)
+      assert Native.df_names(df) == {:ok, ["\uFEFFid"]}
+      assert Native.df_dump_ndjson(df, nil) == {:ok, ~s({"\uFEFFid":1}\n)}
+    end
+
+    test "reads back the binary columns dumped as hex or base64" do
+      series = Explorer.Series.from_list([<<1, 255>>, <<0>>], dtype: :binary)
+      df = Explorer.DataFrame.new(a: series)
+
+      for encoding <- ["hex", "base64"] do
+        assert {:ok, csv} =
+                 Native.df_dump_csv(
+                   df.data,
+                   true,
+                   ?,,
+                   false,
+                   nil,
+                   encoding,
+                   nil,
+                   nil,
+                   true,
+                   nil,
+                   nil
+                 )
+
+        assert {:ok, loaded} = load_csv(csv, dtypes: [{"a", "binary"}], binary_encoding: encoding)
+
+        assert Explorer.DataFrame.to_columns(Shared.create_dataframe(loaded)) == %{
+                 "a" => [<<1, 255>>, <<0>>]
+               }
+      end
+
+      assert {:error, message} = load_csv("a\n01\n", binary_encoding: "base32")
+      assert message =~ "the binary encoding base32 is not supported for CSV"
+    end
+  end
+
+  describe "df_from_tsv/2 and df_dump_tsv/4" do
+    @tag :tmp_dir
+    test "reads quotes as regular characters", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.tsv")
+      File.write!(path, "name\tquote\nada\t\"hello\n")
+
+      assert {:ok, df} = Native.df_from_tsv(path, %{})
+
+      df = Shared.create_dataframe(df)
+
+      assert Explorer.DataFrame.to_columns(df, atom_keys: true) == %{
+               name: ["ada"],
+               quote: [~s("hello)]
+             }
+    end
+
+    @tag :tmp_dir
+    test "takes the same options as the CSV reader", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.tsv")
+      File.write!(path, "name\tscore\nada\tNA\n")
+
+      assert {:ok, df} =
+               Native.df_from_tsv(path, %{nil_values: ["NA"], rename: [{"score", "points"}]})
+
+      assert Explorer.DataFrame.to_columns(Shared.create_dataframe(df), atom_keys: true) == %{
+               name: ["ada"],
+               points: [nil]
+             }
+    end
+
+    test "writes with the tab delimiter" do
+      df = Explorer.DataFrame.new(a: [1, 2], b: ["x", "y"])
+
+      assert Native.df_dump_tsv(df.data, true, false, nil) == {:ok, "a\tb\n1\tx\n2\ty\n"}
+    end
+
+    test "writes quotes as regular characters" do
+      df = Explorer.DataFrame.new(quote: [~s("hello), ~s(say "hi")])
+
+      assert Native.df_dump_tsv(df.data, true, false, nil) ==
+               {:ok, ~s(quote\n"hello\nsay "hi"\n)}
+
+      df = Explorer.DataFrame.new(a: ["x\ty"])
+      assert {:error, message} = Native.df_dump_tsv(df.data, true, false, nil)
+      assert message =~ ~s(cannot write the value "x\\ty" of column "a" without quoting)
+    end
+  end
+
+  describe "df_csv_infer_schema/9" do
+    @tag :tmp_dir
+    test "returns the inferred dtypes without reading the rows", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.csv")
+      File.write!(path, "\uFEFFa,b,c,d\n1,1.5,x,2024-01-02\n2,NA,y,2024-01-03\n")
+
+      assert Native.df_csv_infer_schema(path, 100, true, 0, ?,, "utf8", ["NA"], true, nil) ==
+               {:ok, [{"a", "i64"}, {"b", "f64"}, {"c", "str"}, {"d", "date"}]}
+
+      assert Native.df_csv_infer_schema(path, 100, true, 0, ?,, "utf8", ["NA"], false, nil) ==
+               {:ok, [{"a", "i64"}, {"b", "f64"}, {"c", "str"}, {"d", "str"}]}
+    end
+  end
+
+  describe "df_csv_sniff/2" do
+    @tag :tmp_dir
+    test "detects the delimiter, the quote char and the header", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.csv")
+
+      File.write!(path, "name;price\n\"a;b\";1.5\nc;2\n")
+
+      assert Native.df_csv_sniff(path, 1024) ==
+               {:ok, %{delimiter: ?;, quote_char: ?", has_header: true}}
+
+      File.write!(path, "1\t'x'\n2\t'y'\n")
+
+      assert Native.df_csv_sniff(path, 1024) ==
+               {:ok, %{delimiter: ?\t, quote_char: ?', has_header: false}}
+
+      # The last line is cut by the sample size, so it is ignored.
+      File.write!(path, "a|b\n1|2\n3|4\n5|6\n")
+
+      assert Native.df_csv_sniff(path, 14) ==
+               {:ok, %{delimiter: ?|, quote_char: ?", has_header: true}}
+    end
+
+    @tag :tmp_dir
+    test "fails with an empty sample", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.csv")
+      File.write!(path, "")
+
+      assert {:error, message} = Native.df_csv_sniff(path, 1024)
+      assert message =~ "because the sample is empty"
+    end
+  end
+
+  describe "df_dump_csv/11" do
+    setup do
+      [df: Explorer.DataFrame.new(a: [1, 2], b: ["x", "y"], c: [true, false])]
+    end
+
+    test "writes only the given columns, in the given order", %{df: df} do
+      assert Native.df_dump_csv(
+               df.data,
+               true,
+               ?,,
+               false,
+               ["c", "a"],
+               nil,
+               nil,
+               nil,
+               true,
+               nil,
+               nil
+             ) ==
+               {:ok, "c,a\ntrue,1\nfalse,2\n"}
+
+      assert Explorer.DataFrame.names(df) == ["a", "b", "c"]
+    end
+
+    test "fails with unknown columns", %{df: df} do
+      assert {:error, message} =
+               Native.df_dump_csv(
+                 df.data,
+                 true,
+                 ?,,
+                 false,
+                 ["a", "z"],
+                 nil,
+                 nil,
+                 nil,
+                 true,
+                 nil,
+                 nil
+               )
+
+      assert message =~ ~s(cannot write column "z" because it does not exist)
+    end
+
+    test "writes binary columns as hex or base64" do
+      df = Explorer.DataFrame.new(a: Explorer.Series.from_list([<<1, 255>>, nil], dtype: :binary))
+
+      assert Native.df_dump_csv(df.data, true, ?,, false, nil, "hex", nil, nil, true, nil, nil) ==
+               {:ok, "a\n01ff\n\n"}
+
+      assert Native.df_dump_csv(
+               df.data,
+               true,
+               ?,,
+               false,
+               nil,
+               "base64",
+               nil,
+               nil,
+               true,
+               nil,
+               nil
+             ) ==
+               {:ok, "a\nAf8=\n\n"}
+
+      assert {:error, message} =
+               Native.df_dump_csv(
+                 df.data,
+                 true,
+                 ?,,
+                 false,
+                 nil,
+                 "base32",
+                 nil,
+                 nil,
+                 true,
+                 nil,
+                 nil
+               )
+
+      assert message =~ "the binary encoding base32 is not supported for CSV"
+    end
+
+    test "writes the UTF-8 BOM before the header", %{df: df} do
+      assert Native.df_dump_csv(df.data, true, ?,, true, ["a"], nil, nil, nil, true, nil, nil) ==
+               {:ok, "\uFEFFa\n1\n2\n"}
+
+      assert Native.df_dump_csv(df.data, false, ?,, true, ["a"], nil, nil, nil, true, nil, nil) ==
+               {:ok, "\uFEFF1\n2\n"}
+    end
+
+    test "writes with CRLF line endings", %{df: df} do
+      assert Native.df_dump_csv(
+               df.data,
+               true,
+               ?,,
+               false,
+               ["a"],
+               nil,
+               "\r\n",
+               nil,
+               true,
+               nil,
+               nil
+             ) ==
+               {:ok, "a\r\n1\r\n2\r\n"}
+
+      assert {:error, message} =
+               Native.df_dump_csv(df.data, true, ?,, false, nil, nil, ";", nil, true, nil, nil)
+
+      assert message =~ ~s(the line terminator ";" is not supported for CSV)
+    end
+
+    test "writes custom header names", %{df: df} do
+      assert {:ok, csv} =
+               Native.df_dump_csv(
+                 df.data,
+                 true,
+                 ?,,
+                 false,
+                 ["a", "b"],
+                 nil,
+                 nil,
+                 ["Id", "Nom"],
+                 true,
+                 nil,
+                 nil
+               )
+
+      assert csv == "Id,Nom\n1,x\n2,y\n"
+
+      assert Explorer.DataFrame.names(df) == ["a", "b", "c"]
+
+      assert {:error, message} =
+               Native.df_dump_csv(df.data, true, ?,, false, nil, nil, nil, ["Id"], true, nil, nil)
+
+      assert message =~ "expected 3 header names, but got 1"
+    end
+
+    test "writes without the trailing newline", %{df: df} do
+      assert {:ok, with_newline} =
+               Native.df_dump_csv(df.data, true, ?,, false, nil, nil, "\r\n", nil, true, nil, nil)
+
+      assert {:ok, without_newline} =
+               Native.df_dump_csv(
+                 df.data,
+                 true,
+                 ?,,
+                 false,
+                 nil,
+                 nil,
+                 "\r\n",
+                 nil,
+                 false,
+                 nil,
+                 nil
+               )
+
+      assert without_newline == "a,b,c\r\n1,x,true\r\n2,y,false"
+      assert byte_size(with_newline) - byte_size(without_newline) == 2
+
+      assert {:ok, with_newline} =
+               Native.df_dump_csv(df.data, true, ?,, false, nil, nil, nil, nil, true, nil, nil)
+
+      assert {:ok, without_newline} =
+               Native.df_dump_csv(df.data, true, ?,, false, nil, nil, nil, nil, false, nil, nil)
+
+      assert byte_size(with_newline) - byte_size(without_newline) == 1
+    end
+  end
+
+  describe "df_dump_csv/11 and df_from_csv/34" do
+    @tag :tmp_dir
+    test "round-trips NaN and infinities with custom spellings", %{tmp_dir: tmp_dir} do
+      df = Explorer.DataFrame.new(a: [1.5, :nan, :infinity, :neg_infinity, nil])
+
+      assert {:ok, csv} =
+               Native.df_dump_csv(df.data, true, ?,, false, nil, nil, nil, nil, true, "NA", "Inf")
+
+      assert csv == "a\n1.5\nNA\nInf\n-Inf\n\n"
+
+      path = Path.join(tmp_dir, "data.csv")
+      File.write!(path, csv)
+
+      read = fn as_null ->
+        from_csv(path,
+          dtypes: [{"a", "f64"}],
+          nan_values: ["NA"],
+          inf_values: ["Inf"],
+          special_floats_as_null: as_null
+        )
+      end
+
+      assert {:ok, read_df} = read.(false)
+
+      assert Explorer.DataFrame.to_columns(Shared.create_dataframe(read_df), atom_keys: true) ==
+               %{a: [1.5, :nan, :infinity, :neg_infinity, nil]}
+
+      assert {:ok, read_df} = read.(true)
+
+      assert Explorer.DataFrame.to_columns(Shared.create_dataframe(read_df), atom_keys: true) ==
+               %{a: [1.5, nil, nil, nil, nil]}
+    end
+  end
+
+  describe "df_to_csv/13" do
+    @tag :tmp_dir
+    test "writes without the trailing newline", %{tmp_dir: tmp_dir} do
+      df = Explorer.DataFrame.new(a: [1, 2], b: ["x", "y"])
+      with_path = Path.join(tmp_dir, "with.csv")
+      without_path = Path.join(tmp_dir, "without.csv")
+
+      assert {:ok, _} =
+               Native.df_to_csv(
+                 df.data,
+                 with_path,
+                 true,
+                 ?,,
+                 true,
+                 false,
+                 nil,
+                 nil,
+                 nil,
+                 nil,
+                 true,
+                 nil,
+                 nil
+               )
+
+      assert {:ok, _} =
+               Native.df_to_csv(
+                 df.data,
+                 without_path,
+                 true,
+                 ?,,
+                 true,
+                 false,
+                 nil,
+                 nil,
+                 nil,
+                 nil,
+                 false,
+                 nil,
+                 nil
+               )
+
+      assert File.read!(without_path) == "\uFEFFa,b\n1,x\n2,y"
+      assert File.stat!(with_path).size - File.stat!(without_path).size == 1
+    end
+
+    @tag :tmp_dir
+    test "appends the rows without the header or the BOM", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.csv")
+
+      to_csv = fn df, append ->
+        Native.df_to_csv(
+          df.data,
+          path,
+          true,
+          ?,,
+          true,
+          append,
+          nil,
+          nil,
+          nil,
+          nil,
+          true,
+          nil,
+          nil
+        )
+      end
+
+      assert {:ok, _} = to_csv.(Explorer.DataFrame.new(a: [1], b: ["x"]), true)
+      assert {:ok, _} = to_csv.(Explorer.DataFrame.new(a: [2], b: ["y"]), true)
+      assert File.read!(path) == "\uFEFFa,b\n1,x\n2,y\n"
+
+      assert {:error, message} = to_csv.(Explorer.DataFrame.new(b: ["z"], a: [3]), true)
+      assert message =~ "cannot append to #{path} because the columns do not match"
+      assert File.read!(path) == "\uFEFFa,b\n1,x\n2,y\n"
+
+      assert {:ok, _} = to_csv.(Explorer.DataFrame.new(a: [3], b: ["z"]), false)
+      assert File.read!(path) == "\uFEFFa,b\n3,z\n"
+    end
+  end
+
+  describe "df_from_csv/2" do
+    @tag :tmp_dir
+    test "detects the endianness from a UTF-16 BOM that is not stripped", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.csv")
+      utf16 = :unicode.characters_to_binary("id\n1\n", :utf8, {:utf16, :big})
+      File.write!(path, <<0xFE, 0xFF>> <> utf16)
+
+      assert {:ok, df} = from_csv(path, encoding: "utf16", strip_bom: false)
+
+      assert Native.df_names(df) == {:ok, ["\uFEFFid"]}
+      assert Native.df_dump_ndjson(df, nil) == {:ok, ~s({"\uFEFFid":1}\n)}
+    end
+
+    @tag :tmp_dir
+    test "parses the declared temporal columns with the given formats", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.csv")
+      File.write!(path, "a,b,c\n03/04/2024,03/04/2024 10:30,10h30\nbad,bad,bad\n")
+
+      read = fn strict ->
+        from_csv(path,
+          dtypes: [{"a", "date"}, {"b", "datetime[μs]"}, {"c", "time"}],
+          date_format: "%d/%m/%Y",
+          datetime_format: "%d/%m/%Y %H:%M",
+          time_format: "%Hh%M",
+          strict_date_formats: strict
+        )
+      end
+
+      assert {:error, message} = read.(true)
+      assert message =~ ~s(could not parse "bad" as date (%d/%m/%Y\) in column "a" at row 1)
+
+      assert {:ok, df} = read.(false)
+      assert Native.df_dtypes(df) == {:ok, ["date", "datetime[μs]", "time"]}
+
+      assert %{a: [a, nil], b: [b, nil], c: [c, nil]} =
+               Explorer.DataFrame.to_columns(Shared.create_dataframe(df), atom_keys: true)
+
+      assert a == ~D[2024-04-03]
+      assert NaiveDateTime.compare(b, ~N[2024-04-03 10:30:00]) == :eq
+      assert Time.compare(c, ~T[10:30:00]) == :eq
+    end
+
+    @tag :tmp_dir
+    test "keeps the nanoseconds of the declared time unit", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.csv")
+      File.write!(path, "a\n2024-04-03 10:30:00.123456789\n")
+
+      assert {:ok, df} =
+               from_csv(path,
+                 dtypes: [{"a", "datetime[ns]"}],
+                 datetime_format: "%Y-%m-%d %H:%M:%S%.f"
+               )
+
+      assert Native.df_dtypes(df) == {:ok, ["datetime[ns]"]}
+
+      series = Explorer.DataFrame.pull(Shared.create_dataframe(df), "a")
+      integers = Explorer.Series.cast(series, :integer)
+      assert Explorer.Series.to_list(integers) == [1_712_140_200_123_456_789]
+    end
+
+    @tag :tmp_dir
+    test "reads the header from the given line", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.csv")
+      File.write!(path, "exported by app\ngenerated at 2024-04-03\na,b\n1,x\n2,y\n")
+
+      assert {:ok, df} = from_csv(path, has_header: false, skip_rows: 5, header_row: 2)
+
+      assert Explorer.DataFrame.to_columns(Shared.create_dataframe(df), atom_keys: true) ==
+               %{a: [1, 2], b: ["x", "y"]}
+    end
+
+    @tag :tmp_dir
+    test "fails with a cancelled token", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.csv")
+      File.write!(path, "a,b\n1,x\n2,y\n")
+      token = Native.df_cancel_token_new()
+      assert {:ok, _} = Native.df_cancel(token)
+
+      assert {:error, "cancelled"} = from_csv(path, cancel_token: token)
+    end
+
+    @tag :tmp_dir
+    test "infers the dtypes from the whole file without a length", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.csv")
+      rows = Enum.map_join(1..5000, fn i -> "#{i},x\n" end)
+      File.write!(path, "a,b\n" <> rows <> "1.5,y\n")
+
+      read = fn infer_schema_length ->
+        from_csv(path, infer_schema_length: infer_schema_length)
+      end
+
+      assert {:error, _} = read.(100)
+
+      assert {:ok, df} = read.(nil)
+      assert Native.df_dtypes(df) == {:ok, ["f64", "str"]}
+
+      df = Shared.create_dataframe(df)
+      assert df |> Explorer.DataFrame.pull("a") |> Explorer.Series.last() == 1.5
+
+      assert {:ok, df} = read.(0)
+      assert Native.df_dtypes(df) == {:ok, ["str", "str"]}
+    end
+
+    @tag :tmp_dir
+    test "decodes the declared binary columns", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.csv")
+      File.write!(path, "a,b\n01ff,x\nzz,y\n")
+
+      read = fn binary_encoding ->
+        from_csv(path, dtypes: [{"a", "binary"}], binary_encoding: binary_encoding)
+      end
+
+      assert {:error, message} = read.("hex")
+      assert message =~ ~s(could not parse "zz" as binary (hex\) in column "a" at row 1)
+
+      File.write!(path, "a,b\n01ff,x\nab,y\n")
+      assert {:ok, df} = read.("hex")
+
+      assert Explorer.DataFrame.to_columns(Shared.create_dataframe(df), atom_keys: true) == %{
+               a: [<<1, 255>>, <<171>>],
+               b: ["x", "y"]
+             }
+    end
+
+    @tag :tmp_dir
+    test "renames the columns after reading", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.csv")
+      File.write!(path, "Col 1 (mm),b\n1,x\n")
+
+      read = fn rename ->
+        from_csv(path, rename: rename)
+      end
+
+      assert {:ok, df} = read.([{"Col 1 (mm)", "col_1_mm"}])
+      assert Explorer.DataFrame.names(Shared.create_dataframe(df)) == ["col_1_mm", "b"]
+
+      assert {:error, message} = read.([{"z", "y"}])
+      assert message =~ ~s(cannot rename column "z" because it does not exist)
+    end
+
+    @tag :tmp_dir
+    test "names the projected columns with the column names", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.csv")
+      File.write!(path, "a,b,c\n1,2,3\n")
+
+      read = fn projection, column_names ->
+        from_csv(path, projection: projection, columns: column_names, dtypes: [{"c", "str"}])
+      end
+
+      assert {:ok, df} = read.([0, 2], ["x", "y"])
+
+      assert Explorer.DataFrame.to_columns(Shared.create_dataframe(df), atom_keys: true) == %{
+               x: [1],
+               y: ["3"]
+             }
+
+      assert {:ok, df} = read.(nil, ["c"])
+      assert Explorer.DataFrame.names(Shared.create_dataframe(df)) == ["c"]
+
+      assert {:error, message} = read.([0, 2], ["x"])
+      assert message =~ "the projection has 2 columns, but 1 column names were given"
+    end
+
+    @tag :tmp_dir
+    test "casts the empty columns to their fallback dtypes", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.csv")
+      File.write!(path, "a,b,c\n1,,x\n2,,y\n")
+
+      assert {:ok, df} = from_csv(path, fallback_dtypes: [{"b", "f64"}, {"c", "f64"}])
+
+      assert Explorer.DataFrame.dtypes(Shared.create_dataframe(df)) == %{
+               "a" => :integer,
+               "b" => :float,
+               "c" => :string
+             }
+    end
+
+    @tag :tmp_dir
+    test "names fixed columns by position and rejects short rows", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.csv")
+      File.write!(path, "1,ada,10\n2,bob\n3,eve,30\n")
+
+      read = fn truncate_ragged_lines, stop_after_n_rows ->
+        from_csv(path,
+          has_header: false,
+          max_rows: stop_after_n_rows,
+          columns: ["id", "name", "score"],
+          truncate_ragged_lines: truncate_ragged_lines,
+          fixed_columns: true
+        )
+      end
+
+      assert {:error, message} = read.(false, nil)
+      assert message =~ "expected 3 fields in row 1"
+
+      assert {:ok, df} = read.(true, nil)
+
+      assert Explorer.DataFrame.to_columns(Shared.create_dataframe(df), atom_keys: true) == %{
+               id: [1, 2, 3],
+               name: ["ada", "bob", "eve"],
+               score: [10, nil, 30]
+             }
+
+      File.write!(path, ~s(1,"ada, jr",10\n2,bob\n))
+      assert {:ok, df} = read.(false, 1)
+
+      assert Explorer.DataFrame.to_columns(Shared.create_dataframe(df), atom_keys: true) == %{
+               id: [1],
+               name: ["ada, jr"],
+               score: [10]
+             }
+    end
+  end
+
+  describe "schema_build/1 and df_from_csv_with_schema/3" do
+    @tag :tmp_dir
+    test "reads many files with the same schema", %{tmp_dir: tmp_dir} do
+      assert {:ok, schema} = Native.schema_build([{"a", "str"}, {"b", "f64"}])
+
+      for {contents, index} <- Enum.with_index(["a,b\n1,2\n", "a,b\n3,4\n"]) do
+        path = Path.join(tmp_dir, "data#{index}.csv")
+        File.write!(path, contents)
+
+        assert {:ok, df} = Native.df_from_csv_with_schema(path, schema, %{})
+
+        assert Explorer.DataFrame.dtypes(Shared.create_dataframe(df)) == %{
+                 "a" => :string,
+                 "b" => :float
+               }
+      end
+    end
+
+    test "fails with unknown dtypes" do
+      assert {:error, _} = Native.schema_build([{"a", "unknown"}])
+    end
+
+    @tag :tmp_dir
+    test "fails when the dtypes are also given", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.csv")
+      File.write!(path, "a\n1\n")
+      assert {:ok, schema} = Native.schema_build([{"a", "str"}])
+
+      assert {:error, message} =
+               Native.df_from_csv_with_schema(path, schema, %{dtypes: [{"a", "i64"}]})
+
+      assert message =~ "the dtypes cannot be given together with a schema"
+    end
+
+    @tag :tmp_dir
+    test "strips a leading BOM from the file", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.csv")
+      File.write!(path, "\uFEFFid,name\n1,ada\n")
+
+      assert {:ok, df} = from_csv(path)
+
+      assert Explorer.DataFrame.to_columns(Shared.create_dataframe(df), atom_keys: true) == %{
+               id: [1],
+               name: ["ada"]
+             }
+    end
+  end
+
+  describe "df_from_csv_tail/8" do
+    @tag :tmp_dir
+    test "reads the last rows with the schema of the header", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.csv")
+      File.write!(path, "a,b\n1,x\n2,y\n3,z\n")
+
+      assert {:ok, df} = Native.df_from_csv_tail(path, 2, true, 100, ?,, [], [], nil)
+
+      assert Explorer.DataFrame.to_columns(Shared.create_dataframe(df), atom_keys: true) == %{
+               a: [2, 3],
+               b: ["y", "z"]
+             }
+
+      assert {:ok, df} = Native.df_from_csv_tail(path, 10, true, 100, ?,, [], [], nil)
+      assert Explorer.DataFrame.n_rows(Shared.create_dataframe(df)) == 3
+
+      assert {:ok, df} = Native.df_from_csv_tail(path, 0, true, 100, ?,, [], [], nil)
+      assert Explorer.DataFrame.names(Shared.create_dataframe(df)) == ["a", "b"]
+      assert Explorer.DataFrame.n_rows(Shared.create_dataframe(df)) == 0
+    end
+  end
+
+  describe "df_from_csv_range/8" do
+    @tag :tmp_dir
+    test "reads only the complete lines in the range", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.csv")
+      File.write!(path, "a,b\n1,x\n2,y\n3,z\n4,w\n")
+
+      # Starts in the middle of "1,x" and ends in the middle of "3,z".
+      assert {:ok, df} =
+               Native.df_from_csv_range(path, 5, 6, ?,, ["a", "b"], [{"b", "str"}], [], nil)
+
+      assert Explorer.DataFrame.to_columns(Shared.create_dataframe(df), atom_keys: true) == %{
+               a: [2, 3],
+               b: ["y", "z"]
+             }
+    end
+
+    @tag :tmp_dir
+    test "fails with dtypes for unknown columns", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.csv")
+      File.write!(path, "1,x\n")
+
+      assert {:error, message} =
+               Native.df_from_csv_range(path, 0, 4, ?,, ["a", "b"], [{"c", "str"}], [], nil)
+
+      assert message =~ ~s(cannot set the dtype of column "c")
+    end
+  end
+
+  describe "df_from_csv_resumable/9" do
+    @tag :tmp_dir
+    test "resumes from the offset after the last row read", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.csv")
+      File.write!(path, "a,b\n1,x\n2,y\n3,z\n")
+
+      read = fn offset ->
+        Native.df_from_csv_resumable(path, offset, 2, true, ?,, nil, [], [], nil)
+      end
+
+      assert {:ok, {df, 12}} = read.(0)
+
+      assert Explorer.DataFrame.to_columns(Shared.create_dataframe(df), atom_keys: true) == %{
+               a: [1, 2],
+               b: ["x", "y"]
+             }
+
+      assert {:ok, {df, 16}} = read.(12)
+
+      assert Explorer.DataFrame.to_columns(Shared.create_dataframe(df), atom_keys: true) == %{
+               a: [3],
+               b: ["z"]
+             }
+
+      assert {:ok, {df, 16}} = read.(16)
+      assert Explorer.DataFrame.n_rows(Shared.create_dataframe(df)) == 0
+
+      # Starts in the middle of "1,x", so it is aligned to "2,y".
+      assert {:ok, {df, 16}} = read.(5)
+      df = Shared.create_dataframe(df)
+      assert Explorer.Series.to_list(Explorer.DataFrame.pull(df, "a")) == [2, 3]
+    end
+  end
+
+  describe "df_csv_batched_open/13 and df_csv_batched_next/3" do
+    defp read_csv_batches(path, dtypes) do
+      {:ok, reader} =
+        Native.df_csv_batched_open(
+          path,
+          2,
+          100,
+          true,
+          0,
+          nil,
+          ?,,
+          nil,
+          dtypes,
+          "utf8",
+          [],
+          false,
+          nil
+        )
+
+      Stream.repeatedly(fn -> Native.df_csv_batched_next(reader, 1, nil) end)
+      |> Enum.take_while(&(&1 != {:ok, nil}))
+      |> Enum.flat_map(fn {:ok, batches} -> batches end)
+      |> Enum.map(&Shared.create_dataframe/1)
+      |> Explorer.DataFrame.concat_rows()
+    end
+
+    @tag :tmp_dir
+    test "infers the schema without dtypes", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.csv")
+      File.write!(path, "a,b\n1,x\n2,y\n3,z\n")
+
+      assert Explorer.DataFrame.to_columns(read_csv_batches(path, []), atom_keys: true) == %{
+               a: [1, 2, 3],
+               b: ["x", "y", "z"]
+             }
+    end
+
+    @tag :tmp_dir
+    test "overrides the dtypes of some columns", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.csv")
+      File.write!(path, "a,b\n1,x\n2,y\n3,z\n")
+
+      df = read_csv_batches(path, [{"a", "f64"}])
+
+      assert Explorer.DataFrame.dtypes(df) == %{"a" => :float, "b" => :string}
+
+      assert Explorer.DataFrame.to_columns(df, atom_keys: true) == %{
+               a: [1.0, 2.0, 3.0],
+               b: ["x", "y", "z"]
+             }
+    end
+
+    @tag :tmp_dir
+    test "returns at most the requested number of batches", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.csv")
+      File.write!(path, "a,b\n" <> Enum.map_join(1..100, "", &"#{&1},x#{&1}\n"))
+
+      {:ok, reader} =
+        Native.df_csv_batched_open(
+          path,
+          10,
+          100,
+          true,
+          0,
+          nil,
+          ?,,
+          nil,
+          [],
+          "utf8",
+          [],
+          false,
+          nil
+        )
+
+      assert {:ok, [first]} = Native.df_csv_batched_next(reader, 1, nil)
+      assert {:ok, rest} = Native.df_csv_batched_next(reader, 1000, nil)
+      assert Native.df_csv_batched_next(reader, 1, nil) == {:ok, nil}
+
+      df = Explorer.DataFrame.concat_rows(Enum.map([first | rest], &Shared.create_dataframe/1))
+      assert Explorer.DataFrame.to_columns(df, atom_keys: true).a == Enum.to_list(1..100)
+    end
+
+    @tag :tmp_dir
+    test "stops reading once the token is cancelled", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.csv")
+      File.write!(path, "a,b\n1,x\n2,y\n3,z\n")
+
+      {:ok, reader} =
+        Native.df_csv_batched_open(
+          path,
+          2,
+          100,
+          true,
+          0,
+          nil,
+          ?,,
+          nil,
+          [],
+          "utf8",
+          [],
+          false,
+          nil
+        )
+
+      token = Native.df_cancel_token_new()
+
+      assert {:ok, [_ | _]} = Native.df_csv_batched_next(reader, 1, token)
+      assert {:ok, _} = Native.df_cancel(token)
+      assert Native.df_csv_batched_next(reader, 1, token) == {:error, "cancelled"}
+    end
+  end
+
+  describe "df_from_ndjson/7" do
+    @tag :tmp_dir
+    test "pins the column order", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.ndjson")
+      File.write!(path, ~s({"c": 1, "b": "x", "a": true}\n{"a": false, "b": "y"}\n))
+
+      assert {:ok, {df, 0}} = Native.df_from_ndjson(path, 100, 1000, ["b", "d"], false, 0, [])
+      df = Shared.create_dataframe(df)
+
+      assert Explorer.DataFrame.names(df) == ["b", "d", "a", "c"]
+
+      assert Explorer.DataFrame.to_columns(df, atom_keys: true) == %{
+               a: [true, false],
+               b: ["x", "y"],
+               c: [1, nil],
+               d: [nil, nil]
+             }
+    end
+
+    @tag :tmp_dir
+    test "skips and counts the malformed lines", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.ndjson")
+      File.write!(path, ~s({"a": 1}\nnot json\n\n{"a": 2}\n[1, 2]\n))
+
+      assert {:error, _} = Native.df_from_ndjson(path, 100, 1000, nil, false, 0, [])
+      assert {:ok, {df, 2}} = Native.df_from_ndjson(path, 100, 1000, nil, true, 0, [])
+
+      assert Explorer.DataFrame.to_columns(Shared.create_dataframe(df), atom_keys: true) == %{
+               a: [1, 2]
+             }
+    end
+
+    @tag :tmp_dir
+    test "casts the columns to list dtypes", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.ndjson")
+      File.write!(path, ~s({"a": [1, 2], "b": 1}\n{"a": [3], "b": 2}\n))
+
+      assert {:ok, {df, 0}} =
+               Native.df_from_ndjson(path, 100, 1000, nil, false, 0, [{"a", "list[f64]"}])
+
+      assert Native.df_dtypes(df) == {:ok, ["list[f64]", "i64"]}
+
+      assert Native.df_dump_ndjson(df, nil) ==
+               {:ok, ~s({"a":[1.0,2.0],"b":1}\n{"a":[3.0],"b":2}\n)}
+
+      assert {:error, message} =
+               Native.df_from_ndjson(path, 100, 1000, nil, false, 0, [{"a", "list[foo]"}])
+
+      assert message =~ "invalid inner type foo in the list dtype list[foo]"
+
+      assert {:error, message} =
+               Native.df_from_ndjson(path, 100, 1000, nil, false, 0, [{"c", "list[f64]"}])
+
+      assert message =~ "does not exist"
+    end
+
+    @tag :tmp_dir
+    test "casts the columns to struct dtypes", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.ndjson")
+      File.write!(path, ~s({"a": {"x": 1, "y": "p"}}\n{"a": {"x": 2, "y": "q"}}\n))
+
+      assert {:ok, {df, 0}} =
+               Native.df_from_ndjson(path, 100, 1000, nil, false, 0, [
+                 {"a", "struct{x:f64,y:str}"}
+               ])
+
+      assert Native.df_dtypes(df) == {:ok, ["struct[2]"]}
+
+      assert Native.df_dump_ndjson(df, nil) ==
+               {:ok, ~s({"a":{"x":1.0,"y":"p"}}\n{"a":{"x":2.0,"y":"q"}}\n)}
+
+      assert {:error, message} =
+               Native.df_from_ndjson(path, 100, 1000, nil, false, 0, [
+                 {"a", "struct{x:foo,y:str}"}
+               ])
+
+      assert message =~ "invalid type foo for the field x in the struct dtype"
+    end
+
+    @tag :tmp_dir
+    test "casts the columns to timezone-aware datetimes", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.ndjson")
+      File.write!(path, ~s({"a": 0}\n{"a": 1000}\n))
+
+      assert {:ok, {df, 0}} =
+               Native.df_from_ndjson(path, 100, 1000, nil, false, 0, [
+                 {"a", "datetime[ms, America/New_York]"}
+               ])
+
+      assert Native.df_dtypes(df) == {:ok, ["datetime[ms, America/New_York]"]}
+
+      assert {:error, message} =
+               Native.df_from_ndjson(path, 100, 1000, nil, false, 0, [
+                 {"a", "datetime[ms, Mars/Olympus_Mons]"}
+               ])
+
+      assert message =~ "unknown timezone Mars/Olympus_Mons for datetime"
+
+      assert {:error, message} =
+               Native.df_from_ndjson(path, 100, 1000, nil, false, 0, [{"a", "datetime[s]"}])
+
+      assert message =~ "the time unit s is not supported for datetime"
+    end
+  end
+
+  describe "df_to_ndjson/3 and df_dump_ndjson/2" do
+    @tag :tmp_dir
+    test "writes compressed files that can be read back", %{tmp_dir: tmp_dir} do
+      df = Explorer.DataFrame.new(a: [1, 2])
+
+      for compression <- ["gzip", "zstd"] do
+        path = Path.join(tmp_dir, "data.ndjson.#{compression}")
+
+        assert {:ok, _} = Native.df_to_ndjson(df.data, path, compression)
+        assert {:ok, {read_df, 0}} = Native.df_from_ndjson(path, 100, 1000, nil, false, 0, [])
+        assert Explorer.DataFrame.to_columns(Shared.create_dataframe(read_df)) == %{"a" => [1, 2]}
+      end
+    end
+
+    test "dumps compressed contents" do
+      df = Explorer.DataFrame.new(a: [1, 2])
+
+      assert {:ok, contents} = Native.df_dump_ndjson(df.data, "gzip")
+      assert :zlib.gunzip(contents) == ~s({"a":1}\n{"a":2}\n)
+
+      for compression <- ["gzip", "zstd"] do
+        assert {:ok, contents} = Native.df_dump_ndjson(df.data, compression)
+        assert {:ok, loaded} = Native.df_load_ndjson(contents, 100, 1000)
+        assert Explorer.DataFrame.to_columns(Shared.create_dataframe(loaded)) == %{"a" => [1, 2]}
+      end
+
+      assert {:error, message} = Native.df_dump_ndjson(df.data, "brotli")
+      assert message =~ "the algorithm brotli is not supported for NDJSON compression"
+    end
+  end
+
+  describe "df_ndjson_batched_open/3 and df_ndjson_batched_next/2" do
+    @tag :tmp_dir
+    test "reads the lines in batches until the end of the file", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.ndjson")
+      File.write!(path, ~s({"a": 1}\n{"a": 2}\n\n{"a": 3}\n))
+
+      assert {:ok, reader} = Native.df_ndjson_batched_open(path, 100, 2)
+
+      assert {:ok, batch} = Native.df_ndjson_batched_next(reader, nil)
+      assert Explorer.DataFrame.to_columns(Shared.create_dataframe(batch)) == %{"a" => [1, 2]}
+
+      assert {:ok, batch} = Native.df_ndjson_batched_next(reader, nil)
+      assert Explorer.DataFrame.to_columns(Shared.create_dataframe(batch)) == %{"a" => [3]}
+
+      assert Native.df_ndjson_batched_next(reader, nil) == {:ok, nil}
+    end
+
+    @tag :tmp_dir
+    test "infers the schema from every line of the sample", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.ndjson")
+      File.write!(path, ~s({"b": null, "a": 1}\n{"b": "x", "a": 2.5}\n))
+
+      assert {:ok, reader} = Native.df_ndjson_batched_open(path, nil, 10)
+      assert {:ok, batch} = Native.df_ndjson_batched_next(reader, nil)
+
+      assert Native.df_names(batch) == {:ok, ["b", "a"]}
+
+      assert Explorer.DataFrame.to_columns(Shared.create_dataframe(batch), atom_keys: true) == %{
+               a: [1.0, 2.5],
+               b: [nil, "x"]
+             }
+
+      File.write!(path, ~s({"a": 1}\n[1]\n))
+      assert {:error, message} = Native.df_ndjson_batched_open(path, nil, 10)
+      assert message =~ "the line 2 is not a JSON object"
+    end
+  end
+
+  describe "df_parquet_batched_open/2 and df_parquet_batched_next/2" do
+    @tag :tmp_dir
+    test "reads the row groups until the end of the file", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.parquet")
+      df = Explorer.DataFrame.new(a: [1, 2, 3], b: ["x", "y", "z"])
+      :ok = Explorer.DataFrame.to_parquet!(df, path)
+
+      assert {:ok, reader} = Native.df_parquet_batched_open(path, ["b"])
+      assert {:ok, batch} = Native.df_parquet_batched_next(reader, nil)
+
+      assert Explorer.DataFrame.to_columns(Shared.create_dataframe(batch), atom_keys: true) == %{
+               b: ["x", "y", "z"]
+             }
+
+      assert Native.df_parquet_batched_next(reader, nil) == {:ok, nil}
+    end
+
+    @tag :tmp_dir
+    test "stops reading once the token is cancelled", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.parquet")
+      :ok = Explorer.DataFrame.to_parquet!(Explorer.DataFrame.new(a: [1]), path)
+
+      assert {:ok, reader} = Native.df_parquet_batched_open(path, nil)
+      token = Native.df_cancel_token_new()
+
+      assert {:ok, _batch} = Native.df_parquet_batched_next(reader, token)
+      assert {:ok, _} = Native.df_cancel(token)
+      assert Native.df_parquet_batched_next(reader, token) == {:error, "cancelled"}
+    end
+
+    @tag :tmp_dir
+    test "fails with unknown columns", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.parquet")
+      :ok = Explorer.DataFrame.to_parquet!(Explorer.DataFrame.new(a: [1]), path)
+
+      assert {:error, message} = Native.df_parquet_batched_open(path, ["z"])
+      assert message =~ ~s(column "z" does not exist)
+    end
+  end
+
+  describe "df_to_ipc/6 and df_ipc_metadata/1" do
+    @tag :tmp_dir
+    test "writes the custom metadata of the schema", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.arrow")
+      df = Explorer.DataFrame.new(a: [1, 2], b: ["x", "y"])
+
+      assert {:ok, _} = Native.df_to_ipc(df.data, path, "zstd", nil, [{"version", "2"}], [])
+      assert Native.df_ipc_metadata(path) == {:ok, [{"version", "2"}]}
+
+      assert {:ok, ipc_df} = Native.df_from_ipc(path, nil, nil, true, [], 0)
+
+      assert Explorer.DataFrame.to_columns(Shared.create_dataframe(ipc_df), atom_keys: true) ==
+               %{a: [1, 2], b: ["x", "y"]}
+    end
+
+    @tag :tmp_dir
+    test "writes the same output without metadata", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.arrow")
+      df = Explorer.DataFrame.new(a: [1, 2])
+
+      assert {:ok, _} = Native.df_to_ipc(df.data, path, nil, nil, [], [])
+      assert Native.df_ipc_metadata(path) == {:ok, []}
+      assert Native.df_dump_ipc(df.data, nil, [], []) == {:ok, File.read!(path)}
+    end
+
+    @tag :tmp_dir
+    test "writes the string columns as dictionaries", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.arrow")
+      df = Explorer.DataFrame.new(a: [1, 2, 3], b: ["x", "y", "x"])
+
+      assert {:ok, _} = Native.df_to_ipc(df.data, path, nil, nil, [], ["b"])
+      assert {:ok, ipc_df} = Native.df_from_ipc(path, nil, nil, true, [], 0)
+      assert Native.df_dtypes(ipc_df) == {:ok, ["i64", "cat"]}
+
+      assert {:ok, binary} = Native.df_dump_ipc(df.data, nil, [], ["b"])
+      assert {:ok, loaded} = Native.df_load_ipc(binary, nil, nil)
+      assert Native.df_dtypes(loaded) == {:ok, ["i64", "cat"]}
+
+      assert Explorer.DataFrame.to_columns(Shared.create_dataframe(loaded), atom_keys: true) ==
+               %{a: [1, 2, 3], b: ["x", "y", "x"]}
+
+      assert {:error, message} = Native.df_to_ipc(df.data, path, nil, nil, [], ["a"])
+      assert message =~ "cannot dictionary encode the column a because it has dtype i64"
+    end
+
+    @tag :tmp_dir
+    test "writes a record batch per chunk of the given size", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.arrow")
+      chunked_path = Path.join(tmp_dir, "chunked.arrow")
+      df = Explorer.DataFrame.new(a: [1, 2, 3, 4, 5])
+
+      assert {:ok, _} = Native.df_to_ipc(df.data, path, nil, nil, [], [])
+      assert {:ok, _} = Native.df_to_ipc(df.data, chunked_path, nil, 2, [], [])
+
+      # Each record batch adds its own message header.
+      assert File.stat!(chunked_path).size > File.stat!(path).size
+
+      assert {:ok, ipc_df} = Native.df_from_ipc(chunked_path, nil, nil, true, [], 0)
+
+      assert Explorer.DataFrame.to_columns(Shared.create_dataframe(ipc_df), atom_keys: true) ==
+               %{a: [1, 2, 3, 4, 5]}
+
+      assert Native.df_to_ipc(df.data, chunked_path, nil, 0, [], []) ==
+               {:error, "the chunk size must be greater than zero"}
+    end
+  end
+
+  describe "df_ipc_stream_writer_open/2 and df_ipc_stream_writer_write/2" do
+    @tag :tmp_dir
+    test "appends the dataframes to the same stream", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.arrows")
+
+      assert {:ok, writer} = Native.df_ipc_stream_writer_open(path, "zstd")
+
+      for df <- [Explorer.DataFrame.new(a: [1, 2]), Explorer.DataFrame.new(a: [3])] do
+        assert {:ok, _} = Native.df_ipc_stream_writer_write(writer, df.data)
+      end
+
+      assert {:error, message} =
+               Native.df_ipc_stream_writer_write(writer, Explorer.DataFrame.new(b: [4]).data)
+
+      assert message =~ "cannot write to the IPC stream because the schemas do not match"
+
+      assert {:ok, _} = Native.df_ipc_stream_writer_close(writer)
+
+      assert Native.df_ipc_stream_writer_close(writer) ==
+               {:error, "the IPC stream writer is already closed"}
+
+      assert {:ok, df} = Native.df_from_ipc_stream(path, nil, nil, 0)
+
+      assert Explorer.DataFrame.to_columns(Shared.create_dataframe(df), atom_keys: true) ==
+               %{a: [1, 2, 3]}
+    end
+  end
+
+  describe "df_ipc_schema/1 and df_ipc_stream_schema/1" do
+    @tag :tmp_dir
+    test "reads the dtypes without reading the rows", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.arrow")
+      stream_path = Path.join(tmp_dir, "data.arrows")
+      df = Explorer.DataFrame.new(a: [1], b: ["x"], c: [~D[2024-01-02]])
+      dtypes = [{"a", "i64"}, {"b", "str"}, {"c", "date"}]
+
+      assert {:ok, _} = Native.df_to_ipc(df.data, path, nil, nil, [], [])
+      assert {:ok, _} = Native.df_to_ipc_stream(df.data, stream_path, nil)
+
+      assert Native.df_ipc_schema(path) == {:ok, dtypes}
+      assert Native.df_ipc_stream_schema(stream_path) == {:ok, dtypes}
+
+      assert {:error, _} = Native.df_ipc_schema(stream_path)
+    end
+
+    @tag :tmp_dir
+    test "reads the empty schema of a stream closed without dataframes", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.arrows")
+
+      assert {:ok, writer} = Native.df_ipc_stream_writer_open(path, nil)
+      assert {:ok, _} = Native.df_ipc_stream_writer_close(writer)
+      assert Native.df_ipc_stream_schema(path) == {:ok, []}
+    end
+  end
+
+  describe "df_load_ipc_concat/3" do
+    test "stacks the binaries in order" do
+      first_df = Explorer.DataFrame.new(a: [1, 2], b: ["x", "y"])
+      second_df = Explorer.DataFrame.new(a: [3], b: ["z"])
+      {:ok, first} = Native.df_dump_ipc(first_df.data, nil, [], [])
+      {:ok, second} = Native.df_dump_ipc(second_df.data, "zstd", [], [])
+
+      assert {:ok, df} = Native.df_load_ipc_concat([first, second], ["b"], nil)
+
+      assert Explorer.DataFrame.to_columns(Shared.create_dataframe(df), atom_keys: true) ==
+               %{b: ["x", "y", "z"]}
+    end
+
+    test "fails with mismatching schemas or without binaries" do
+      {:ok, first} = Native.df_dump_ipc(Explorer.DataFrame.new(a: [1]).data, nil, [], [])
+      {:ok, second} = Native.df_dump_ipc(Explorer.DataFrame.new(a: ["x"]).data, nil, [], [])
+
+      assert {:error, message} = Native.df_load_ipc_concat([first, first, second], nil, nil)
+      assert message =~ "the IPC binary at index 2 does not match the schema of the first one"
+
+      assert {:error, message} = Native.df_load_ipc_concat([], nil, nil)
+      assert message =~ "expected at least one IPC binary to concatenate"
+    end
+  end
+
+  describe "df_dump_arrow_chunks/1" do
+    test "dumps each chunk in order with the schema" do
+      {:ok, first} = Native.df_dump_ipc(Explorer.DataFrame.new(a: [1, 2]).data, nil, [], [])
+      {:ok, second} = Native.df_dump_ipc(Explorer.DataFrame.new(a: [3]).data, nil, [], [])
+      {:ok, df} = Native.df_load_ipc_concat([first, second], nil, nil)
+
+      assert {:ok, {[first_chunk, second_chunk], [{"a", "i64"}]}} =
+               Native.df_dump_arrow_chunks(df)
+
+      for {chunk, expected} <- [{first_chunk, [1, 2]}, {second_chunk, [3]}] do
+        assert {:ok, chunk_df} = Native.df_load_ipc(chunk, nil, nil)
+        assert Explorer.DataFrame.to_columns(Shared.create_dataframe(chunk_df)) ==
+                 %{"a" => expected}
+      end
+    end
+  end
+
+  describe "df_dump_ipc_shards/3" do
+    test "splits the rows in shards with the full schema" do
+      df = Explorer.DataFrame.new(a: [1, 2, 3, 4, 5], b: ["v", "w", "x", "y", "z"])
+
+      assert {:ok, shards} = Native.df_dump_ipc_shards(df.data, 3, "lz4")
+
+      columns =
+        for shard <- shards do
+          {:ok, shard_df} = Native.df_load_ipc(shard, nil, nil)
+          Explorer.DataFrame.to_columns(Shared.create_dataframe(shard_df), atom_keys: true)
+        end
+
+      assert columns == [
+               %{a: [1, 2], b: ["v", "w"]},
+               %{a: [3, 4], b: ["x", "y"]},
+               %{a: [5], b: ["z"]}
+             ]
+    end
+
+    test "dumps empty shards when there are more shards than rows" do
+      df = Explorer.DataFrame.new(a: [1])
+
+      assert {:ok, [first, second]} = Native.df_dump_ipc_shards(df.data, 2, nil)
+      assert {:ok, first_df} = Native.df_load_ipc(first, nil, nil)
+      assert {:ok, second_df} = Native.df_load_ipc(second, nil, nil)
+
+      assert Native.df_shape(first_df) == {:ok, {1, 1}}
+      assert Native.df_shape(second_df) == {:ok, {0, 1}}
+      assert Native.df_dtypes(second_df) == {:ok, ["i64"]}
+
+      assert {:error, message} = Native.df_dump_ipc_shards(df.data, 0, nil)
+      assert message =~ "the number of shards must be greater than zero"
+    end
+  end
+
+  describe "df_train_zstd_dict/2, df_dump_ipc_zstd_dict/4 and df_load_ipc_zstd_dict/4" do
+    test "dumps and loads IPC binaries with a trained dictionary" do
+      samples =
+        for i <- 1..200 do
+          df = Explorer.DataFrame.new(id: [i, i + 1], name: ["name #{i}", "name #{i + 1}"])
+          {:ok, sample} = Native.df_dump_ipc(df.data, nil, [], [])
+          sample
+        end
+
+      assert {:ok, dict} = Native.df_train_zstd_dict(samples, 4096)
+
+      df = Explorer.DataFrame.new(id: [1, 2], name: ["ada", "bob"])
+      assert {:ok, binary} = Native.df_dump_ipc_zstd_dict(df.data, dict, [], [])
+      assert byte_size(binary) < byte_size(hd(samples))
+
+      assert {:ok, loaded} = Native.df_load_ipc_zstd_dict(binary, dict, nil, nil)
+
+      assert Explorer.DataFrame.to_columns(Shared.create_dataframe(loaded), atom_keys: true) ==
+               %{id: [1, 2], name: ["ada", "bob"]}
+
+      assert {:error, _} = Native.df_load_ipc(binary, nil, nil)
+    end
+  end
+
+  describe "df_to_file/3" do
+    @tag :tmp_dir
+    test "picks the writer from the extension", %{tmp_dir: tmp_dir} do
+      df = Explorer.DataFrame.new(a: [1, 2], b: ["x", "y"])
+
+      csv_path = Path.join(tmp_dir, "data.csv")
+      assert {:ok, _} = Native.df_to_file(df.data, csv_path, %{delimiter: ?;})
+      assert File.read!(csv_path) == "a;b\n1;x\n2;y\n"
+
+      parquet_path = Path.join(tmp_dir, "data.parquet")
+      assert {:ok, _} = Native.df_to_file(df.data, parquet_path, %{compression: {:zstd, 3}})
+
+      assert {:ok, [{"a", ["zstd"], _}, {"b", ["zstd"], _}]} =
+               Native.df_parquet_column_info(parquet_path)
+
+      arrow_path = Path.join(tmp_dir, "data.arrow")
+      assert {:ok, _} = Native.df_to_file(df.data, arrow_path, %{})
+      assert {:ok, arrow_df} = Native.df_from_ipc(arrow_path, nil, nil, true, [], 0)
+
+      assert Explorer.DataFrame.to_columns(Shared.create_dataframe(arrow_df), atom_keys: true) ==
+               %{a: [1, 2], b: ["x", "y"]}
+    end
+
+    @tag :tmp_dir
+    test "fails with unknown extensions or compressions", %{tmp_dir: tmp_dir} do
+      df = Explorer.DataFrame.new(a: [1, 2])
+
+      assert {:error, message} = Native.df_to_file(df.data, Path.join(tmp_dir, "data.xml"), %{})
+      assert message =~ "The supported extensions are: .csv, .parquet, .arrow and .ndjson"
+
+      path = Path.join(tmp_dir, "data.arrow")
+      assert {:error, message} = Native.df_to_file(df.data, path, %{compression: :zstd})
+      assert message =~ ~r/invalid compression :?zstd for IPC/
+    end
+  end
+
+  describe "df_dump_ipc_with_schema/2" do
+    test "returns the same binary as df_dump_ipc/4 and the schema" do
+      df = Explorer.DataFrame.new(a: [1, 2], b: ["x", "y"], c: [1.5, nil])
+
+      for compression <- [nil, "zstd"] do
+        assert {:ok, {binary, schema}} = Native.df_dump_ipc_with_schema(df.data, compression)
+        assert Native.df_dump_ipc(df.data, compression, [], []) == {:ok, binary}
+        assert schema == [{"a", "i64"}, {"b", "str"}, {"c", "f64"}]
+      end
+    end
+  end
+
+  describe "df_csv_to_parquet/9" do
+    @tag :tmp_dir
+    test "converts the CSV in batches", %{tmp_dir: tmp_dir} do
+      csv_path = Path.join(tmp_dir, "data.csv")
+      parquet_path = Path.join(tmp_dir, "data.parquet")
+      File.write!(csv_path, "a,b\n1,x\n2,y\n3,NA\n")
+
+      assert {:ok, _} =
+               Native.df_csv_to_parquet(
+                 csv_path,
+                 parquet_path,
+                 2,
+                 100,
+                 true,
+                 ?,,
+                 [],
+                 ["NA"],
+                 :zstd
+               )
+
+      assert {:ok, df} =
+               Native.df_from_parquet(
+                 parquet_path,
+                 nil,
+                 nil,
+                 nil,
+                 true,
+                 [],
+                 "auto",
+                 nil,
+                 false,
+                 0
+               )
+
+      assert Explorer.DataFrame.to_columns(Shared.create_dataframe(df), atom_keys: true) == %{
+               a: [1, 2, 3],
+               b: ["x", "y", nil]
+             }
+    end
+
+    @tag :tmp_dir
+    test "overrides the dtypes of some columns", %{tmp_dir: tmp_dir} do
+      csv_path = Path.join(tmp_dir, "data.csv")
+      parquet_path = Path.join(tmp_dir, "data.parquet")
+      File.write!(csv_path, "a,b\n1,x\n2,y\n3,z\n")
+
+      assert {:ok, _} =
+               Native.df_csv_to_parquet(
+                 csv_path,
+                 parquet_path,
+                 2,
+                 100,
+                 true,
+                 ?,,
+                 [{"a", "f64"}],
+                 [],
+                 :snappy
+               )
+
+      df = Explorer.DataFrame.from_parquet!(parquet_path)
+
+      assert Explorer.DataFrame.to_columns(df, atom_keys: true) == %{
+               a: [1.0, 2.0, 3.0],
+               b: ["x", "y", "z"]
+             }
+    end
+
+    @tag :tmp_dir
+    test "writes only the columns of a CSV without rows", %{tmp_dir: tmp_dir} do
+      csv_path = Path.join(tmp_dir, "data.csv")
+      parquet_path = Path.join(tmp_dir, "data.parquet")
+      File.write!(csv_path, "a,b\n")
+
+      assert {:ok, _} =
+               Native.df_csv_to_parquet(csv_path, parquet_path, 2, 100, true, ?,, [], [], :snappy)
+
+      assert {:ok, df} =
+               Native.df_from_parquet(
+                 parquet_path,
+                 nil,
+                 nil,
+                 nil,
+                 true,
+                 [],
+                 "auto",
+                 nil,
+                 false,
+                 0
+               )
+      assert Explorer.DataFrame.names(Shared.create_dataframe(df)) == ["a", "b"]
+    end
+  end
+
+  describe "df_validate_schema/2" do
+    setup do
+      [df: Explorer.DataFrame.new(a: [1, 2], b: ["x", "y"])]
+    end
+
+    test "accepts the matching schema", %{df: df} do
+      assert {:ok, _} = Native.df_validate_schema(df.data, [{"a", "i64"}, {"b", "str"}])
+    end
+
+    test "fails with the first mismatch", %{df: df} do
+      assert Native.df_validate_schema(df.data, [{"a", "i64"}, {"c", "str"}]) ==
+               {:error, {:schema_mismatch, {:missing_column, "c"}}}
+
+      assert Native.df_validate_schema(df.data, [{"a", "i64"}]) ==
+               {:error, {:schema_mismatch, {:extra_column, "b"}}}
+
+      assert Native.df_validate_schema(df.data, [{"a", "f64"}, {"b", "str"}]) ==
+               {:error, {:schema_mismatch, {:dtype, "a", "f64", "i64"}}}
+    end
+  end
+
+  describe "df_column_stats/1" do
+    test "returns the dtype, null count, min and max of each column" do
+      df =
+        Explorer.DataFrame.new(
+          a: [3, nil, 1],
+          b: [1.5, 2.5, nil],
+          c: ["x", nil, nil],
+          d: [~D[2024-01-02], ~D[2024-01-01], nil]
+        )
+
+      assert Native.df_column_stats(df.data) ==
+               {:ok,
+                [
+                  {"a", "i64", 1, 1, 3},
+                  {"b", "f64", 1, 1.5, 2.5},
+                  {"c", "str", 2, nil, nil},
+                  {"d", "date", 1, ~D[2024-01-01], ~D[2024-01-02]}
+                ]}
+
+      assert Explorer.DataFrame.to_columns(df, atom_keys: true).a == [3, nil, 1]
+    end
+
+    @tag :tmp_dir
+    test "handles timezone-aware datetimes and durations", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.ndjson")
+      File.write!(path, ~s({"a": 1000, "b": 5}\n{"a": 0, "b": 2}\n))
+
+      assert {:ok, {df, 0}} =
+               Native.df_from_ndjson(path, 100, 1000, nil, false, 0, [
+                 {"a", "datetime[ms, America/New_York]"},
+                 {"b", "duration[ms]"}
+               ])
+
+      assert {:ok, [{"a", "datetime[ms,America/New_York]", 0, min, max}, b]} =
+               Native.df_column_stats(df)
+
+      assert NaiveDateTime.compare(min, ~N[1970-01-01 00:00:00]) == :eq
+      assert NaiveDateTime.compare(max, ~N[1970-01-01 00:00:01]) == :eq
+      assert b == {"b", "duration[ms]", 0, 2, 5}
+    end
+  end
+
+  describe "df_parquet_append/3" do
+    @tag :tmp_dir
+    test "appends the rows by rewriting the file", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.parquet")
+
+      first_df = Explorer.DataFrame.new(a: [1, 2], b: ["x", "y"])
+      second_df = Explorer.DataFrame.new(a: [3], b: ["z"])
+
+      assert {:ok, _} = Native.df_parquet_append(path, first_df.data, :snappy)
+      assert {:ok, _} = Native.df_parquet_append(path, second_df.data, :snappy)
+
+      df = Explorer.DataFrame.from_parquet!(path)
+
+      assert Explorer.DataFrame.to_columns(df, atom_keys: true) ==
+               %{a: [1, 2, 3], b: ["x", "y", "z"]}
+
+      assert File.ls!(tmp_dir) == ["data.parquet"]
+    end
+
+    @tag :tmp_dir
+    test "fails with a mismatching schema before writing", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.parquet")
+      :ok = Explorer.DataFrame.to_parquet!(Explorer.DataFrame.new(a: [1, 2]), path)
+      contents = File.read!(path)
+
+      assert {:error, message} =
+               Native.df_parquet_append(path, Explorer.DataFrame.new(a: ["x"]).data, :snappy)
+
+      assert message =~ "because the schemas do not match"
+      assert File.read!(path) == contents
+    end
+  end
+
+  describe "df_to_parquet/7" do
+    @tag :tmp_dir
+    test "coerces the given columns before writing", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.parquet")
+      df = Explorer.DataFrame.new(a: [1, 2], b: [1.0, 2.0])
+
+      assert {:ok, _} =
+               Native.df_to_parquet(
+                 df.data,
+                 path,
+                 :uncompressed,
+                 nil,
+                 false,
+                 [{"a", "i32"}],
+                 "dictionary"
+               )
+
+      assert {:ok, df} =
+               Native.df_from_parquet(path, nil, nil, nil, false, [], "auto", nil, false, 0)
+      assert Native.df_dtypes(df) == {:ok, ["i32", "f64"]}
+    end
+
+    @tag :tmp_dir
+    test "fails when the cast loses values", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.parquet")
+      df = Explorer.DataFrame.new(a: [1, 300], b: [1.5, 2.0])
+
+      assert {:error, message} =
+               Native.df_to_parquet(
+                 df.data,
+                 path,
+                 :uncompressed,
+                 nil,
+                 false,
+                 [{"a", "u8"}],
+                 "dictionary"
+               )
+
+      assert message =~ ~s(cannot coerce column "a" from i64 to u8 without losing values)
+
+      assert {:error, message} =
+               Native.df_to_parquet(
+                 df.data,
+                 path,
+                 :uncompressed,
+                 nil,
+                 false,
+                 [{"b", "i64"}],
+                 "dictionary"
+               )
+
+      assert message =~ ~s(cannot coerce column "b" from f64 to i64 without losing values)
+    end
+
+    @tag :tmp_dir
+    test "round-trips decimal columns without changing the scale", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.parquet")
+      df = Explorer.DataFrame.new(a: [1.25, 2.5, nil])
+
+      assert {:ok, _} =
+               Native.df_to_parquet(
+                 df.data,
+                 path,
+                 :uncompressed,
+                 nil,
+                 false,
+                 [{"a", "decimal[38,4]"}],
+                 "dictionary"
+               )
+
+      assert {:ok, df} =
+               Native.df_from_parquet(path, nil, nil, nil, true, [], "auto", nil, false, 0)
+      assert Native.df_dtypes(df) == {:ok, ["decimal[38,4]"]}
+
+      assert {:ok, series} = Native.df_pull(df, "a")
+      assert {:ok, series} = Native.s_cast(series, "float")
+      assert Native.s_to_list(series) == {:ok, [1.25, 2.5, nil]}
+    end
+
+    for {compression, codec} <- [{:lz4raw, "lz4raw"}, {{:brotli, 5}, "brotli"}] do
+      @tag :tmp_dir
+      test "round-trips with #{inspect(compression)} compression", %{tmp_dir: tmp_dir} do
+        path = Path.join(tmp_dir, "data.parquet")
+        df = Explorer.DataFrame.new(a: [1, 2, 3], b: ["x", "y", nil])
+
+        assert {:ok, _} =
+                 Native.df_to_parquet(
+                   df.data,
+                   path,
+                   unquote(compression),
+                   nil,
+                   false,
+                   [],
+                   "dictionary"
+                 )
+
+        assert {:ok, [{"a", [unquote(codec)], _}, {"b", [unquote(codec)], _}]} =
+                 Native.df_parquet_column_info(path)
+
+        assert {:ok, df} =
+                 Native.df_from_parquet(path, nil, nil, nil, true, [], "auto", nil, false, 0)
+
+        assert Explorer.DataFrame.to_columns(Shared.create_dataframe(df), atom_keys: true) ==
+                 %{a: [1, 2, 3], b: ["x", "y", nil]}
+      end
+    end
+
+    @tag :tmp_dir
+    test "raises on an unknown compression", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.parquet")
+      df = Explorer.DataFrame.new(a: [1, 2, 3])
+
+      assert_raise ErlangError,
+                   ~r/expected one of brotli, gzip, lz4raw, snappy, uncompressed and zstd/,
+                   fn ->
+                     Native.df_to_parquet(df.data, path, :lzo, nil, false, [], "dictionary")
+                   end
+    end
+  end
+
+  describe "df_to_parquet/7 with categoricals" do
+    @tag :tmp_dir
+    test "writes the categorical columns as dictionaries or strings", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.parquet")
+      series = Explorer.Series.from_list(["x", "y", "x"], dtype: :category)
+      df = Explorer.DataFrame.new(a: series)
+
+      for {categorical_as, dtype} <- [{"dictionary", "cat"}, {"string", "str"}] do
+        assert {:ok, _} =
+                 Native.df_to_parquet(df.data, path, :snappy, nil, false, [], categorical_as)
+
+        assert {:ok, read_df} =
+                 Native.df_from_parquet(path, nil, nil, nil, true, [], "auto", nil, false, 0)
+
+        assert Native.df_dtypes(read_df) == {:ok, [dtype]}
+
+        assert Explorer.DataFrame.to_columns(Shared.create_dataframe(read_df), atom_keys: true) ==
+                 %{a: ["x", "y", "x"]}
+      end
+
+      assert {:error, message} =
+               Native.df_to_parquet(df.data, path, :snappy, nil, false, [], "integer")
+
+      assert message =~ ~s(unknown categorical encoding "integer" for Parquet)
+    end
+  end
+
+  describe "df_dump_parquet/4" do
+    setup do
+      [df: Explorer.DataFrame.new(a: Enum.to_list(1..10_000))]
+    end
+
+    test "splits the data pages at the given size", %{df: df} do
+      assert {:ok, default} = Native.df_dump_parquet(df.data, :uncompressed, nil, false)
+      assert {:ok, small_pages} = Native.df_dump_parquet(df.data, :uncompressed, 1024, false)
+
+      # Each page adds a header.
+      assert byte_size(small_pages) > byte_size(default)
+
+      assert {:ok, loaded} = Native.df_load_parquet(small_pages)
+
+      assert Explorer.DataFrame.to_columns(Shared.create_dataframe(loaded)) ==
+               Explorer.DataFrame.to_columns(df)
+    end
+
+    test "writes the statistics only when asked", %{df: df} do
+      assert {:ok, with_stats} = Native.df_dump_parquet(df.data, :uncompressed, nil, true)
+      assert {:ok, without_stats} = Native.df_dump_parquet(df.data, :uncompressed, nil, false)
+
+      # The statistics are the min, max and null count of each column.
+      assert byte_size(with_stats) > byte_size(without_stats)
+
+      assert {:ok, loaded} = Native.df_load_parquet(with_stats)
+
+      assert Explorer.DataFrame.to_columns(Shared.create_dataframe(loaded)) ==
+               Explorer.DataFrame.to_columns(df)
+    end
+  end
+
+  describe "df_to_parquet_partitioned/4" do
+    @tag :tmp_dir
+    test "writes one file per partition", %{tmp_dir: tmp_dir} do
+      df = Explorer.DataFrame.new(year: [2022, 2023, 2022], region: ["a", "b", "a"], v: [1, 2, 3])
+
+      assert {:ok, _} =
+               Native.df_to_parquet_partitioned(df.data, tmp_dir, ["year", "region"], :snappy)
+
+      assert File.ls!(Path.join(tmp_dir, "year=2022")) == ["region=a"]
+
+      assert {:ok, file_df} =
+               Native.df_from_parquet(
+                 Path.join([tmp_dir, "year=2022", "region=a", "part-0.parquet"]),
+                 nil,
+                 nil,
+                 nil,
+                 true,
+                 [],
+                 "auto",
+                 nil,
+                 false,
+                 0
+               )
+
+      assert Explorer.DataFrame.to_columns(Shared.create_dataframe(file_df)) == %{"v" => [1, 3]}
+
+      assert {:ok, dataset_df} = Native.df_from_parquet_dataset(tmp_dir, nil, nil, true, [])
+
+      assert dataset_df |> Shared.create_dataframe() |> Explorer.DataFrame.n_rows() == 3
+    end
+
+    @tag :tmp_dir
+    test "escapes the values in the directory names", %{tmp_dir: tmp_dir} do
+      root = Path.join(tmp_dir, "dataset")
+      df = Explorer.DataFrame.new(key: ["../a/b", "x=y", "50%"], v: [1, 2, 3])
+
+      assert {:ok, _} = Native.df_to_parquet_partitioned(df.data, root, ["key"], :snappy)
+
+      assert File.ls!(tmp_dir) == ["dataset"]
+      assert Enum.sort(File.ls!(root)) == ["key=..%2Fa%2Fb", "key=50%25", "key=x%3Dy"]
+
+      assert {:ok, dataset_df} = Native.df_from_parquet_dataset(root, nil, nil, true, [])
+
+      assert dataset_df |> Shared.create_dataframe() |> Explorer.DataFrame.to_columns() == %{
+               "key" => ["../a/b", "50%", "x=y"],
+               "v" => [1, 3, 2]
+             }
+
+      assert {:ok, dataset_df} =
+               Native.df_from_parquet_dataset(root, ["v"], nil, true, [{"key", "x=y"}])
+
+      assert Explorer.DataFrame.to_columns(Shared.create_dataframe(dataset_df)) == %{"v" => [2]}
+    end
+  end
+
+  describe "df_from_parquet_dataset/5" do
+    @tag :tmp_dir
+    test "reads only the partitions in the filter", %{tmp_dir: tmp_dir} do
+      df =
+        Explorer.DataFrame.new(
+          year: [2023, 2024, 2024, 2024],
+          month: [3, 2, 3, 3],
+          v: [1, 2, 3, 4]
+        )
+
+      assert {:ok, _} =
+               Native.df_to_parquet_partitioned(df.data, tmp_dir, ["year", "month"], :snappy)
+
+      assert {:ok, dataset_df} =
+               Native.df_from_parquet_dataset(tmp_dir, nil, nil, true, [
+                 {"year", "2024"},
+                 {"month", "3"}
+               ])
+
+      dataset_df = Shared.create_dataframe(dataset_df)
+
+      assert Explorer.DataFrame.to_columns(dataset_df, atom_keys: true) ==
+               %{v: [3, 4], year: ["2024", "2024"], month: ["3", "3"]}
+
+      assert {:ok, dataset_df} =
+               Native.df_from_parquet_dataset(tmp_dir, ["v"], nil, true, [
+                 {"month", "2"},
+                 {"month", "3"}
+               ])
+
+      assert dataset_df |> Shared.create_dataframe() |> Explorer.DataFrame.n_rows() == 4
+
+      assert {:error, message} =
+               Native.df_from_parquet_dataset(tmp_dir, nil, nil, true, [{"v", "1"}])
+
+      assert message =~ ~s(cannot filter by "v" because it is not a partition key)
+      assert message =~ "Filter the dataframe after reading it instead"
+    end
+
+    @tag :tmp_dir
+    test "adds the partition columns only with hive partitioning", %{tmp_dir: tmp_dir} do
+      for {region, v} <- [{"eu", [1, 2]}, {"us", [3]}] do
+        dir = Path.join(tmp_dir, "region=#{region}")
+        File.mkdir_p!(dir)
+        Explorer.DataFrame.to_parquet!(Explorer.DataFrame.new(v: v), Path.join(dir, "0.parquet"))
+      end
+
+      File.write!(Path.join(tmp_dir, "README.md"), "not a Parquet file")
+
+      assert {:ok, df} = Native.df_from_parquet_dataset(tmp_dir, nil, nil, true, [])
+
+      assert Explorer.DataFrame.to_columns(Shared.create_dataframe(df), atom_keys: true) ==
+               %{v: [1, 2, 3], region: ["eu", "eu", "us"]}
+
+      assert {:ok, df} = Native.df_from_parquet_dataset(tmp_dir, ["region"], nil, true, [])
+
+      assert Explorer.DataFrame.to_columns(Shared.create_dataframe(df), atom_keys: true) ==
+               %{region: ["eu", "eu", "us"]}
+
+      assert {:ok, df} = Native.df_from_parquet_dataset(tmp_dir, nil, nil, false, [])
+
+      assert Explorer.DataFrame.to_columns(Shared.create_dataframe(df), atom_keys: true) ==
+               %{v: [1, 2, 3]}
+
+      assert Native.df_from_parquet_dataset(tmp_dir, nil, nil, false, [{"region", "eu"}]) ==
+               {:error, "the partition filter requires hive partitioning"}
+    end
+
+    @tag :tmp_dir
+    test "fails without Parquet files", %{tmp_dir: tmp_dir} do
+      assert Native.df_from_parquet_dataset(tmp_dir, nil, nil, true, []) ==
+               {:error, "no Parquet files were found in #{tmp_dir}"}
+    end
+  end
+
+  describe "df_from_parquet_filtered/5" do
+    @tag :tmp_dir
+    test "reads only the rows that match the filter", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.parquet")
+      df = Explorer.DataFrame.new(a: [1, 2, 3, 4, 5], b: ["v", "w", "x", "y", "z"])
+      Explorer.DataFrame.to_parquet!(df, path)
+
+      read = fn op, value, projection ->
+        {:ok, df} = Native.df_from_parquet_filtered(path, "a", op, value, projection)
+        Explorer.DataFrame.to_columns(Shared.create_dataframe(df), atom_keys: true)
+      end
+
+      assert read.("gt", Native.expr_integer(3), nil) == %{a: [4, 5], b: ["y", "z"]}
+      assert read.("le", Native.expr_integer(2), nil) == %{a: [1, 2], b: ["v", "w"]}
+      assert read.("eq", Native.expr_integer(3), [1]) == %{b: ["x"]}
+      assert read.("lt", Native.expr_integer(0), nil) == %{a: [], b: []}
+
+      assert Native.df_from_parquet_filtered(path, "a", "ne", Native.expr_integer(3), nil) ==
+               {:error, "the operation ne is not supported for filtering Parquet files"}
+
+      assert {:error, message} =
+               Native.df_from_parquet_filtered(path, "a", "eq", Native.expr_integer(3), [2])
+
+      assert message =~ "column index 2 out of bounds in #{path}"
+    end
+  end
+
+  describe "df_to_parquet_sharded/5" do
+    @tag :tmp_dir
+    test "spreads the rows over the given number of files", %{tmp_dir: tmp_dir} do
+      df = Explorer.DataFrame.new(key: Enum.map(1..100, &rem(&1, 10)), v: Enum.to_list(1..100))
+
+      assert {:ok, _} = Native.df_to_parquet_sharded(df.data, tmp_dir, ["key"], 4, :snappy)
+
+      files = Enum.map(0..3, &Path.join(tmp_dir, "part-0000#{&1}.parquet"))
+      assert Enum.sort(File.ls!(tmp_dir)) == Enum.map(files, &Path.basename/1)
+
+      shards =
+        Enum.map(files, fn file ->
+          {:ok, shard} =
+            Native.df_from_parquet(file, nil, nil, nil, true, [], "auto", nil, false, 0)
+          shard = Shared.create_dataframe(shard)
+          assert Explorer.DataFrame.names(shard) == ["key", "v"]
+          Explorer.DataFrame.to_columns(shard, atom_keys: true)
+        end)
+
+      assert shards |> Enum.flat_map(& &1.v) |> Enum.sort() == Enum.to_list(1..100)
+
+      keys = Enum.map(shards, &MapSet.new(&1.key))
+
+      for {a, i} <- Enum.with_index(keys), {b, j} <- Enum.with_index(keys), i < j do
+        assert MapSet.disjoint?(a, b)
+      end
+    end
+
+    @tag :tmp_dir
+    test "fails without files or columns", %{tmp_dir: tmp_dir} do
+      df = Explorer.DataFrame.new(a: [1, 2])
+
+      assert {:error, message} = Native.df_to_parquet_sharded(df.data, tmp_dir, ["a"], 0, :snappy)
+      assert message =~ "the number of files must be greater than zero"
+
+      assert {:error, message} = Native.df_to_parquet_sharded(df.data, tmp_dir, [], 2, :snappy)
+      assert message =~ "cannot write a sharded Parquet dataset without columns to hash"
+    end
+  end
+
+  describe "df_from_parquet/10" do
+    @tag :tmp_dir
+    test "does not retry opening a missing file", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "missing.parquet")
+
+      {time, result} =
+        :timer.tc(fn ->
+          Native.df_from_parquet(path, nil, nil, nil, true, [], "auto", nil, false, 10)
+        end)
+
+      assert {:error, message} = result
+      assert message =~ "File Not Found Error while reading"
+      assert time < 1_000_000
+    end
+
+    @tag :tmp_dir
+    test "reads with the given parallel strategy", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.parquet")
+      :ok = Explorer.DataFrame.to_parquet!(Explorer.DataFrame.new(a: [1, 2]), path)
+
+      for parallel <- ["auto", "columns", "row_groups", "none"] do
+        assert {:ok, df} =
+                 Native.df_from_parquet(
+                   path,
+                   nil,
+                   nil,
+                   nil,
+                   true,
+                   [],
+                   parallel,
+                   nil,
+                   false,
+                   0
+                 )
+
+        assert Explorer.DataFrame.to_columns(Shared.create_dataframe(df)) == %{"a" => [1, 2]}
+      end
+
+      assert {:error, message} =
+               Native.df_from_parquet(
+                 path,
+                 nil,
+                 nil,
+                 nil,
+                 true,
+                 [],
+                 "threads",
+                 nil,
+                 false,
+                 0
+               )
+
+      assert message =~ "the parallel strategy threads is not supported for Parquet"
+    end
+
+    @tag :tmp_dir
+    test "keeps the stored dtypes without normalizing", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.parquet")
+      df = Explorer.DataFrame.new(a: [1, 2], b: [1.0, 2.0])
+
+      assert {:ok, _} =
+               Native.df_to_parquet(
+                 df.data,
+                 path,
+                 :uncompressed,
+                 nil,
+                 false,
+                 [{"a", "u32"}, {"b", "f32"}],
+                 "dictionary"
+               )
+
+      read = fn normalize ->
+        {:ok, df} =
+          Native.df_from_parquet(path, nil, nil, nil, normalize, [], "auto", nil, false, 0)
+
+        Native.df_dtypes(df)
+      end
+
+      assert read.(true) == {:ok, ["i64", "f64"]}
+      assert read.(false) == {:ok, ["u32", "f32"]}
+    end
+
+    test "reads MAP columns as lists of key and value structs" do
+      # A single "attrs" column of string to integer maps, with the
+      # rows %{"a" => 1, "b" => 2} and %{"c" => 3}.
+      path = "test/support/map_column.parquet"
+
+      assert {:ok, df} =
+               Native.df_from_parquet(path, nil, nil, nil, true, [], "auto", nil, true, 0)
+
+      assert Native.df_dtypes(df) == {:ok, ["list[struct[2]]"]}
+      assert Native.df_n_rows(df) == {:ok, 2}
+
+      assert {:ok, df} = Native.df_from_parquet(path, 1, nil, [0], true, [], "auto", nil, true, 0)
+
+      assert Native.df_names(df) == {:ok, ["attrs"]}
+      assert Native.df_n_rows(df) == {:ok, 1}
+
+      assert {:error, message} =
+               Native.df_from_parquet(path, nil, ["a"], nil, true, [], "auto", nil, true, 0)
+
+      assert message =~ ~s(cannot read the column "a" because it does not exist)
+    end
+
+    test "coerces Int96 timestamps to the given time unit" do
+      # Written like old Spark versions do, with the timestamps
+      # 2020-01-01 00:00:00.123456789 and 1970-01-01 00:00:00.
+      path = "test/support/int96_timestamps.parquet"
+
+      for {coerce_int96, dtype, values} <- [
+            {nil, "datetime[ns]", [1_577_836_800_123_456_789, 0]},
+            {"us", "datetime[μs]", [1_577_836_800_123_456, 0]},
+            {"ms", "datetime[ms]", [1_577_836_800_123, 0]}
+          ] do
+        assert {:ok, df} =
+                 Native.df_from_parquet(
+                   path,
+                   nil,
+                   nil,
+                   nil,
+                   true,
+                   [],
+                   "auto",
+                   coerce_int96,
+                   false,
+                   0
+                 )
+
+        assert Native.df_dtypes(df) == {:ok, [dtype]}
+
+        assert df
+               |> Shared.create_dataframe()
+               |> Explorer.DataFrame.pull("ts")
+               |> Explorer.Series.cast(:integer)
+               |> Explorer.Series.to_list() == values
+      end
+
+      assert {:error, message} =
+               Native.df_from_parquet(path, nil, nil, nil, true, [], "auto", "s", false, 0)
+
+      assert message =~ "the time unit s is not supported for Int96 timestamps"
+    end
+
+    test "coerces Int96 timestamps outside of the range of nanoseconds" do
+      # The timestamps 2500-01-01 00:00:00 and 1600-01-01 00:00:00.
+      path = "test/support/int96_far_timestamps.parquet"
+
+      for {coerce_int96, values} <- [
+            {"us", [16_725_225_600_000_000, -11_676_096_000_000_000]},
+            {"ms", [16_725_225_600_000, -11_676_096_000_000]}
+          ] do
+        assert {:ok, df} =
+                 Native.df_from_parquet(
+                   path,
+                   nil,
+                   nil,
+                   nil,
+                   true,
+                   [],
+                   "auto",
+                   coerce_int96,
+                   false,
+                   0
+                 )
+
+        assert df
+               |> Shared.create_dataframe()
+               |> Explorer.DataFrame.pull("ts")
+               |> Explorer.Series.cast(:integer)
+               |> Explorer.Series.to_list() == values
+      end
+    end
+  end
+
+  describe "df_from_parquet_cloud/3" do
+    setup do
+      [bypass: Bypass.open()]
+    end
+
+    test "fetches the footer and then the selected columns", %{bypass: bypass} do
+      df = Explorer.DataFrame.new(a: [1, 2, 3], b: ["x", "y", "z"], c: [1.0, 2.0, 3.0])
+      contents = Explorer.DataFrame.dump_parquet!(df)
+      test_pid = self()
+
+      Bypass.expect_once(bypass, "HEAD", "/test-bucket/data.parquet", fn conn ->
+        conn
+        |> Plug.Conn.put_resp_header("last-modified", "Tue, 15 Nov 1994 08:12:31 GMT")
+        |> Plug.Conn.put_resp_header("etag", ~s("1"))
+        |> Plug.Conn.resp(200, contents)
+      end)
+
+      Bypass.expect(bypass, "GET", "/test-bucket/data.parquet", fn conn ->
+        ["bytes=" <> range] = Plug.Conn.get_req_header(conn, "range")
+        [first, last] = range |> String.split("-") |> Enum.map(&String.to_integer/1)
+        send(test_pid, {:range, first, last})
+
+        Plug.Conn.resp(conn, 206, binary_part(contents, first, last - first + 1))
+      end)
+
+      entry = %FSS.S3.Entry{
+        bucket: "test-bucket",
+        key: "data.parquet",
+        config: %FSS.S3.Config{
+          access_key_id: "test",
+          secret_access_key: "test",
+          endpoint: "http://localhost:#{bypass.port}",
+          region: "us-east-1"
+        }
+      }
+
+      assert {:ok, df} = Native.df_from_parquet_cloud(entry, ["b"], nil)
+
+      assert Explorer.DataFrame.to_columns(Shared.create_dataframe(df), atom_keys: true) == %{
+               b: ["x", "y", "z"]
+             }
+
+      # One request for the footer and one for the column chunks.
+      last_byte = byte_size(contents) - 1
+      assert_received {:range, _, ^last_byte}
+      assert_received {:range, _, _}
+      refute_received {:range, _, _}
+    end
+  end
+
+  describe "cloud_download/2" do
+    setup do
+      [bypass: Bypass.open()]
+    end
+
+    defp s3_entry(bypass, key) do
+      %FSS.S3.Entry{
+        bucket: "test-bucket",
+        key: key,
+        config: %FSS.S3.Config{
+          access_key_id: "test",
+          secret_access_key: "test",
+          endpoint: "http://localhost:#{bypass.port}",
+          region: "us-east-1"
+        }
+      }
+    end
+
+    @tag :tmp_dir
+    test "writes the object to the local file", %{bypass: bypass, tmp_dir: tmp_dir} do
+      Bypass.expect_once(bypass, "GET", "/test-bucket/data.csv", fn conn ->
+        Plug.Conn.resp(conn, 200, "a,b\n1,2\n")
+      end)
+
+      path = Path.join(tmp_dir, "data.csv")
+
+      assert Native.cloud_download(s3_entry(bypass, "data.csv"), path) == {:ok, 8}
+      assert File.read!(path) == "a,b\n1,2\n"
+      assert File.ls!(tmp_dir) == ["data.csv"]
+    end
+
+    @tag :tmp_dir
+    test "leaves the local file untouched on errors", %{bypass: bypass, tmp_dir: tmp_dir} do
+      Bypass.expect_once(bypass, "GET", "/test-bucket/missing.csv", fn conn ->
+        Plug.Conn.resp(conn, 404, "")
+      end)
+
+      path = Path.join(tmp_dir, "data.csv")
+      File.write!(path, "old")
+
+      assert {:error, _} = Native.cloud_download(s3_entry(bypass, "missing.csv"), path)
+      assert File.read!(path) == "old"
+      assert File.ls!(tmp_dir) == ["data.csv"]
+    end
+  end
+
+  describe "df_from_parquet_url/3" do
+    setup do
+      [bypass: Bypass.open()]
+    end
+
+    test "keeps the query string of the URL", %{bypass: bypass} do
+      df = Explorer.DataFrame.new(a: [1, 2, 3])
+      contents = Explorer.DataFrame.dump_parquet!(df)
+
+      Bypass.expect(bypass, fn conn ->
+        assert conn.request_path == "/files/data.parquet"
+        assert conn.query_string == "token=secret"
+        serve_ranges(conn, contents)
+      end)
+
+      url = "http://localhost:#{bypass.port}/files/data.parquet?token=secret#ignored"
+      assert {:ok, df} = Native.df_from_parquet_url(url, nil, nil)
+
+      assert Explorer.DataFrame.to_columns(Shared.create_dataframe(df), atom_keys: true) == %{
+               a: [1, 2, 3]
+             }
+    end
+
+    test "reads the columns in the requested order", %{bypass: bypass} do
+      df = Explorer.DataFrame.new(a: [1, 2], b: ["x", "y"], c: [1.0, 2.0])
+      contents = Explorer.DataFrame.dump_parquet!(df)
+      Bypass.expect(bypass, &serve_ranges(&1, contents))
+      url = "http://localhost:#{bypass.port}/data.parquet"
+
+      assert {:ok, df} = Native.df_from_parquet_url(url, ["c", "a"], nil)
+      assert Native.df_names(df) == {:ok, ["c", "a"]}
+
+      assert {:ok, df} = Native.df_from_parquet_url(url, nil, [1, 0])
+      assert Native.df_names(df) == {:ok, ["b", "a"]}
+
+      assert {:error, message} = Native.df_from_parquet_url(url, ["a", "d"], nil)
+      assert message =~ ~s(unable to find column "d")
+
+      assert {:error, message} = Native.df_from_parquet_url(url, nil, [3])
+      assert message == "projection index 3 is out of bounds for schema of length 3"
+    end
+
+    test "fetches the whole file from servers that ignore ranges", %{bypass: bypass} do
+      df = Explorer.DataFrame.new(a: [1, 2, 3], b: ["x", "y", "z"])
+      contents = Explorer.DataFrame.dump_parquet!(df)
+
+      Bypass.expect(bypass, fn conn ->
+        conn
+        |> Plug.Conn.delete_req_header("range")
+        |> serve_ranges(contents)
+      end)
+
+      url = "http://localhost:#{bypass.port}/data.parquet"
+      assert {:ok, df} = Native.df_from_parquet_url(url, ["b"], nil)
+
+      assert Explorer.DataFrame.to_columns(Shared.create_dataframe(df), atom_keys: true) == %{
+               b: ["x", "y", "z"]
+             }
+    end
+
+    test "keeps the columns of a file without rows", %{bypass: bypass} do
+      df = Explorer.DataFrame.new(a: [1], b: ["x"]) |> Explorer.DataFrame.slice(0, 0)
+      contents = Explorer.DataFrame.dump_parquet!(df)
+      Bypass.expect(bypass, &serve_ranges(&1, contents))
+      url = "http://localhost:#{bypass.port}/data.parquet"
+
+      assert {:ok, df} = Native.df_from_parquet_url(url, ["b"], nil)
+      assert Native.df_names(df) == {:ok, ["b"]}
+      assert Native.df_dtypes(df) == {:ok, ["str"]}
+      assert Native.df_n_rows(df) == {:ok, 0}
+    end
+
+    test "returns an error for a URL without a file name" do
+      assert {:error, message} = Native.df_from_parquet_url("http://localhost/files/", nil, nil)
+      assert message ==
+               "invalid URL for a Parquet file http://localhost/files/: it has no file name"
+    end
+
+    defp serve_ranges(conn, contents) do
+      case Plug.Conn.get_req_header(conn, "range") do
+        ["bytes=" <> range] ->
+          [first, last] = range |> String.split("-") |> Enum.map(&String.to_integer/1)
+          Plug.Conn.resp(conn, 206, binary_part(contents, first, last - first + 1))
+
+        [] ->
+          conn
+          |> Plug.Conn.put_resp_header("last-modified", "Tue, 15 Nov 1994 08:12:31 GMT")
+          |> Plug.Conn.put_resp_header("etag", ~s("1"))
+          |> Plug.Conn.resp(200, contents)
+      end
+    end
+  end
+
+  describe "df_parquet_column_info/1" do
+    @tag :tmp_dir
+    test "returns the codecs and encodings of each column", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.parquet")
+      df = Explorer.DataFrame.new(a: [1, 2, 3], b: ["x", "y", "z"])
+      :ok = Explorer.DataFrame.to_parquet!(df, path, compression: :zstd)
+
+      assert {:ok, [{"a", ["zstd"], a_encodings}, {"b", ["zstd"], b_encodings}]} =
+               Native.df_parquet_column_info(path)
+
+      assert a_encodings != []
+      assert b_encodings != []
+    end
+  end
+
+  describe "df_parquet_recompress/3" do
+    @tag :tmp_dir
+    test "rewrites the file with another compression", %{tmp_dir: tmp_dir} do
+      src = Path.join(tmp_dir, "snappy.parquet")
+      dst = Path.join(tmp_dir, "zstd.parquet")
+      df = Explorer.DataFrame.new(a: [1, 2, 3], b: ["x", "y", nil])
+      :ok = Explorer.DataFrame.to_parquet!(df, src, compression: :snappy)
+
+      assert {:ok, _} = Native.df_parquet_recompress(src, dst, {:zstd, 3})
+
+      assert {:ok, [{"a", ["zstd"], _}, {"b", ["zstd"], _}]} = Native.df_parquet_column_info(dst)
+
+      assert Explorer.DataFrame.to_columns(Explorer.DataFrame.from_parquet!(dst)) ==
+               Explorer.DataFrame.to_columns(df)
+
+      assert {:ok, _} = Native.df_parquet_recompress(dst, dst, :uncompressed)
+
+      assert {:ok, [{"a", ["uncompressed"], _}, {"b", ["uncompressed"], _}]} =
+               Native.df_parquet_column_info(dst)
+    end
+
+    @tag :tmp_dir
+    test "keeps the dictionary encoding of the categorical columns", %{tmp_dir: tmp_dir} do
+      src = Path.join(tmp_dir, "snappy.parquet")
+      dst = Path.join(tmp_dir, "zstd.parquet")
+      df = Explorer.DataFrame.new(a: Explorer.Series.from_list(["x", "y", "x"], dtype: :category))
+      :ok = Explorer.DataFrame.to_parquet!(df, src, compression: :snappy)
+
+      assert {:ok, _} = Native.df_parquet_recompress(src, dst, {:zstd, 3})
+      assert {:ok, [{"a", ["zstd"], encodings}]} = Native.df_parquet_column_info(dst)
+      assert "rle_dictionary" in encodings
+
+      assert Explorer.DataFrame.to_columns(Explorer.DataFrame.from_parquet!(dst)) ==
+               %{"a" => ["x", "y", "x"]}
+    end
+
+    @tag :tmp_dir
+    test "keeps the key-value metadata", %{tmp_dir: tmp_dir} do
+      # A single "a" column with the values 1, 2 and 3, and the
+      # key-value metadata %{"owner" => "explorer"} in the footer.
+      src = "test/support/key_value_metadata.parquet"
+      dst = Path.join(tmp_dir, "zstd.parquet")
+
+      assert {:ok, _} = Native.df_parquet_recompress(src, dst, {:zstd, 3})
+
+      contents = File.read!(dst)
+      assert :binary.match(contents, "owner") != :nomatch
+      assert :binary.match(contents, "explorer") != :nomatch
+
+      assert Explorer.DataFrame.to_columns(Explorer.DataFrame.from_parquet!(dst)) ==
+               %{"a" => [1, 2, 3]}
+    end
+  end
+
+  describe "df_parquet_verify/1" do
+    @tag :tmp_dir
+    test "finds the first page with a mismatching checksum", %{tmp_dir: tmp_dir} do
+      path = "test/support/page_checksums.parquet"
+      assert Native.df_parquet_verify(path) == {:ok, nil}
+
+      corrupt_path = Path.join(tmp_dir, "corrupt.parquet")
+      corrupt = :binary.replace(File.read!(path), <<3::little-64>>, <<4::little-64>>)
+      File.write!(corrupt_path, corrupt)
+
+      assert Native.df_parquet_verify(corrupt_path) == {:ok, {0, "a", 0}}
+    end
+
+    @tag :tmp_dir
+    test "accepts files without checksums", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "data.parquet")
+      df = Explorer.DataFrame.new(a: [1, 2, 3], b: ["x", "y", "z"])
+      :ok = Explorer.DataFrame.to_parquet!(df, path, compression: :zstd)
+
+      assert Native.df_parquet_verify(path) == {:ok, nil}
+    end
+
+    @tag :tmp_dir
+    test "fails for page headers that are nested too deeply", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "nested.parquet")
+      df = Explorer.DataFrame.new(a: Enum.to_list(1..1000))
+      :ok = Explorer.DataFrame.to_parquet!(df, path)
+
+      # The first page header starts right after the magic bytes. Each 0x1C
+      # byte is the header of a struct field that holds another struct.
+      <<magic::binary-size(4), _header::binary-size(200), rest::binary>> = File.read!(path)
+      File.write!(path, [magic, :binary.copy(<<0x1C>>, 200), rest])
+
+      assert {:error, message} = Native.df_parquet_verify(path)
+      assert message =~ "is nested more than 64 levels deep"
+    end
+  end
+
+  describe "df_preview/3" do
+    @tag :tmp_dir
+    test "reads the first rows and the dtypes", %{tmp_dir: tmp_dir} do
+      df = Explorer.DataFrame.new(a: [1, 2, 3, 4], b: ["x", "y", "z", "w"])
+      csv_path = Path.join(tmp_dir, "preview.csv")
+      parquet_path = Path.join(tmp_dir, "preview.parquet")
+
+      :ok = Explorer.DataFrame.to_csv(df, csv_path)
+      :ok = Explorer.DataFrame.to_parquet(df, parquet_path)
+
+      for {path, format} <- [{csv_path, "csv"}, {parquet_path, "parquet"}] do
+        assert {:ok, {preview, dtypes}} = Native.df_preview(path, format, 2)
+
+        assert Explorer.DataFrame.to_columns(Shared.create_dataframe(preview), atom_keys: true) ==
+                 %{a: [1, 2], b: ["x", "y"]}
+
+        assert dtypes == [{"a", "i64"}, {"b", "str"}]
+      end
+    end
+
+    @tag :tmp_dir
+    test "fails for an unknown format", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "preview.txt")
+      File.write!(path, "a\n1\n")
+
+      assert {:error, message} = Native.df_preview(path, "txt", 2)
+      assert message =~ "the format txt is not supported for previews"
+    end
+  end
+end